@@ -1,12 +1,21 @@
+use anyhow::bail;
 use anyhow::Result;
 use clap::App;
 use clap::AppSettings;
+use delphinus_host::host::db::overlay_tree_db;
+use delphinus_host::host::merkle_helper::state::root_to_u64s;
+use delphinus_host::host::merkle_helper::state::MerkleRoots;
+use delphinus_host::host::merkle_helper::state::MerkleState;
+use delphinus_host::host::merkle_helper::state::MERKLE_ROOT_WORDS;
 use delphinus_host::ExecutionArg as StandardArg;
-use delphinus_host::HostEnvConfig;
 use delphinus_host::StandardHostEnvBuilder as StandardEnvBuilder;
 use delphinus_zkwasm::circuits::config::MIN_K;
+use delphinus_zkwasm::foreign::wasm_input_helper::source::ChainedSource;
+use delphinus_zkwasm::foreign::wasm_input_helper::source::FileSource;
+use delphinus_zkwasm::foreign::wasm_input_helper::source::PrivateInputSource;
 use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
 use delphinus_zkwasm::runtime::host::default_env::ExecutionArg;
+use specs::manifest::EncodedInputs;
 
 use log::info;
 use std::cell::RefCell;
@@ -18,13 +27,23 @@ use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use crate::args::check_unproven_ops;
 use crate::args::HostMode;
 use crate::exec::exec_dry_run;
+use crate::exec::exec_dump_trace;
+use crate::exec::exec_estimate;
+use crate::exec::exec_push_merkle_state;
+use crate::exec::load_merkle_state_bundles;
+use crate::exec::merkle_state_of_bundles;
 
 use super::command::CommandBuilder;
+use super::exec::exec_aggregate_create_proof;
 use super::exec::exec_create_proof;
 use super::exec::exec_image_checksum;
 use super::exec::exec_setup;
+use super::exec::exec_solidity_aggregate_proof;
+use super::exec::exec_verify_aggregate_proof;
+use super::exec::exec_verify_bundle;
 use super::exec::exec_verify_proof;
 
 fn load_or_generate_output_path(
@@ -61,6 +80,46 @@ pub fn write_context_output(
     Ok(())
 }
 
+/// With `--state`, the first `MERKLE_ROOT_WORDS` public inputs are the merkle root which the
+/// execution starts from, the guest reads it by `wasm_input(1)` before `merkle_setroot`. The
+/// root is the final root output by the last proof of the state, the root of the first
+/// execution is supplied by `--public`.
+fn load_merkle_state(output_dir: &PathBuf, public_inputs: &mut Vec<u64>) -> Result<MerkleState> {
+    let merkle_state = merkle_state_of_bundles(&load_merkle_state_bundles(output_dir)?)?;
+
+    match merkle_state.latest_root() {
+        Some(root) => {
+            info!("Start from the merkle root {:?}", root);
+
+            public_inputs.splice(0..0, root_to_u64s(&root));
+        }
+        None => {
+            if public_inputs.len() < MERKLE_ROOT_WORDS {
+                bail!(
+                    "The initial merkle root is required as the first {} public inputs.",
+                    MERKLE_ROOT_WORDS
+                );
+            }
+        }
+    }
+
+    Ok(merkle_state)
+}
+
+/// Private inputs given by arguments followed by the inputs streamed from `private_file`.
+fn private_input_source(
+    private_inputs: Vec<u64>,
+    private_file: Option<&PathBuf>,
+) -> Box<dyn PrivateInputSource> {
+    match private_file {
+        Some(path) => Box::new(ChainedSource::new(vec![
+            private_inputs.into(),
+            Box::new(FileSource::new(path.clone())),
+        ])),
+        None => private_inputs.into(),
+    }
+}
+
 pub trait AppBuilder: CommandBuilder {
     const NAME: &'static str;
     const VERSION: &'static str;
@@ -78,13 +137,23 @@ pub trait AppBuilder: CommandBuilder {
             .arg(Self::function_name_arg())
             .arg(Self::phantom_functions_arg())
             .arg(Self::zkwasm_file_arg())
-            .arg(Self::host_mode_arg());
+            .arg(Self::host_mode_arg())
+            .arg(Self::host_ops_arg())
+            .arg(Self::host_config_arg())
+            .arg(Self::unsafe_unproven_ops_arg())
+            .arg(Self::tree_db_arg())
+            .arg(Self::provable_trap_arg());
 
         let app = Self::append_setup_subcommand(app);
         let app = Self::append_dry_run_subcommand(app);
+        let app = Self::append_estimate_subcommand(app);
+        let app = Self::append_dump_trace_subcommand(app);
         let app = Self::append_create_single_proof_subcommand(app);
         let app = Self::append_verify_single_proof_subcommand(app);
         let app = Self::append_image_checksum_subcommand(app);
+        let app = Self::append_create_aggregate_proof_subcommand(app);
+        let app = Self::append_verify_aggregate_verify_subcommand(app);
+        let app = Self::append_generate_solidity_verifier(app);
 
         app
     }
@@ -123,15 +192,29 @@ pub trait AppBuilder: CommandBuilder {
         fs::create_dir_all(&param_dir)?;
 
         let host_mode = Self::parse_host_mode(&top_matches);
+        let host_config = Self::parse_host_env_config(&top_matches)?;
+        let tree_db = Self::parse_tree_db(&top_matches)?;
+        let provable_trap = Self::parse_provable_trap_arg(&top_matches);
+
+        if let (HostMode::STANDARD, Some(("setup" | "single-prove" | "aggregate-prove", _))) =
+            (&host_mode, top_matches.subcommand())
+        {
+            check_unproven_ops(
+                &host_config,
+                Self::parse_unsafe_unproven_ops_arg(&top_matches),
+            )?;
+        }
 
         match top_matches.subcommand() {
-            Some(("setup", _)) => match host_mode {
+            Some(("setup", sub_matches)) => match host_mode {
                 HostMode::DEFAULT => exec_setup::<DefaultHostEnvBuilder>(
                     zkwasm_k,
                     Self::AGGREGATE_K,
                     Self::NAME,
                     wasm_binary,
                     phantom_functions,
+                    provable_trap,
+                    Self::parse_record_host_config_arg(&sub_matches),
                     (),
                     &output_dir,
                     &param_dir,
@@ -142,7 +225,9 @@ pub trait AppBuilder: CommandBuilder {
                     Self::NAME,
                     wasm_binary,
                     phantom_functions,
-                    HostEnvConfig::default(),
+                    provable_trap,
+                    Self::parse_record_host_config_arg(&sub_matches),
+                    host_config.clone(),
                     &output_dir,
                     &param_dir,
                 ),
@@ -159,18 +244,26 @@ pub trait AppBuilder: CommandBuilder {
                 HostMode::STANDARD => exec_image_checksum::<StandardEnvBuilder>(
                     zkwasm_k,
                     wasm_binary,
-                    HostEnvConfig::default(),
+                    host_config.clone(),
                     phantom_functions,
                     &output_dir,
                 ),
             },
 
             Some(("dry-run", sub_matches)) => {
-                let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
-                let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
-                let context_in: Vec<u64> = Self::parse_context_in_arg(&sub_matches);
+                let EncodedInputs {
+                    mut public_inputs,
+                    private_inputs,
+                    context_inputs: context_in,
+                } = Self::parse_single_inputs(&sub_matches)?;
+                let private_file = Self::parse_private_file_arg(&sub_matches);
                 let context_out_path: Option<PathBuf> =
                     Self::parse_context_out_path_arg(&sub_matches);
+                let profile_path: Option<PathBuf> = Self::parse_profile_path_arg(&sub_matches);
+                // The state is not advanced since the execution is not proved.
+                if Self::parse_stateful_arg(&sub_matches) {
+                    load_merkle_state(&output_dir, &mut public_inputs)?;
+                }
                 assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
 
                 let context_output = Arc::new(Mutex::new(vec![]));
@@ -181,9 +274,139 @@ pub trait AppBuilder: CommandBuilder {
                             zkwasm_k,
                             wasm_binary,
                             phantom_functions,
+                            profile_path,
                             ExecutionArg {
-                                public_inputs,
+                                public_inputs: public_inputs.clone(),
+                                private_inputs: private_input_source(
+                                    private_inputs.clone(),
+                                    private_file.as_ref(),
+                                ),
+                                context_inputs: context_in.clone(),
+                                context_outputs: context_output.clone(),
+                            },
+                            || ExecutionArg {
+                                public_inputs: public_inputs.clone(),
+                                private_inputs: private_input_source(
+                                    private_inputs.clone(),
+                                    private_file.as_ref(),
+                                ),
+                                context_inputs: context_in.clone(),
+                                context_outputs: Arc::new(Mutex::new(vec![])),
+                            },
+                            || (),
+                        )?;
+                    }
+                    HostMode::STANDARD => {
+                        exec_dry_run::<StandardEnvBuilder>(
+                            zkwasm_k,
+                            wasm_binary,
+                            phantom_functions,
+                            profile_path,
+                            StandardArg {
+                                public_inputs: public_inputs.clone(),
+                                private_inputs: private_input_source(
+                                    private_inputs.clone(),
+                                    private_file.as_ref(),
+                                ),
+                                context_inputs: context_in.clone(),
+                                context_outputs: context_output.clone(),
+                                indexed_witness: Rc::new(RefCell::new(HashMap::new())),
+                                tree_db: tree_db.clone(),
+                                merkle_roots: Rc::new(RefCell::new(MerkleRoots::default())),
+                            },
+                            || StandardArg {
+                                public_inputs: public_inputs.clone(),
+                                private_inputs: private_input_source(
+                                    private_inputs.clone(),
+                                    private_file.as_ref(),
+                                ),
+                                context_inputs: context_in.clone(),
+                                context_outputs: Arc::new(Mutex::new(vec![])),
+                                indexed_witness: Rc::new(RefCell::new(HashMap::new())),
+                                tree_db: overlay_tree_db(tree_db.clone()),
+                                merkle_roots: Rc::new(RefCell::new(MerkleRoots::default())),
+                            },
+                            || host_config.clone(),
+                        )?;
+                    }
+                };
+
+                write_context_output(&context_output.lock().unwrap(), context_out_path)?;
+                Ok(())
+            }
+
+            Some(("estimate", sub_matches)) => {
+                let EncodedInputs {
+                    public_inputs,
+                    private_inputs,
+                    context_inputs: context_in,
+                } = Self::parse_single_inputs(&sub_matches)?;
+                let private_file = Self::parse_private_file_arg(&sub_matches);
+                assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
+
+                match host_mode {
+                    HostMode::DEFAULT => exec_estimate::<DefaultHostEnvBuilder>(
+                        wasm_binary,
+                        phantom_functions,
+                        ExecutionArg {
+                            public_inputs,
+                            private_inputs: private_input_source(
+                                private_inputs,
+                                private_file.as_ref(),
+                            ),
+                            context_inputs: context_in,
+                            context_outputs: Arc::new(Mutex::new(vec![])),
+                        },
+                        (),
+                    ),
+                    HostMode::STANDARD => exec_estimate::<StandardEnvBuilder>(
+                        wasm_binary,
+                        phantom_functions,
+                        StandardArg {
+                            public_inputs,
+                            private_inputs: private_input_source(
                                 private_inputs,
+                                private_file.as_ref(),
+                            ),
+                            context_inputs: context_in,
+                            context_outputs: Arc::new(Mutex::new(vec![])),
+                            indexed_witness: Rc::new(RefCell::new(HashMap::new())),
+                            tree_db: tree_db.clone(),
+                            merkle_roots: Rc::new(RefCell::new(MerkleRoots::default())),
+                        },
+                        host_config.clone(),
+                    ),
+                }
+            }
+
+            Some(("dump-trace", sub_matches)) => {
+                let EncodedInputs {
+                    public_inputs,
+                    private_inputs,
+                    context_inputs: context_in,
+                } = Self::parse_single_inputs(&sub_matches)?;
+                let private_file = Self::parse_private_file_arg(&sub_matches);
+                let context_out_path: Option<PathBuf> =
+                    Self::parse_context_out_path_arg(&sub_matches);
+                let trace_path = Self::parse_trace_path_arg(&sub_matches)
+                    .unwrap_or_else(|| output_dir.join(format!("{}.trace", Self::NAME)));
+                assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
+
+                let context_output = Arc::new(Mutex::new(vec![]));
+
+                match host_mode {
+                    HostMode::DEFAULT => {
+                        exec_dump_trace::<DefaultHostEnvBuilder>(
+                            zkwasm_k,
+                            wasm_binary,
+                            phantom_functions,
+                            &trace_path,
+                            ExecutionArg {
+                                public_inputs,
+                                private_inputs: private_input_source(
+                                    private_inputs,
+                                    private_file.as_ref(),
+                                ),
                                 context_inputs: context_in,
                                 context_outputs: context_output.clone(),
                             },
@@ -191,19 +414,24 @@ pub trait AppBuilder: CommandBuilder {
                         )?;
                     }
                     HostMode::STANDARD => {
-                        exec_dry_run::<StandardEnvBuilder>(
+                        exec_dump_trace::<StandardEnvBuilder>(
                             zkwasm_k,
                             wasm_binary,
                             phantom_functions,
+                            &trace_path,
                             StandardArg {
                                 public_inputs,
-                                private_inputs,
+                                private_inputs: private_input_source(
+                                    private_inputs,
+                                    private_file.as_ref(),
+                                ),
                                 context_inputs: context_in,
                                 context_outputs: context_output.clone(),
                                 indexed_witness: Rc::new(RefCell::new(HashMap::new())),
-                                tree_db: None,
+                                tree_db: tree_db.clone(),
+                                merkle_roots: Rc::new(RefCell::new(MerkleRoots::default())),
                             },
-                            HostEnvConfig::default(),
+                            host_config.clone(),
                         )?;
                     }
                 };
@@ -213,13 +441,24 @@ pub trait AppBuilder: CommandBuilder {
             }
 
             Some(("single-prove", sub_matches)) => {
-                let public_inputs: Vec<u64> = Self::parse_single_public_arg(&sub_matches);
-                let private_inputs: Vec<u64> = Self::parse_single_private_arg(&sub_matches);
-                let context_in: Vec<u64> = Self::parse_context_in_arg(&sub_matches);
+                let EncodedInputs {
+                    mut public_inputs,
+                    private_inputs,
+                    context_inputs: context_in,
+                } = Self::parse_single_inputs(&sub_matches)?;
+                let private_file = Self::parse_private_file_arg(&sub_matches);
                 let context_out_path: Option<PathBuf> =
                     Self::parse_context_out_path_arg(&sub_matches);
+                let trace_path: Option<PathBuf> = Self::parse_trace_path_arg(&sub_matches);
+                let profile_path: Option<PathBuf> = Self::parse_profile_path_arg(&sub_matches);
+                let merkle_state = if Self::parse_stateful_arg(&sub_matches) {
+                    Some(load_merkle_state(&output_dir, &mut public_inputs)?)
+                } else {
+                    None
+                };
 
                 let context_out = Arc::new(Mutex::new(vec![]));
+                let merkle_roots = Rc::new(RefCell::new(MerkleRoots::default()));
 
                 assert!(public_inputs.len() <= Self::MAX_PUBLIC_INPUT_SIZE);
                 match host_mode {
@@ -231,9 +470,15 @@ pub trait AppBuilder: CommandBuilder {
                             phantom_functions,
                             &output_dir,
                             &param_dir,
+                            trace_path,
+                            profile_path,
+                            provable_trap,
                             ExecutionArg {
                                 public_inputs,
-                                private_inputs,
+                                private_inputs: private_input_source(
+                                    private_inputs,
+                                    private_file.as_ref(),
+                                ),
                                 context_inputs: context_in,
                                 context_outputs: context_out.clone(),
                             },
@@ -248,24 +493,163 @@ pub trait AppBuilder: CommandBuilder {
                             phantom_functions,
                             &output_dir,
                             &param_dir,
+                            trace_path,
+                            profile_path,
+                            provable_trap,
                             StandardArg {
                                 public_inputs,
-                                private_inputs,
+                                private_inputs: private_input_source(
+                                    private_inputs,
+                                    private_file.as_ref(),
+                                ),
                                 context_inputs: context_in,
                                 context_outputs: context_out.clone(),
                                 indexed_witness: Rc::new(RefCell::new(HashMap::new())),
-                                tree_db: None,
+                                tree_db: tree_db.clone(),
+                                merkle_roots: merkle_roots.clone(),
                             },
-                            HostEnvConfig::default(),
+                            host_config.clone(),
                         )?;
                     }
                 };
 
                 write_context_output(&context_out.lock().unwrap(), context_out_path)?;
 
+                if let Some(mut merkle_state) = merkle_state {
+                    exec_push_merkle_state(
+                        Self::NAME,
+                        &output_dir,
+                        &mut merkle_state,
+                        &merkle_roots.borrow(),
+                    )?;
+                }
+
                 Ok(())
             }
-            Some(("single-verify", _)) => exec_verify_proof(Self::NAME, &output_dir, &param_dir),
+            Some(("single-verify", sub_matches)) => {
+                match Self::parse_proof_bundle_arg(&sub_matches) {
+                    Some(bundle_path) => match host_mode {
+                        HostMode::DEFAULT => exec_verify_bundle::<DefaultHostEnvBuilder>(
+                            Self::NAME,
+                            zkwasm_k,
+                            wasm_binary,
+                            phantom_functions,
+                            (),
+                            &bundle_path,
+                            &param_dir,
+                        ),
+                        HostMode::STANDARD => exec_verify_bundle::<StandardEnvBuilder>(
+                            Self::NAME,
+                            zkwasm_k,
+                            wasm_binary,
+                            phantom_functions,
+                            host_config.clone(),
+                            &bundle_path,
+                            &param_dir,
+                        ),
+                    },
+                    None => exec_verify_proof(Self::NAME, &output_dir, &param_dir),
+                }
+            }
+            Some(("aggregate-prove", sub_matches)) => {
+                let public_inputs: Vec<Vec<u64>> = Self::parse_aggregate_public_args(&sub_matches)?;
+                let private_inputs: Vec<Vec<u64>> =
+                    Self::parse_aggregate_private_args(&sub_matches)?;
+
+                if public_inputs.len() != Self::N_PROOFS || private_inputs.len() != Self::N_PROOFS {
+                    bail!(
+                        "{} public and {} private inputs are supplied, but the aggregate circuit verifies {} proofs.",
+                        public_inputs.len(),
+                        private_inputs.len(),
+                        Self::N_PROOFS
+                    );
+                }
+                for public_inputs in &public_inputs {
+                    if public_inputs.len() > Self::MAX_PUBLIC_INPUT_SIZE {
+                        bail!(
+                            "{} public inputs are supplied, at most {} are allowed.",
+                            public_inputs.len(),
+                            Self::MAX_PUBLIC_INPUT_SIZE
+                        );
+                    }
+                }
+
+                let inputs = public_inputs.into_iter().zip(private_inputs.into_iter());
+
+                match host_mode {
+                    HostMode::DEFAULT => exec_aggregate_create_proof::<DefaultHostEnvBuilder>(
+                        zkwasm_k,
+                        Self::AGGREGATE_K,
+                        Self::NAME,
+                        wasm_binary,
+                        phantom_functions,
+                        provable_trap,
+                        &output_dir,
+                        &param_dir,
+                        inputs
+                            .map(|(public_inputs, private_inputs)| ExecutionArg {
+                                public_inputs,
+                                private_inputs: private_inputs.into(),
+                                context_inputs: vec![],
+                                context_outputs: Arc::new(Mutex::new(vec![])),
+                            })
+                            .collect(),
+                        || (),
+                    ),
+                    HostMode::STANDARD => exec_aggregate_create_proof::<StandardEnvBuilder>(
+                        zkwasm_k,
+                        Self::AGGREGATE_K,
+                        Self::NAME,
+                        wasm_binary,
+                        phantom_functions,
+                        provable_trap,
+                        &output_dir,
+                        &param_dir,
+                        inputs
+                            .map(|(public_inputs, private_inputs)| StandardArg {
+                                public_inputs,
+                                private_inputs: private_inputs.into(),
+                                context_inputs: vec![],
+                                context_outputs: Arc::new(Mutex::new(vec![])),
+                                indexed_witness: Rc::new(RefCell::new(HashMap::new())),
+                                tree_db: tree_db.clone(),
+                                merkle_roots: Rc::new(RefCell::new(MerkleRoots::default())),
+                            })
+                            .collect(),
+                        || host_config.clone(),
+                    ),
+                }
+            }
+            Some(("aggregate-verify", sub_matches)) => {
+                let proof_path: PathBuf = Self::parse_proof_path_arg(&sub_matches);
+                let instances_path: PathBuf = Self::parse_aggregate_instance(&sub_matches);
+
+                exec_verify_aggregate_proof(
+                    Self::AGGREGATE_K,
+                    &param_dir,
+                    &proof_path,
+                    &instances_path,
+                    Self::N_PROOFS,
+                )
+            }
+            Some(("solidity-aggregate-verifier", sub_matches)) => {
+                let proof_path: PathBuf = Self::parse_proof_path_arg(&sub_matches);
+                let instances_path: PathBuf = Self::parse_aggregate_instance(&sub_matches);
+                let sol_path: PathBuf = Self::parse_sol_dir_arg(&sub_matches);
+                let aux_only: bool = Self::parse_auxonly(&sub_matches);
+
+                exec_solidity_aggregate_proof(
+                    zkwasm_k,
+                    Self::AGGREGATE_K,
+                    &output_dir,
+                    &param_dir,
+                    &proof_path,
+                    &sol_path,
+                    &instances_path,
+                    Self::N_PROOFS,
+                    aux_only,
+                )
+            }
             Some((_, _)) => todo!(),
             None => todo!(),
         }
@@ -1,10 +1,59 @@
+use anyhow::bail;
+use anyhow::Result;
 use clap::arg;
 use clap::value_parser;
 use clap::Arg;
 use clap::ArgAction;
 use clap::ArgMatches;
+use delphinus_host::host::db::open_tree_db;
+use delphinus_host::HostEnvConfig;
+use delphinus_host::HostOp;
+use log::warn;
 use specs::args::parse_args;
+use specs::manifest::EncodedInputs;
+use specs::manifest::InputManifest;
+use std::cell::RefCell;
+use std::fs::File;
 use std::path::PathBuf;
+use std::rc::Rc;
+use std::str::FromStr;
+use zkwasm_host_circuits::host::db::TreeDB;
+
+/// The host config of the standard host, read from `host_config` if supplied, otherwise made
+/// of `ops` or the default ops if `ops` is empty.
+pub fn host_env_config(host_config: Option<&PathBuf>, ops: Vec<HostOp>) -> Result<HostEnvConfig> {
+    if let Some(path) = host_config {
+        return Ok(serde_json::from_reader(File::open(path)?)?);
+    }
+
+    if ops.is_empty() {
+        Ok(HostEnvConfig::default())
+    } else {
+        Ok(HostEnvConfig { ops })
+    }
+}
+
+/// Rejects the ops without a host circuit unless `allow_unproven_ops` is set, since a proof of
+/// an image calling them doesn't constrain their results.
+pub fn check_unproven_ops(config: &HostEnvConfig, allow_unproven_ops: bool) -> Result<()> {
+    let unproven_ops = config.unproven_ops();
+
+    if !unproven_ops.is_empty() {
+        if !allow_unproven_ops {
+            bail!(
+                "Host ops {:?} are not proven by a host circuit, pass --unsafe-unproven-ops to set up or prove with them.",
+                unproven_ops
+            );
+        }
+
+        warn!(
+            "Host ops {:?} are not proven by a host circuit, their results are not constrained by the proof.",
+            unproven_ops
+        );
+    }
+
+    Ok(())
+}
 
 #[derive(clap::ArgEnum, Clone, Debug)]
 pub enum HostMode {
@@ -66,6 +115,66 @@ pub trait ArgBuilder {
             .map_or(HostMode::DEFAULT, |v| v.clone())
     }
 
+    fn host_ops_arg<'a>() -> Arg<'a> {
+        Arg::new("host-op")
+            .long("host-op")
+            .value_parser(|op: &str| HostOp::from_str(op).map_err(|e| e.to_string()))
+            .action(ArgAction::Append)
+            .help("Specify host ops of the standard host, e.g. poseidonhash, keccakhash or sha256.\nsha256 has no host circuit, setup and proving with it require --unsafe-unproven-ops.\nThe default ops are used if not supplied.")
+            .min_values(0)
+    }
+    fn host_config_arg<'a>() -> Arg<'a> {
+        arg!(
+            --"host-config" [HOST_CONFIG] "Path of the JSON host config of the standard host, e.g. {\"ops\":[\"POSEIDONHASH\",\"SHA256\"]}."
+        )
+        .value_parser(value_parser!(PathBuf))
+        .conflicts_with("host-op")
+    }
+
+    fn parse_host_env_config(matches: &ArgMatches) -> Result<HostEnvConfig> {
+        host_env_config(
+            matches.get_one::<PathBuf>("host-config"),
+            matches
+                .get_many::<HostOp>("host-op")
+                .unwrap_or_default()
+                .copied()
+                .collect(),
+        )
+    }
+
+    fn unsafe_unproven_ops_arg<'a>() -> Arg<'a> {
+        arg!(
+            --"unsafe-unproven-ops" "Allow setup and proving with host ops which have no host circuit, e.g. sha256."
+        )
+        .takes_value(false)
+    }
+    fn parse_unsafe_unproven_ops_arg(matches: &ArgMatches) -> bool {
+        matches.contains_id("unsafe-unproven-ops")
+    }
+
+    fn record_host_config_arg<'a>() -> Arg<'a> {
+        arg!(
+            --"record-host-config" "Record the host config of a param path set up before host configs were recorded."
+        )
+        .takes_value(false)
+    }
+    fn parse_record_host_config_arg(matches: &ArgMatches) -> bool {
+        matches.contains_id("record-host-config")
+    }
+
+    fn tree_db_arg<'a>() -> Arg<'a> {
+        arg!(
+            --db [DB] "Tree db of the merkle and data cache host ops of the standard host, `memory` or the path of a file db.\nThe Mongo db is used if not supplied."
+        )
+        .value_parser(value_parser!(String))
+    }
+    fn parse_tree_db(matches: &ArgMatches) -> Result<Option<Rc<RefCell<dyn TreeDB>>>> {
+        matches
+            .get_one::<String>("db")
+            .map(|db| open_tree_db(db))
+            .transpose()
+    }
+
     fn phantom_functions_arg<'a>() -> Arg<'a> {
         Arg::new("phantom")
             .long("phantom")
@@ -109,6 +218,16 @@ pub trait ArgBuilder {
             .clone()
     }
 
+    fn proof_bundle_arg<'a>() -> Arg<'a> {
+        arg!(
+            --bundle [PROOF_BUNDLE] "Path of the proof bundle written by single-prove."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_proof_bundle_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("bundle").cloned()
+    }
+
     fn sol_dir_arg<'a>() -> Arg<'a> {
         arg!(
             -s --sol_dir [SOL_DIRECTORY] "Path of solidity directory."
@@ -130,16 +249,44 @@ pub trait ArgBuilder {
     }
 
     fn single_public_arg<'a>() -> Arg<'a>;
-    fn parse_single_public_arg(matches: &ArgMatches) -> Vec<u64>;
+    fn parse_single_public_arg(matches: &ArgMatches) -> Result<Vec<u64>>;
 
     fn aggregate_public_args<'a>() -> Arg<'a>;
-    fn parse_aggregate_public_args(matches: &ArgMatches) -> Vec<Vec<u64>>;
+    fn parse_aggregate_public_args(matches: &ArgMatches) -> Result<Vec<Vec<u64>>>;
 
     fn single_private_arg<'a>() -> Arg<'a>;
-    fn parse_single_private_arg(matches: &ArgMatches) -> Vec<u64>;
+    fn parse_single_private_arg(matches: &ArgMatches) -> Result<Vec<u64>>;
 
     fn aggregate_private_args<'a>() -> Arg<'a>;
-    fn parse_aggregate_private_args(matches: &ArgMatches) -> Vec<Vec<u64>>;
+    fn parse_aggregate_private_args(matches: &ArgMatches) -> Result<Vec<Vec<u64>>>;
+
+    fn input_manifest_arg<'a>() -> Arg<'a> {
+        arg!(
+            --inputs [INPUT_MANIFEST] "Path of the JSON or TOML manifest of the public, private and context inputs."
+        )
+        .value_parser(value_parser!(PathBuf))
+        .conflicts_with_all(&["public", "private", "ctxin"])
+    }
+    fn private_file_arg<'a>() -> Arg<'a> {
+        arg!(
+            --"private-file" [PRIVATE_FILE] "Path of a file read on demand as private inputs after the other private inputs, in 8-byte big-endian chunks."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_private_file_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("private-file").cloned()
+    }
+
+    fn parse_single_inputs(matches: &ArgMatches) -> Result<EncodedInputs> {
+        match matches.get_one::<PathBuf>("inputs") {
+            Some(path) => Ok(InputManifest::load(path)?.encode()?),
+            None => Ok(EncodedInputs {
+                public_inputs: Self::parse_single_public_arg(matches)?,
+                private_inputs: Self::parse_single_private_arg(matches)?,
+                context_inputs: Self::parse_context_in_arg(matches)?,
+            }),
+        }
+    }
 
     fn single_instance_path_arg<'a>() -> Arg<'a> {
         arg!(
@@ -162,14 +309,14 @@ pub trait ArgBuilder {
         .help("Context arguments of your wasm program arguments of format value:type where type=i64|bytes|bytes-packed")
         .min_values(0)
     }
-    fn parse_context_in_arg(matches: &ArgMatches) -> Vec<u64> {
+    fn parse_context_in_arg(matches: &ArgMatches) -> Result<Vec<u64>> {
         let inputs: Vec<&str> = matches
             .get_many("ctxin")
             .unwrap_or_default()
             .map(|v: &String| v.as_str())
             .collect();
 
-        parse_args(inputs.into())
+        Ok(parse_args(inputs.into())?)
     }
 
     fn context_out_path_arg<'a>() -> Arg<'a> {
@@ -182,6 +329,46 @@ pub trait ArgBuilder {
         matches.get_one::<PathBuf>("ctxout").cloned()
     }
 
+    fn trace_path_arg<'a>() -> Arg<'a> {
+        arg!(
+            --trace [TRACE_PATH] "Path of the binary execution trace."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_trace_path_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("trace").cloned()
+    }
+
+    fn profile_path_arg<'a>() -> Arg<'a> {
+        arg!(
+            --profile [PROFILE_PATH] "Path of the folded call stacks, a JSON report is written next to it."
+        )
+        .value_parser(value_parser!(PathBuf))
+    }
+    fn parse_profile_path_arg(matches: &ArgMatches) -> Option<PathBuf> {
+        matches.get_one::<PathBuf>("profile").cloned()
+    }
+
+    fn provable_trap_arg<'a>() -> Arg<'a> {
+        arg!(
+            --provable_trap "Prove the trap outcome instead of failing when the execution traps, setup and proving must agree on it."
+        )
+        .takes_value(false)
+    }
+    fn parse_provable_trap_arg(matches: &ArgMatches) -> bool {
+        matches.contains_id("provable_trap")
+    }
+
+    fn stateful_arg<'a>() -> Arg<'a> {
+        arg!(
+            --state "Start from the final merkle root output by the previous single-prove recorded in the output path."
+        )
+        .takes_value(false)
+    }
+    fn parse_stateful_arg(matches: &ArgMatches) -> bool {
+        matches.contains_id("state")
+    }
+
     fn instances_path_arg<'a>() -> Arg<'a> {
         arg!(
             -i --instances <AGGREGATE_INSTANCE_PATH> "Path of aggregate instances."
@@ -195,8 +382,6 @@ pub trait ArgBuilder {
             .clone()
     }
     fn parse_auxonly(matches: &ArgMatches) -> bool {
-        matches
-            .get_many::<String>("auxonly")
-            .map_or(false, |_| true)
+        matches.contains_id("auxonly")
     }
 }
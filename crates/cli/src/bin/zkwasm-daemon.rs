@@ -0,0 +1,88 @@
+use anyhow::Result;
+use clap::arg;
+use clap::value_parser;
+use clap::App;
+use clap::ArgAction;
+use delphinus_cli::args::check_unproven_ops;
+use delphinus_cli::args::host_env_config;
+use delphinus_cli::args::HostMode;
+use delphinus_cli::daemon::Daemon;
+use delphinus_cli::daemon::DaemonConfig;
+use delphinus_host::HostOp;
+use delphinus_zkwasm::circuits::config::MIN_K;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// Serves the proving daemon, see `delphinus_cli::daemon` for the API.
+fn main() -> Result<()> {
+    env_logger::init();
+
+    let matches = App::new("zkwasm-daemon")
+        .arg(arg!(-k [K] "Circuit Size K").value_parser(value_parser!(u32)))
+        .arg(
+            arg!(--"aggregate-k" [AGGREGATE_K] "Circuit Size K of the aggregate circuit")
+                .value_parser(value_parser!(u32)),
+        )
+        .arg(
+            arg!(--dir <DIR> "Path of the images, params and jobs of the daemon.")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--socket [SOCKET] "Path of the Unix socket, <DIR>/zkwasm.sock if not supplied.")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--host [HOST] "Specify host functions set.").value_parser(value_parser!(HostMode)),
+        )
+        .arg(
+            arg!(--"host-op" [HOST_OP] "Specify host ops of the standard host, e.g. poseidonhash, keccakhash or sha256.")
+                .value_parser(|op: &str| HostOp::from_str(op).map_err(|e| e.to_string()))
+                .action(ArgAction::Append),
+        )
+        .arg(
+            arg!(--"host-config" [HOST_CONFIG] "Path of the JSON host config of the standard host.")
+                .value_parser(value_parser!(PathBuf))
+                .conflicts_with("host-op"),
+        )
+        .arg(arg!(--"unsafe-unproven-ops" "Allow host ops which have no host circuit, e.g. sha256."))
+        .arg(
+            arg!(--db [DB] "Tree db of the standard host, `memory` or the path of a file db.\nThe Mongo db is used if not supplied.")
+                .value_parser(value_parser!(String)),
+        )
+        .get_matches();
+
+    let dir = matches.get_one::<PathBuf>("dir").unwrap().clone();
+    let socket = matches
+        .get_one::<PathBuf>("socket")
+        .cloned()
+        .unwrap_or_else(|| dir.join("zkwasm.sock"));
+
+    let host_config = host_env_config(
+        matches.get_one::<PathBuf>("host-config"),
+        matches
+            .get_many::<HostOp>("host-op")
+            .unwrap_or_default()
+            .copied()
+            .collect(),
+    )?;
+    let host_mode = matches
+        .get_one::<HostMode>("host")
+        .cloned()
+        .unwrap_or(HostMode::DEFAULT);
+
+    // The daemon sets up and proves with the host config.
+    if let HostMode::STANDARD = host_mode {
+        check_unproven_ops(&host_config, matches.contains_id("unsafe-unproven-ops"))?;
+    }
+
+    let daemon = Daemon::start(DaemonConfig {
+        dir,
+        zkwasm_k: matches.get_one::<u32>("K").cloned().unwrap_or(MIN_K),
+        aggregate_k: matches.get_one::<u32>("aggregate-k").cloned().unwrap_or(22),
+        host_mode,
+        host_config,
+        tree_db: matches.get_one::<String>("db").cloned(),
+    })?;
+
+    daemon.serve(&socket)
+}
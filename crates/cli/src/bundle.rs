@@ -0,0 +1,187 @@
+use anyhow::bail;
+use anyhow::Result;
+use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::group::ff::PrimeField;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::File;
+use std::path::Path;
+
+/// Version of the bundle format, bundles of other versions are rejected.
+pub const BUNDLE_VERSION: u32 = 2;
+
+pub const POSEIDON_HASH: &'static str = "poseidon";
+
+/// A proof of a single execution with everything required to verify it against an image.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProofBundle {
+    pub version: u32,
+    /// Version of the cli which created the proof
+    pub tool_version: String,
+    pub wasm_md5: String,
+    pub k: u32,
+    /// Commitment of the image table, see `checksum`
+    pub image_checksum: String,
+    /// Hex encoded SHA-256 of the verifying key written by setup
+    pub vkey_hash: String,
+    pub host_config: Value,
+    /// Hash of the transcript of the proof
+    pub hash_type: String,
+    /// Hex encoded little-endian representation of each instance
+    pub instances: Vec<String>,
+    /// Hex encoded transcript of the proof
+    pub proof: String,
+}
+
+impl ProofBundle {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let bundle: ProofBundle = serde_json::from_reader(File::open(path)?)?;
+
+        if bundle.version != BUNDLE_VERSION {
+            bail!(
+                "Unsupported proof bundle version {}, expected {}.",
+                bundle.version,
+                BUNDLE_VERSION
+            );
+        }
+
+        Ok(bundle)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        serde_json::to_writer_pretty(File::create(path)?, self)?;
+
+        Ok(())
+    }
+
+    pub fn encode_instances(instances: &Vec<Fr>) -> Vec<String> {
+        instances
+            .iter()
+            .map(|instance| hex::encode(instance.to_repr()))
+            .collect()
+    }
+
+    pub fn decode_instances(&self) -> Result<Vec<Fr>> {
+        self.instances
+            .iter()
+            .map(|instance| {
+                let mut repr = <Fr as PrimeField>::Repr::default();
+                hex::decode_to_slice(instance, repr.as_mut())?;
+
+                match Option::from(Fr::from_repr(repr)) {
+                    Some(instance) => Ok(instance),
+                    None => bail!("Instance {} is not a field element.", instance),
+                }
+            })
+            .collect()
+    }
+
+    /// Decodes the instances built from `u64` public inputs and outputs.
+    pub fn decode_u64_instances(&self) -> Result<Vec<u64>> {
+        self.decode_instances()?
+            .iter()
+            .map(|instance| {
+                let repr = instance.to_repr();
+
+                if repr.as_ref()[8..].iter().any(|byte| *byte != 0) {
+                    bail!("Instance {:?} is not a u64 value.", instance);
+                }
+
+                Ok(u64::from_le_bytes(repr.as_ref()[..8].try_into().unwrap()))
+            })
+            .collect()
+    }
+
+    pub fn decode_proof(&self) -> Result<Vec<u8>> {
+        Ok(hex::decode(&self.proof)?)
+    }
+
+    /// Rejects the bundle if it is created for another image, circuit size or hash.
+    pub fn check_image(&self, wasm_md5: &str, k: u32, image_checksum: &str) -> Result<()> {
+        if self.wasm_md5 != wasm_md5 {
+            bail!(
+                "The proof is created for the image {}, but the image is {}.",
+                self.wasm_md5,
+                wasm_md5
+            );
+        }
+
+        if self.k != k {
+            bail!("The proof is created with K = {}, but K is {}.", self.k, k);
+        }
+
+        if self.image_checksum != image_checksum {
+            bail!(
+                "The image checksum {} of the proof does not match the checksum {} of the image.",
+                self.image_checksum,
+                image_checksum
+            );
+        }
+
+        if self.hash_type != POSEIDON_HASH {
+            bail!("Unsupported hash type {} of the proof.", self.hash_type);
+        }
+
+        Ok(())
+    }
+
+    /// Rejects the bundle if it is created with another verifying key.
+    pub fn check_vkey(&self, vkey_hash: &str) -> Result<()> {
+        if self.vkey_hash != vkey_hash {
+            bail!(
+                "The proof is created with the vkey {}, but the vkey is {}.",
+                self.vkey_hash,
+                vkey_hash
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bundle(instances: &Vec<Fr>) -> ProofBundle {
+        ProofBundle {
+            version: BUNDLE_VERSION,
+            tool_version: "0.1.0".to_string(),
+            wasm_md5: "C8E2A3".to_string(),
+            k: 18,
+            image_checksum: "checksum".to_string(),
+            vkey_hash: "vkey".to_string(),
+            host_config: Value::Null,
+            hash_type: POSEIDON_HASH.to_string(),
+            instances: ProofBundle::encode_instances(instances),
+            proof: "00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_instances() {
+        let bundle = bundle(&vec![
+            Fr::from(1),
+            Fr::from(u64::MAX),
+            Fr::from(0) - Fr::from(1),
+        ]);
+
+        assert_eq!(
+            bundle.decode_instances().unwrap(),
+            vec![Fr::from(1), Fr::from(u64::MAX), Fr::from(0) - Fr::from(1)]
+        );
+        assert!(bundle.decode_u64_instances().is_err());
+    }
+
+    #[test]
+    fn test_check_bundle() {
+        let bundle = bundle(&vec![Fr::from(1)]);
+
+        assert!(bundle.check_image("C8E2A3", 18, "checksum").is_ok());
+        assert!(bundle.check_image("C8E2A3", 19, "checksum").is_err());
+        assert!(bundle.check_image("C8E2A3", 18, "other").is_err());
+        assert!(bundle.check_vkey("vkey").is_ok());
+        assert!(bundle.check_vkey("other").is_err());
+    }
+}
@@ -5,7 +5,7 @@ use super::args::ArgBuilder;
 
 pub trait CommandBuilder: ArgBuilder {
     fn append_setup_subcommand(app: App) -> App {
-        let command = Command::new("setup");
+        let command = Command::new("setup").arg(Self::record_host_config_arg());
 
         app.subcommand(command)
     }
@@ -21,7 +21,35 @@ pub trait CommandBuilder: ArgBuilder {
             .arg(Self::single_public_arg())
             .arg(Self::single_private_arg())
             .arg(Self::context_in_arg())
-            .arg(Self::context_out_path_arg());
+            .arg(Self::input_manifest_arg())
+            .arg(Self::private_file_arg())
+            .arg(Self::context_out_path_arg())
+            .arg(Self::profile_path_arg())
+            .arg(Self::stateful_arg());
+
+        app.subcommand(command)
+    }
+
+    fn append_estimate_subcommand(app: App) -> App {
+        let command = Command::new("estimate")
+            .arg(Self::single_public_arg())
+            .arg(Self::single_private_arg())
+            .arg(Self::context_in_arg())
+            .arg(Self::input_manifest_arg())
+            .arg(Self::private_file_arg());
+
+        app.subcommand(command)
+    }
+
+    fn append_dump_trace_subcommand(app: App) -> App {
+        let command = Command::new("dump-trace")
+            .arg(Self::single_public_arg())
+            .arg(Self::single_private_arg())
+            .arg(Self::context_in_arg())
+            .arg(Self::input_manifest_arg())
+            .arg(Self::private_file_arg())
+            .arg(Self::context_out_path_arg())
+            .arg(Self::trace_path_arg());
 
         app.subcommand(command)
     }
@@ -31,13 +59,27 @@ pub trait CommandBuilder: ArgBuilder {
             .arg(Self::single_public_arg())
             .arg(Self::single_private_arg())
             .arg(Self::context_in_arg())
-            .arg(Self::context_out_path_arg());
+            .arg(Self::input_manifest_arg())
+            .arg(Self::private_file_arg())
+            .arg(Self::context_out_path_arg())
+            // The inputs and outputs of the execution are recorded in the trace.
+            .arg(Self::trace_path_arg().conflicts_with_all(&[
+                "public",
+                "private",
+                "ctxin",
+                "inputs",
+                "private-file",
+                "ctxout",
+                "state",
+            ]))
+            .arg(Self::profile_path_arg())
+            .arg(Self::stateful_arg());
 
         app.subcommand(command)
     }
 
     fn append_verify_single_proof_subcommand(app: App) -> App {
-        let command = Command::new("single-verify");
+        let command = Command::new("single-verify").arg(Self::proof_bundle_arg());
         app.subcommand(command)
     }
 
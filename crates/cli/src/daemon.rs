@@ -0,0 +1,609 @@
+//! A proving daemon serving a local API over a Unix socket.
+//!
+//! Each request and its response are a line of JSON, e.g.
+//!
+//! ```text
+//! {"method": "submit-image", "wasm": "0061736d01000000..."}
+//! {"ok": {"image": "C8E2A3..."}}
+//! {"method": "prove", "image": "C8E2A3...", "inputs": {"public": [{"type": "u64", "value": 1}]}}
+//! {"ok": {"job": 3}}
+//! ```
+//!
+//! Jobs are executed one by one in submission order. The daemon directory contains
+//! - `images/<md5>/image.wasm` and `images/<md5>/phantom.json`, the submitted images,
+//! - `images/<md5>/param`, the param path of the image written by setup,
+//! - `jobs/<id>/job.json`, the status of a job, `jobs/<id>` is the output path of the job,
+//! - `jobs/<id>/zkwasm.bundle.json`, the proof bundle created by a prove job.
+//!
+//! The proving key of the last proved image is kept by the worker, so that proving the same
+//! image repeatedly doesn't compile the image and generate the key again.
+
+use anyhow::anyhow;
+use anyhow::bail;
+use anyhow::Result;
+use delphinus_host::host::db::open_tree_db;
+use delphinus_host::host::db::overlay_tree_db;
+use delphinus_host::ExecutionArg as StandardArg;
+use delphinus_host::HostEnvConfig;
+use delphinus_host::StandardHostEnvBuilder;
+use delphinus_zkwasm::loader::session::ZkWasmSession;
+use delphinus_zkwasm::loader::ZkWasmLoader;
+use delphinus_zkwasm::runtime::host::default_env::DefaultHostEnvBuilder;
+use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use halo2_proofs::pairing::bn256::Bn256;
+use halo2aggregator_s::circuits::utils::load_or_build_unsafe_params;
+use log::info;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+use specs::manifest::EncodedInputs;
+use specs::manifest::InputManifest;
+use std::fmt::Debug;
+use std::fs;
+use std::fs::File;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::os::unix::net::UnixListener;
+use std::os::unix::net::UnixStream;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::channel;
+use std::sync::mpsc::Receiver;
+use std::sync::mpsc::Sender;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use crate::args::HostMode;
+use crate::bundle::ProofBundle;
+use crate::bundle::BUNDLE_VERSION;
+use crate::bundle::POSEIDON_HASH;
+use crate::exec::check_host_config;
+use crate::exec::exec_dry_run;
+use crate::exec::exec_setup;
+use crate::exec::vkey_hash;
+
+const NAME: &'static str = "zkwasm";
+
+const IMAGE: &'static str = "image.wasm";
+const PHANTOM_FUNCTIONS: &'static str = "phantom.json";
+const JOB: &'static str = "job.json";
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "method", rename_all = "kebab-case")]
+pub enum Request {
+    /// Stores the hex encoded wasm image, the md5 of the image identifies it in other requests.
+    SubmitImage {
+        wasm: String,
+        #[serde(default)]
+        phantom_functions: Vec<String>,
+    },
+    Setup {
+        image: String,
+    },
+    DryRun {
+        image: String,
+        inputs: InputManifest,
+    },
+    Prove {
+        image: String,
+        inputs: InputManifest,
+    },
+    Status {
+        job: u64,
+    },
+    /// Returns the output path and the proof bundle created by a finished job.
+    FetchProof {
+        job: u64,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Task {
+    Setup,
+    DryRun(EncodedInputs),
+    Prove(EncodedInputs),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "kebab-case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed { reason: String },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub id: u64,
+    pub image: String,
+    pub task: Task,
+    pub status: JobStatus,
+}
+
+pub struct DaemonConfig {
+    pub dir: PathBuf,
+    pub zkwasm_k: u32,
+    pub aggregate_k: u32,
+    pub host_mode: HostMode,
+    /// Host config of the standard host
+    pub host_config: HostEnvConfig,
+    /// Tree db of the standard host, see `open_tree_db`
+    pub tree_db: Option<String>,
+}
+
+/// The proving key of the last proved image, with the image checksum and the vkey hash
+/// written to the bundles of its proofs.
+struct ImageSession<Builder: HostEnvBuilder> {
+    image: String,
+    image_checksum: String,
+    vkey_hash: String,
+    session: ZkWasmSession<Bn256, Builder::Arg, Builder>,
+}
+
+pub struct Daemon {
+    config: DaemonConfig,
+    next_job: AtomicU64,
+    queue: Mutex<Sender<u64>>,
+}
+
+impl Daemon {
+    /// Opens the daemon directory and starts the worker. Queued jobs of the previous run are
+    /// enqueued again, running jobs are marked as failed since they were interrupted.
+    pub fn start(config: DaemonConfig) -> Result<Arc<Self>> {
+        fs::create_dir_all(config.dir.join("images"))?;
+        fs::create_dir_all(config.dir.join("jobs"))?;
+
+        let (sender, receiver) = channel();
+        let daemon = Arc::new(Daemon {
+            config,
+            next_job: AtomicU64::new(0),
+            queue: Mutex::new(sender),
+        });
+
+        let mut jobs = vec![];
+        for entry in fs::read_dir(daemon.config.dir.join("jobs"))? {
+            let path = entry?.path().join(JOB);
+            if path.exists() {
+                jobs.push(serde_json::from_reader::<_, Job>(File::open(path)?)?);
+            }
+        }
+        jobs.sort_by_key(|job| job.id);
+
+        for mut job in jobs {
+            daemon.next_job.fetch_max(job.id + 1, Ordering::SeqCst);
+
+            match job.status {
+                JobStatus::Queued => daemon.enqueue(job.id)?,
+                JobStatus::Running => {
+                    job.status = JobStatus::Failed {
+                        reason: "the daemon stopped while running the job".to_string(),
+                    };
+                    daemon.save_job(&job)?;
+                }
+                JobStatus::Done | JobStatus::Failed { .. } => (),
+            }
+        }
+
+        let worker = daemon.clone();
+        std::thread::spawn(move || worker.work(receiver));
+
+        Ok(daemon)
+    }
+
+    /// Serves requests on the Unix socket at `socket` until the listener fails.
+    pub fn serve(self: Arc<Self>, socket: &PathBuf) -> Result<()> {
+        if socket.exists() {
+            fs::remove_file(socket)?;
+        }
+        let listener = UnixListener::bind(socket)?;
+        info!("Listening on {:?}", socket);
+
+        for stream in listener.incoming() {
+            let daemon = self.clone();
+            let stream = stream?;
+
+            std::thread::spawn(move || {
+                if let Err(e) = daemon.handle_connection(stream) {
+                    warn!("Connection closed: {}", e);
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    fn handle_connection(&self, stream: UnixStream) -> Result<()> {
+        let mut writer = stream.try_clone()?;
+
+        for line in BufReader::new(stream).lines() {
+            let response = match serde_json::from_str::<Request>(&line?) {
+                Ok(request) => self.handle(request),
+                Err(e) => Err(anyhow!("invalid request: {}", e)),
+            };
+
+            let response = match response {
+                Ok(value) => json!({ "ok": value }),
+                Err(e) => json!({ "error": e.to_string() }),
+            };
+
+            writeln!(writer, "{}", response)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn handle(&self, request: Request) -> Result<Value> {
+        match request {
+            Request::SubmitImage {
+                wasm,
+                phantom_functions,
+            } => {
+                let wasm = hex::decode(wasm.trim_start_matches("0x"))?;
+                let image = format!("{:X}", md5::compute(&wasm));
+
+                let image_dir = self.image_dir(&image);
+                fs::create_dir_all(image_dir.join("param"))?;
+                fs::write(image_dir.join(IMAGE), &wasm)?;
+                serde_json::to_writer(
+                    File::create(image_dir.join(PHANTOM_FUNCTIONS))?,
+                    &phantom_functions,
+                )?;
+
+                Ok(json!({ "image": image }))
+            }
+            Request::Setup { image } => self.submit_job(image, Task::Setup),
+            Request::DryRun { image, inputs } => {
+                self.submit_job(image, Task::DryRun(inputs.encode()?))
+            }
+            Request::Prove { image, inputs } => {
+                self.submit_job(image, Task::Prove(inputs.encode()?))
+            }
+            Request::Status { job } => Ok(serde_json::to_value(self.load_job(job)?)?),
+            Request::FetchProof { job } => {
+                let job = self.load_job(job)?;
+
+                if !matches!(job.task, Task::Prove(_)) {
+                    bail!("Job {} doesn't create a proof.", job.id);
+                }
+                if job.status != JobStatus::Done {
+                    bail!("Job {} is not done: {:?}.", job.id, job.status);
+                }
+
+                let output_dir = self.job_dir(job.id);
+                let bundle = ProofBundle::load(output_dir.join(format!("{}.bundle.json", NAME)))?;
+
+                Ok(json!({ "output": output_dir, "bundle": bundle }))
+            }
+        }
+    }
+
+    fn image_dir(&self, image: &str) -> PathBuf {
+        self.config.dir.join("images").join(image)
+    }
+
+    fn job_dir(&self, job: u64) -> PathBuf {
+        self.config.dir.join("jobs").join(job.to_string())
+    }
+
+    fn load_job(&self, job: u64) -> Result<Job> {
+        let path = self.job_dir(job).join(JOB);
+        if !path.exists() {
+            bail!("Job {} is not found.", job);
+        }
+
+        Ok(serde_json::from_reader(File::open(path)?)?)
+    }
+
+    /// Writes the job to a temporary file first, so that a concurrent status request never
+    /// reads a partially written job.
+    fn save_job(&self, job: &Job) -> Result<()> {
+        let dir = self.job_dir(job.id);
+        fs::create_dir_all(&dir)?;
+
+        let tmp = dir.join(format!("{}.tmp", JOB));
+        serde_json::to_writer_pretty(File::create(&tmp)?, job)?;
+        fs::rename(tmp, dir.join(JOB))?;
+
+        Ok(())
+    }
+
+    fn enqueue(&self, job: u64) -> Result<()> {
+        self.queue
+            .lock()
+            .unwrap()
+            .send(job)
+            .map_err(|_| anyhow!("the worker of the daemon stopped"))
+    }
+
+    fn submit_job(&self, image: String, task: Task) -> Result<Value> {
+        // The image is a part of the paths of the daemon directory.
+        if image.len() != 32 || !image.chars().all(|c| c.is_ascii_hexdigit()) {
+            bail!("Image {:?} is not the md5 of an image.", image);
+        }
+        if !self.image_dir(&image).join(IMAGE).exists() {
+            bail!("Image {} is not submitted.", image);
+        }
+
+        let job = Job {
+            id: self.next_job.fetch_add(1, Ordering::SeqCst),
+            image,
+            task,
+            status: JobStatus::Queued,
+        };
+        self.save_job(&job)?;
+        self.enqueue(job.id)?;
+
+        Ok(json!({ "job": job.id }))
+    }
+
+    fn work(&self, receiver: Receiver<u64>) {
+        match self.config.host_mode {
+            HostMode::DEFAULT => {
+                let mut session = None;
+
+                self.work_with(receiver, |job| {
+                    self.run_job::<DefaultHostEnvBuilder>(
+                        job,
+                        &mut session,
+                        (),
+                        |inputs| inputs.into(),
+                        |inputs| inputs.into(),
+                    )
+                })
+            }
+            HostMode::STANDARD => {
+                // The tree db is opened by the worker since it is not shared across threads.
+                let tree_db = self
+                    .config
+                    .tree_db
+                    .as_ref()
+                    .map(|db| open_tree_db(db))
+                    .transpose()
+                    .map_err(|e| e.to_string());
+                let mut session = None;
+
+                self.work_with(receiver, |job| {
+                    let tree_db = tree_db
+                        .clone()
+                        .map_err(|e| anyhow!("failed to open the tree db: {}", e))?;
+
+                    self.run_job::<StandardHostEnvBuilder>(
+                        job,
+                        &mut session,
+                        self.config.host_config.clone(),
+                        |inputs| StandardArg {
+                            tree_db: tree_db.clone(),
+                            ..inputs.into()
+                        },
+                        |inputs| StandardArg {
+                            tree_db: overlay_tree_db(tree_db.clone()),
+                            ..inputs.into()
+                        },
+                    )
+                })
+            }
+        }
+    }
+
+    fn work_with(&self, receiver: Receiver<u64>, mut run: impl FnMut(&Job) -> Result<()>) {
+        for id in receiver {
+            if let Err(e) = self.execute_job(id, &mut run) {
+                warn!("Failed to update job {}: {}", id, e);
+            }
+        }
+    }
+
+    /// Runs the job and records its status, a panic of the job is recorded as a failure
+    /// instead of stopping the worker.
+    fn execute_job(&self, id: u64, run: &mut impl FnMut(&Job) -> Result<()>) -> Result<()> {
+        let mut job = self.load_job(id)?;
+        job.status = JobStatus::Running;
+        self.save_job(&job)?;
+
+        info!("Run job {}: {:?}", job.id, job.task);
+        job.status = match catch_unwind(AssertUnwindSafe(|| run(&job))) {
+            Ok(Ok(())) => JobStatus::Done,
+            Ok(Err(e)) => JobStatus::Failed {
+                reason: e.to_string(),
+            },
+            Err(panic) => JobStatus::Failed {
+                reason: format!(
+                    "the job panicked: {}",
+                    panic
+                        .downcast_ref::<&str>()
+                        .map(|message| message.to_string())
+                        .or_else(|| panic.downcast_ref::<String>().cloned())
+                        .unwrap_or_default()
+                ),
+            },
+        };
+        info!("Job {} finished: {:?}", job.id, job.status);
+
+        self.save_job(&job)
+    }
+
+    /// `replay_arg` is the arg of an execution replayed to collect a backtrace, which must not
+    /// write the tree db of `arg`.
+    fn run_job<Builder: HostEnvBuilder>(
+        &self,
+        job: &Job,
+        session: &mut Option<ImageSession<Builder>>,
+        config: Builder::HostConfig,
+        arg: impl Fn(EncodedInputs) -> Builder::Arg,
+        replay_arg: impl Fn(EncodedInputs) -> Builder::Arg,
+    ) -> Result<()>
+    where
+        Builder::HostConfig: Clone + Serialize + DeserializeOwned + PartialEq + Debug,
+    {
+        let image_dir = self.image_dir(&job.image);
+        let wasm_binary = fs::read(image_dir.join(IMAGE))?;
+        let phantom_functions: Vec<String> =
+            serde_json::from_reader(File::open(image_dir.join(PHANTOM_FUNCTIONS))?)?;
+        let param_dir = image_dir.join("param");
+        let output_dir = self.job_dir(job.id);
+
+        let DaemonConfig {
+            zkwasm_k,
+            aggregate_k,
+            ..
+        } = self.config;
+
+        match &job.task {
+            Task::Setup => exec_setup::<Builder>(
+                zkwasm_k,
+                aggregate_k,
+                NAME,
+                wasm_binary,
+                phantom_functions,
+                false,
+                false,
+                config,
+                &output_dir,
+                &param_dir,
+            ),
+            Task::DryRun(inputs) => exec_dry_run::<Builder>(
+                zkwasm_k,
+                wasm_binary,
+                phantom_functions,
+                None,
+                arg(inputs.clone()),
+                || replay_arg(inputs.clone()),
+                || config.clone(),
+            ),
+            Task::Prove(inputs) => {
+                if session.as_ref().map(|session| &session.image) != Some(&job.image) {
+                    let params_path = param_dir.join(format!("K{}.params", zkwasm_k));
+                    if !params_path.exists() {
+                        bail!("Image {} is not set up.", job.image);
+                    }
+                    check_host_config(&param_dir, NAME, &config)?;
+
+                    // Drop the proving key of the previous image before creating a new one.
+                    *session = None;
+
+                    let loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
+                        zkwasm_k,
+                        wasm_binary,
+                        phantom_functions,
+                    )?;
+                    let params = load_or_build_unsafe_params::<Bn256>(zkwasm_k, Some(&params_path));
+
+                    let zkwasm_session = ZkWasmSession::new(loader, params, config.clone())?;
+
+                    let checksum = zkwasm_session.checksum();
+                    if checksum.len() != 1 {
+                        bail!(
+                            "The image checksum has {} points, 1 is expected.",
+                            checksum.len()
+                        );
+                    }
+
+                    *session = Some(ImageSession {
+                        image: job.image.clone(),
+                        image_checksum: format!("{:?}", checksum[0]),
+                        vkey_hash: vkey_hash(&param_dir, NAME)?,
+                        session: zkwasm_session,
+                    });
+                }
+                let session = session.as_ref().unwrap();
+
+                let proof = session.session.prove(arg(inputs.clone()))?;
+                let bundle = ProofBundle {
+                    version: BUNDLE_VERSION,
+                    tool_version: env!("CARGO_PKG_VERSION").to_string(),
+                    wasm_md5: job.image.clone(),
+                    k: zkwasm_k,
+                    image_checksum: session.image_checksum.clone(),
+                    vkey_hash: session.vkey_hash.clone(),
+                    host_config: serde_json::to_value(&config)?,
+                    hash_type: POSEIDON_HASH.to_string(),
+                    instances: ProofBundle::encode_instances(&proof.instances),
+                    proof: hex::encode(&proof.proof),
+                };
+
+                bundle.save(output_dir.join(format!("{}.bundle.json", NAME)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use delphinus_zkwasm::circuits::config::MIN_K;
+
+    fn daemon(name: &str) -> Daemon {
+        let dir = std::env::temp_dir().join(format!("daemon_{}_{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        Daemon {
+            config: DaemonConfig {
+                dir,
+                zkwasm_k: MIN_K,
+                aggregate_k: 22,
+                host_mode: HostMode::DEFAULT,
+                host_config: HostEnvConfig::default(),
+                tree_db: None,
+            },
+            next_job: AtomicU64::new(0),
+            queue: Mutex::new(channel().0),
+        }
+    }
+
+    fn job(daemon: &Daemon, id: u64) -> Job {
+        let job = Job {
+            id,
+            image: "0".repeat(32),
+            task: Task::Setup,
+            status: JobStatus::Queued,
+        };
+        daemon.save_job(&job).unwrap();
+
+        job
+    }
+
+    #[test]
+    fn test_failed_jobs() {
+        let daemon = daemon("failed_jobs");
+        job(&daemon, 0);
+        job(&daemon, 1);
+        job(&daemon, 2);
+
+        let (sender, receiver) = channel();
+        for id in 0..3 {
+            sender.send(id).unwrap();
+        }
+        drop(sender);
+
+        daemon.work_with(receiver, |job| match job.id {
+            0 => panic!("job {} panics", job.id),
+            1 => bail!("job {} fails", job.id),
+            _ => Ok(()),
+        });
+
+        assert_eq!(
+            daemon.load_job(0).unwrap().status,
+            JobStatus::Failed {
+                reason: "the job panicked: job 0 panics".to_string()
+            }
+        );
+        assert_eq!(
+            daemon.load_job(1).unwrap().status,
+            JobStatus::Failed {
+                reason: "job 1 fails".to_string()
+            }
+        );
+        assert_eq!(daemon.load_job(2).unwrap().status, JobStatus::Done);
+
+        fs::remove_dir_all(&daemon.config.dir).unwrap();
+    }
+}
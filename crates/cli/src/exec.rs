@@ -1,21 +1,90 @@
+use anyhow::anyhow;
+use anyhow::bail;
 use anyhow::Result;
 use circuits_batcher::proof::CircuitInfo;
 use circuits_batcher::proof::ParamsCache;
 use circuits_batcher::proof::ProofInfo;
 use circuits_batcher::proof::ProofLoadInfo;
 use circuits_batcher::proof::ProvingKeyCache;
+use delphinus_host::host::merkle_helper::state::MerkleRoots;
+use delphinus_host::host::merkle_helper::state::MerkleState;
+use delphinus_host::host::merkle_helper::state::RootTransition;
+use delphinus_zkwasm::circuits::config::MAX_K;
 use delphinus_zkwasm::circuits::ZkWasmCircuit;
+use delphinus_zkwasm::loader::err::Error;
+use delphinus_zkwasm::loader::err::RuntimeErr;
 use delphinus_zkwasm::loader::ZkWasmLoader;
+use delphinus_zkwasm::profile::Profiler;
+use delphinus_zkwasm::runtime::host::ForeignError;
+use delphinus_zkwasm::runtime::host::ForeignStatics;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
+use delphinus_zkwasm::runtime::ExecutionResult;
 use halo2_proofs::pairing::bn256::Bn256;
 use halo2_proofs::pairing::bn256::Fr;
+use halo2_proofs::pairing::bn256::G1Affine;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsVerifier;
+use halo2aggregator_s::circuits::aggregator::AggregatorCircuit;
+use halo2aggregator_s::circuits::utils::load_instance;
 use halo2aggregator_s::circuits::utils::load_or_build_unsafe_params;
+use halo2aggregator_s::circuits::utils::load_proof;
+use halo2aggregator_s::circuits::utils::load_vkey;
+use halo2aggregator_s::circuits::utils::run_circuit_unsafe_full_pass;
 use halo2aggregator_s::circuits::utils::TranscriptHash;
 use halo2aggregator_s::native_verifier;
+use halo2aggregator_s::solidity_verifier::solidity_aux_gen;
+use halo2aggregator_s::solidity_verifier::solidity_render;
 use log::info;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::BufWriter;
 use std::io::Write;
 use std::path::PathBuf;
+use wasmi::RuntimeValue;
+
+use crate::bundle::ProofBundle;
+use crate::bundle::BUNDLE_VERSION;
+use crate::bundle::POSEIDON_HASH;
+
+fn host_config_path(param_dir: &PathBuf, prefix: &str) -> PathBuf {
+    param_dir.join(format!("{}.hostconfig.json", prefix))
+}
+
+/// The host ops are part of the image, the config used by setup is recorded next to the vkey
+/// so that a proof created with a different config is rejected before proving.
+pub(crate) fn check_host_config<C>(param_dir: &PathBuf, prefix: &str, config: &C) -> Result<()>
+where
+    C: DeserializeOwned + PartialEq + Debug,
+{
+    let path = host_config_path(param_dir, prefix);
+
+    if !path.exists() {
+        bail!(
+            "Host config is not recorded at {:?}. If the param path was set up before host configs were recorded, run setup with --record-host-config once to record the host config of the image.",
+            path
+        );
+    }
+
+    let expected: C = serde_json::from_reader(File::open(&path)?)?;
+    if &expected != config {
+        bail!(
+            "Host config {:?} does not match the config {:?} recorded at {:?}, please setup again.",
+            config,
+            expected,
+            path
+        );
+    }
+
+    Ok(())
+}
 
 pub fn exec_setup<Builder: HostEnvBuilder>(
     zkwasm_k: u32,
@@ -23,10 +92,15 @@ pub fn exec_setup<Builder: HostEnvBuilder>(
     prefix: &str,
     wasm_binary: Vec<u8>,
     phantom_functions: Vec<String>,
+    provable_trap: bool,
+    record_host_config: bool,
     envconfig: Builder::HostConfig,
     _output_dir: &PathBuf,
     param_dir: &PathBuf,
-) -> Result<()> {
+) -> Result<()>
+where
+    Builder::HostConfig: Serialize + DeserializeOwned + PartialEq + Debug,
+{
     info!("Setup Params and VerifyingKey");
 
     macro_rules! prepare_params {
@@ -52,13 +126,31 @@ pub fn exec_setup<Builder: HostEnvBuilder>(
 
         if vk_path.exists() {
             info!("Found Verifying at {:?}", vk_path);
+
+            let host_config_path = host_config_path(param_dir, prefix);
+            // Param paths set up before the host config was recorded are migrated once.
+            if record_host_config && !host_config_path.exists() {
+                info!(
+                    "Record host config {:?} to {:?}",
+                    envconfig, host_config_path
+                );
+                serde_json::to_writer_pretty(File::create(&host_config_path)?, &envconfig)?;
+            }
+
+            check_host_config(param_dir, prefix, &envconfig)?;
         } else {
             info!("Create Verifying to {:?}", vk_path);
-            let loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
+            let mut loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
                 zkwasm_k,
                 wasm_binary,
                 phantom_functions,
             )?;
+            loader.set_provable_trap(provable_trap)?;
+
+            serde_json::to_writer_pretty(
+                File::create(host_config_path(param_dir, prefix))?,
+                &envconfig,
+            )?;
 
             let vkey = loader.create_vkey(&params, envconfig)?;
 
@@ -92,7 +184,12 @@ where
     );
 
     let checksum = loader.checksum(&params, hostenv)?;
-    assert_eq!(checksum.len(), 1);
+    if checksum.len() != 1 {
+        bail!(
+            "The image checksum has {} points, 1 is expected.",
+            checksum.len()
+        );
+    }
     let checksum = checksum[0];
 
     println!("image checksum: {:?}", checksum);
@@ -105,21 +202,275 @@ where
     Ok(())
 }
 
+/// Loads the params of the param path created by setup, the params are never generated
+/// since a proof must be created and verified with the same params.
+fn load_params(k: u32, param_dir: &PathBuf) -> Result<Params<G1Affine>> {
+    let params_path = param_dir.join(format!("K{}.params", k));
+    if !params_path.exists() {
+        bail!(
+            "The params {:?} are not found, please run setup.",
+            params_path
+        );
+    }
+
+    Ok(load_or_build_unsafe_params::<Bn256>(k, Some(&params_path)))
+}
+
+/// The hex encoded SHA-256 of the vkey written by setup, which binds a proof bundle to it.
+pub(crate) fn vkey_hash(param_dir: &PathBuf, prefix: &str) -> Result<String> {
+    let vkey_path = param_dir.join(format!("{}.vkey.data", prefix));
+    if !vkey_path.exists() {
+        bail!("The vkey {:?} is not found, please run setup.", vkey_path);
+    }
+
+    Ok(hex::encode(Sha256::digest(std::fs::read(vkey_path)?)))
+}
+
+/// The checksum of the image with the params, formatted as written by the checksum command.
+fn image_checksum<Builder: HostEnvBuilder>(
+    loader: &ZkWasmLoader<Bn256, Builder::Arg, Builder>,
+    params: &Params<G1Affine>,
+    config: Builder::HostConfig,
+) -> Result<String> {
+    let checksum = loader.checksum(params, config)?;
+    if checksum.len() != 1 {
+        bail!(
+            "The image checksum has {} points, 1 is expected.",
+            checksum.len()
+        );
+    }
+
+    Ok(format!("{:?}", checksum[0]))
+}
+
+#[derive(Serialize, Deserialize)]
+struct CachedImageChecksum {
+    vkey_hash: String,
+    image_checksum: String,
+}
+
+/// The image checksum is cached in the param path together with the hash of the vkey it is
+/// computed for. The vkey commits to the image and the host config, hence the checksum is
+/// recomputed once the param path is set up again with another image or host config.
+fn cached_image_checksum<Builder: HostEnvBuilder>(
+    loader: &ZkWasmLoader<Bn256, Builder::Arg, Builder>,
+    zkwasm_k: u32,
+    prefix: &str,
+    param_dir: &PathBuf,
+    vkey_hash: &str,
+    config: Builder::HostConfig,
+) -> Result<String> {
+    let checksum_path = param_dir.join(format!("{}.checksum.json", prefix));
+    if checksum_path.exists() {
+        let cached: CachedImageChecksum =
+            serde_json::from_reader(BufReader::new(File::open(&checksum_path)?))?;
+
+        if cached.vkey_hash == vkey_hash {
+            return Ok(cached.image_checksum);
+        }
+    }
+
+    let params = load_params(zkwasm_k, param_dir)?;
+    let image_checksum = image_checksum(loader, &params, config)?;
+    serde_json::to_writer_pretty(
+        File::create(checksum_path)?,
+        &CachedImageChecksum {
+            vkey_hash: vkey_hash.to_string(),
+            image_checksum: image_checksum.clone(),
+        },
+    )?;
+
+    Ok(image_checksum)
+}
+
+fn write_profile(
+    execution_result: &ExecutionResult<RuntimeValue>,
+    profile_path: &PathBuf,
+) -> Result<()> {
+    let report = execution_result.tables.profile_functions();
+
+    report.write_folded(BufWriter::new(File::create(profile_path)?))?;
+    report.write_json(BufWriter::new(File::create(
+        profile_path.with_extension("json"),
+    )?))?;
+
+    info!("Profile has been written to {:?}.", profile_path);
+
+    Ok(())
+}
+
+fn print_host_budget(host_statics: &HashMap<String, ForeignStatics>) {
+    let mut plugins = host_statics.iter().collect::<Vec<_>>();
+    plugins.sort_by(|a, b| a.0.cmp(b.0));
+
+    println!("{:<28}{:>16}{:>16}", "plugin", "used rounds", "max rounds");
+    for (plugin, statics) in plugins {
+        println!(
+            "{:<28}{:>16}{:>16}",
+            plugin, statics.used_round, statics.max_round
+        );
+    }
+}
+
 pub fn exec_dry_run<Builder: HostEnvBuilder>(
     zkwasm_k: u32,
     wasm_binary: Vec<u8>,
     phantom_functions: Vec<String>,
+    profile_path: Option<PathBuf>,
     arg: Builder::Arg,
-    config: Builder::HostConfig,
+    replay_arg: impl FnOnce() -> Builder::Arg,
+    config: impl Fn() -> Builder::HostConfig,
 ) -> Result<()> {
     let loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
         zkwasm_k,
         wasm_binary,
         phantom_functions,
     )?;
-    let result = loader.run(arg, config, true, false)?;
+    // Profiling requires the execution tables, which are not traced in dry-run mode.
+    let dryrun = profile_path.is_none();
+    let result = match loader.run(arg, config(), dryrun, false) {
+        Ok(result) => result,
+        Err(e) => {
+            if let Some(Error::Runtime(_)) = e.downcast_ref::<Error>() {
+                // Events are not traced in dry-run mode, execute again with tracing
+                // to get the backtrace of the trap. The replayed execution must not repeat
+                // the side effects of the first one, `replay_arg` discards its context
+                // outputs and keeps the writes to the tree db in memory.
+                let traced = if dryrun {
+                    loader.run(replay_arg(), config(), false, false).err()
+                } else {
+                    None
+                };
+
+                if let Some(Error::Runtime(err)) =
+                    traced.as_ref().unwrap_or(&e).downcast_ref::<Error>()
+                {
+                    if let Some(backtrace) = err.backtrace() {
+                        println!("guest backtrace:\n{}", backtrace);
+                    }
+
+                    if let RuntimeErr::ForeignTrap {
+                        error:
+                            ForeignError::RoundsExceeded {
+                                plugin,
+                                used_round,
+                                max_round,
+                                suggested_k,
+                            },
+                        ..
+                    } = err
+                    {
+                        println!(
+                            "{} uses {} rounds but only {} rounds are supported with K = {}",
+                            plugin, used_round, max_round, zkwasm_k
+                        );
+                        match suggested_k {
+                            Some(k) => println!("the rounds fit in K = {}", k),
+                            None => println!("the rounds exceed the maximal K {}", MAX_K),
+                        }
+                    }
+                }
+            }
+
+            return Err(e);
+        }
+    };
     println!("total guest instructions used {:?}", result.guest_statics);
-    println!("total host api used {:?}", result.host_statics);
+    print_host_budget(&result.host_statics);
+
+    if let Some(profile_path) = profile_path {
+        write_profile(&result, &profile_path)?;
+    }
+
+    Ok(())
+}
+
+pub fn exec_estimate<Builder: HostEnvBuilder>(
+    wasm_binary: Vec<u8>,
+    phantom_functions: Vec<String>,
+    arg: Builder::Arg,
+    config: Builder::HostConfig,
+) -> Result<()>
+where
+    Builder::HostConfig: Clone,
+{
+    // Load the image with the maximal K so that the precheck doesn't reject the image
+    // before the rows are estimated. The capacities of the tables and the host circuits
+    // are computed for each candidate K by the estimate.
+    let loader =
+        ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(MAX_K, wasm_binary, phantom_functions)?;
+
+    let estimate = loader.estimate(arg, config)?;
+    let minimal_k = estimate.minimal_k();
+    let k = minimal_k.unwrap_or(MAX_K);
+
+    println!(
+        "{:<28}{:>16}{:>16}",
+        "table",
+        "required rows",
+        format!("rows (K = {})", k)
+    );
+    for rows in &estimate.tables {
+        println!(
+            "{:<28}{:>16}{:>16}",
+            rows.table.to_string(),
+            rows.required_rows,
+            estimate.available_rows(rows.table, k)
+        );
+    }
+    for rounds in &estimate.host_rounds {
+        println!(
+            "{:<28}{:>16}{:>16}",
+            format!("{} rounds", rounds.plugin),
+            rounds.used_rounds,
+            rounds.available_rounds(k)
+        );
+    }
+
+    match minimal_k {
+        Some(k) => println!("minimal K: {}", k),
+        None => {
+            for rows in estimate.overflowed_tables(MAX_K) {
+                println!("{} overflows with the maximal K {}", rows.table, MAX_K);
+            }
+            for rounds in estimate.overflowed_host_rounds(MAX_K) {
+                println!(
+                    "{} rounds overflow with the maximal K {}",
+                    rounds.plugin, MAX_K
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn exec_dump_trace<Builder: HostEnvBuilder>(
+    zkwasm_k: u32,
+    wasm_binary: Vec<u8>,
+    phantom_functions: Vec<String>,
+    trace_path: &PathBuf,
+    arg: Builder::Arg,
+    config: Builder::HostConfig,
+) -> Result<()> {
+    let loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
+        zkwasm_k,
+        wasm_binary,
+        phantom_functions,
+    )?;
+
+    let execution_result = loader.run(arg, config, false, false)?;
+
+    println!(
+        "total guest instructions used {:?}",
+        execution_result.guest_statics
+    );
+    println!("total host api used {:?}", execution_result.host_statics);
+
+    loader.write_trace(&execution_result, BufWriter::new(File::create(trace_path)?))?;
+
+    info!("Trace has been written to {:?}.", trace_path);
+
     Ok(())
 }
 
@@ -130,16 +481,43 @@ pub fn exec_create_proof<Builder: HostEnvBuilder>(
     phantom_functions: Vec<String>,
     output_dir: &PathBuf,
     param_dir: &PathBuf,
+    trace_path: Option<PathBuf>,
+    profile_path: Option<PathBuf>,
+    provable_trap: bool,
     arg: Builder::Arg,
     config: Builder::HostConfig,
-) -> Result<()> {
-    let loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
+) -> Result<()>
+where
+    Builder::HostConfig: Clone + Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    check_host_config(param_dir, prefix, &config)?;
+
+    let wasm_md5 = format!("{:X}", md5::compute(&wasm_binary));
+    let mut loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
         zkwasm_k,
         wasm_binary,
         phantom_functions,
     )?;
+    loader.set_provable_trap(provable_trap)?;
+    let vkey_hash = vkey_hash(param_dir, prefix)?;
+    let image_checksum = cached_image_checksum(
+        &loader,
+        zkwasm_k,
+        prefix,
+        param_dir,
+        &vkey_hash,
+        config.clone(),
+    )?;
+    let host_config = serde_json::to_value(&config)?;
 
-    let execution_result = loader.run(arg, config, false, true)?;
+    // Skip the execution if the trace is provided.
+    let execution_result = match trace_path {
+        Some(trace_path) => {
+            info!("Load trace from {:?}.", trace_path);
+            loader.load_trace(config, BufReader::new(File::open(trace_path)?))?
+        }
+        None => loader.run(arg, config, false, true)?,
+    };
 
     println!(
         "total guest instructions used {:?}",
@@ -147,6 +525,16 @@ pub fn exec_create_proof<Builder: HostEnvBuilder>(
     );
     println!("total host api used {:?}", execution_result.host_statics);
     println!("application outout {:?}", execution_result.outputs);
+    if let Some(trap) = execution_result.trap {
+        println!(
+            "trapped with {:?} at fid {} iid {}",
+            trap.code, trap.fid, trap.iid
+        );
+    }
+
+    if let Some(profile_path) = profile_path {
+        write_profile(&execution_result, &profile_path)?;
+    }
 
     let (circuit, instances) = loader.circuit_with_witness(execution_result)?;
 
@@ -178,9 +566,110 @@ pub fn exec_create_proof<Builder: HostEnvBuilder>(
 
     info!("Proof has been created.");
 
+    let proof =
+        ProofInfo::<Bn256>::load_proof(output_dir, param_dir, &circuit.proofloadinfo).remove(0);
+    let bundle = ProofBundle {
+        version: BUNDLE_VERSION,
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        wasm_md5,
+        k: zkwasm_k,
+        image_checksum,
+        vkey_hash,
+        host_config,
+        hash_type: POSEIDON_HASH.to_string(),
+        instances: ProofBundle::encode_instances(&proof.instances[0]),
+        proof: hex::encode(&proof.transcripts),
+    };
+    let bundle_path = output_dir.join(format!("{}.bundle.json", prefix));
+    bundle.save(&bundle_path)?;
+
+    info!("Proof bundle has been written to {:?}.", bundle_path);
+
     Ok(())
 }
 
+/// Proof bundles of the sequential single-prove runs with `--state`, the bundle of the n-th run
+/// is saved as `<n>.bundle.json`.
+pub const MERKLE_STATE: &'static str = "merkle_state";
+
+fn merkle_state_bundle_path(output_dir: &PathBuf, index: usize) -> PathBuf {
+    output_dir
+        .join(MERKLE_STATE)
+        .join(format!("{}.bundle.json", index))
+}
+
+/// Loads the proof bundles of the previous single-prove runs with `--state`.
+pub fn load_merkle_state_bundles(output_dir: &PathBuf) -> Result<Vec<ProofBundle>> {
+    let mut bundles = vec![];
+
+    loop {
+        let path = merkle_state_bundle_path(output_dir, bundles.len());
+        if !path.exists() {
+            return Ok(bundles);
+        }
+
+        bundles.push(ProofBundle::load(path)?);
+    }
+}
+
+/// Builds the chain of the merkle roots from the instances of the proofs.
+pub fn merkle_state_of_bundles(bundles: &Vec<ProofBundle>) -> Result<MerkleState> {
+    let mut merkle_state = MerkleState::default();
+
+    for bundle in bundles {
+        merkle_state.push(RootTransition::from_instances(&bundle.decode_instances()?)?)?;
+    }
+
+    Ok(merkle_state)
+}
+
+/// Appends the proof bundle of the last single-prove to the merkle state, the roots in its
+/// instances must be the roots observed during the execution.
+pub fn exec_push_merkle_state(
+    prefix: &'static str,
+    output_dir: &PathBuf,
+    merkle_state: &mut MerkleState,
+    roots: &MerkleRoots,
+) -> Result<()> {
+    let bundle_path = output_dir.join(format!("{}.bundle.json", prefix));
+    let transition =
+        RootTransition::from_instances(&ProofBundle::load(&bundle_path)?.decode_instances()?)?;
+    transition.check_observed(roots)?;
+
+    let state_path = merkle_state_bundle_path(output_dir, merkle_state.transitions.len());
+    merkle_state.push(transition)?;
+
+    std::fs::create_dir_all(output_dir.join(MERKLE_STATE))?;
+    std::fs::copy(&bundle_path, state_path)?;
+
+    Ok(())
+}
+
+/// Verifies the proof of each single-prove run with `--state` and checks that the roots in
+/// their instances are chained, the last run must be the proof being verified.
+fn verify_merkle_state(
+    params_verifier: &ParamsVerifier<Bn256>,
+    proof: &ProofInfo<Bn256>,
+    bundles: &Vec<ProofBundle>,
+) -> Result<()> {
+    for bundle in bundles {
+        native_verifier::verify_single_proof::<Bn256>(
+            params_verifier,
+            &proof.vkey,
+            &vec![bundle.decode_instances()?],
+            bundle.decode_proof()?,
+            TranscriptHash::Poseidon,
+        );
+    }
+
+    merkle_state_of_bundles(bundles)?;
+
+    match bundles.last() {
+        Some(bundle) if bundle.decode_instances()? == proof.instances[0] => Ok(()),
+        _ => bail!("The proof is not the last execution of the merkle state."),
+    }
+}
+
 pub fn exec_verify_proof(
     prefix: &'static str,
     output_dir: &PathBuf,
@@ -190,11 +679,12 @@ pub fn exec_verify_proof(
     let proofloadinfo = ProofLoadInfo::load(&load_info);
     let proofs: Vec<ProofInfo<Bn256>> =
         ProofInfo::load_proof(&output_dir, &param_dir, &proofloadinfo);
-    let params = load_or_build_unsafe_params::<Bn256>(
-        proofloadinfo.k as u32,
-        Some(&param_dir.join(format!("K{}.params", proofloadinfo.k))),
-    );
-    let mut public_inputs_size = 0;
+    let params = load_params(proofloadinfo.k as u32, param_dir)?;
+    let merkle_state_bundles = load_merkle_state_bundles(output_dir)?;
+
+    let mut public_inputs_size = merkle_state_bundles
+        .iter()
+        .fold(0, |acc, bundle| usize::max(acc, bundle.instances.len()));
     for proof in proofs.iter() {
         public_inputs_size = usize::max(
             public_inputs_size,
@@ -217,5 +707,294 @@ pub fn exec_verify_proof(
     }
     info!("Verifing proof passed");
 
+    if !merkle_state_bundles.is_empty() {
+        verify_merkle_state(&params_verifier, &proofs[0], &merkle_state_bundles)?;
+        info!("Verifing merkle state passed");
+    }
+
+    Ok(())
+}
+
+/// Verifies a proof bundle against the image and the host config, the params and vkey are
+/// loaded from the param path written by setup and the vkey must be the one of the bundle.
+pub fn exec_verify_bundle<Builder: HostEnvBuilder>(
+    prefix: &'static str,
+    zkwasm_k: u32,
+    wasm_binary: Vec<u8>,
+    phantom_functions: Vec<String>,
+    config: Builder::HostConfig,
+    bundle_path: &PathBuf,
+    param_dir: &PathBuf,
+) -> Result<()>
+where
+    Builder::HostConfig: Clone + DeserializeOwned + PartialEq + Debug,
+{
+    let bundle = ProofBundle::load(bundle_path)?;
+
+    let bundle_config: Builder::HostConfig = serde_json::from_value(bundle.host_config.clone())?;
+    if bundle_config != config {
+        bail!(
+            "The proof is created with the host config {:?}, but the host config is {:?}.",
+            bundle_config,
+            config
+        );
+    }
+
+    let wasm_md5 = format!("{:X}", md5::compute(&wasm_binary));
+    let loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
+        zkwasm_k,
+        wasm_binary,
+        phantom_functions,
+    )?;
+    let params = load_params(zkwasm_k, param_dir)?;
+    let image_checksum = image_checksum(&loader, &params, config)?;
+    bundle.check_image(&wasm_md5, zkwasm_k, &image_checksum)?;
+    bundle.check_vkey(&vkey_hash(param_dir, prefix)?)?;
+
+    let vkey = load_vkey::<Bn256, ZkWasmCircuit<Fr>>(
+        &params,
+        &param_dir.join(format!("{}.vkey.data", prefix)),
+    );
+    let instances = bundle.decode_instances()?;
+
+    let params_verifier: ParamsVerifier<Bn256> = params.verifier(instances.len()).unwrap();
+    native_verifier::verify_single_proof::<Bn256>(
+        &params_verifier,
+        &vkey,
+        &vec![instances],
+        bundle.decode_proof()?,
+        TranscriptHash::Poseidon,
+    );
+    info!("Verifing proof bundle passed");
+
+    Ok(())
+}
+
+const AGGREGATE_PREFIX: &'static str = "aggregate-circuit";
+
+/// Written by aggregate-prove since the number of the instances of the aggregate circuit
+/// depends on the aggregated circuits.
+#[derive(Serialize, Deserialize)]
+struct AggregateInfo {
+    n_proofs: usize,
+    instances: usize,
+}
+
+fn aggregate_info_path(param_dir: &PathBuf) -> PathBuf {
+    param_dir.join(format!("{}.info.json", AGGREGATE_PREFIX))
+}
+
+pub fn exec_aggregate_create_proof<Builder: HostEnvBuilder>(
+    zkwasm_k: u32,
+    aggregate_k: u32,
+    prefix: &'static str,
+    wasm_binary: Vec<u8>,
+    phantom_functions: Vec<String>,
+    provable_trap: bool,
+    output_dir: &PathBuf,
+    param_dir: &PathBuf,
+    args: Vec<Builder::Arg>,
+    config: impl Fn() -> Builder::HostConfig,
+) -> Result<()>
+where
+    Builder::HostConfig: DeserializeOwned + PartialEq + Debug,
+{
+    check_host_config(param_dir, prefix, &config())?;
+
+    let mut loader = ZkWasmLoader::<Bn256, Builder::Arg, Builder>::new(
+        zkwasm_k,
+        wasm_binary,
+        phantom_functions,
+    )?;
+    loader.set_provable_trap(provable_trap)?;
+
+    let n_proofs = args.len();
+    let mut circuits = vec![];
+    let mut instances = vec![];
+
+    for arg in args {
+        let execution_result = loader.run(arg, config(), false, false)?;
+
+        println!(
+            "total guest instructions used {:?}",
+            execution_result.guest_statics
+        );
+        println!("total host api used {:?}", execution_result.host_statics);
+
+        let (circuit, public_inputs_and_outputs) = loader.circuit_with_witness(execution_result)?;
+
+        circuits.push(circuit);
+        instances.push(vec![public_inputs_and_outputs]);
+    }
+
+    // Create the proofs of the zkwasm circuits and the circuit verifying them.
+    let (aggregate_circuit, aggregate_instances) = run_circuit_unsafe_full_pass::<Bn256, _>(
+        param_dir,
+        prefix,
+        zkwasm_k,
+        circuits,
+        instances,
+        TranscriptHash::Poseidon,
+        vec![],
+        true,
+    )
+    .ok_or_else(|| {
+        anyhow!(
+            "Failed to create the proofs of the zkwasm circuit with K = {}, please check the params and vkey created by setup.",
+            zkwasm_k
+        )
+    })?;
+
+    let aggregate_info = AggregateInfo {
+        n_proofs,
+        instances: aggregate_instances.len(),
+    };
+
+    // The params and vkey of the aggregate circuit stay in the param path next to the ones
+    // created by setup, the proof and instances are moved to the output path.
+    run_circuit_unsafe_full_pass::<Bn256, _>(
+        param_dir,
+        AGGREGATE_PREFIX,
+        aggregate_k,
+        vec![aggregate_circuit],
+        vec![vec![aggregate_instances]],
+        TranscriptHash::Sha,
+        vec![],
+        true,
+    )
+    .ok_or_else(|| {
+        anyhow!(
+            "Failed to create the proof of the aggregate circuit with K = {}.",
+            aggregate_k
+        )
+    })?;
+
+    for file in ["transcript", "instance"] {
+        let name = format!("{}.0.{}.data", AGGREGATE_PREFIX, file);
+
+        std::fs::copy(param_dir.join(&name), output_dir.join(&name))?;
+        std::fs::remove_file(param_dir.join(&name))?;
+    }
+
+    serde_json::to_writer_pretty(
+        File::create(aggregate_info_path(param_dir))?,
+        &aggregate_info,
+    )?;
+
+    info!("Aggregate proof has been created.");
+
+    Ok(())
+}
+
+/// Loads the params and vkey of the aggregate circuit and the instances of an aggregate proof
+/// of `n_proofs` proofs.
+fn load_aggregate_proof(
+    aggregate_k: u32,
+    param_dir: &PathBuf,
+    instances_path: &PathBuf,
+    n_proofs: usize,
+) -> Result<(Params<G1Affine>, VerifyingKey<G1Affine>, Vec<Vec<Fr>>)> {
+    let params_path = param_dir.join(format!("K{}.params", aggregate_k));
+    let vkey_path = param_dir.join(format!("{}.0.vkey.data", AGGREGATE_PREFIX));
+    let info_path = aggregate_info_path(param_dir);
+
+    for path in [&params_path, &vkey_path, &info_path] {
+        if !path.exists() {
+            bail!(
+                "{:?} is not found, the aggregate proof must be created with the same param path.",
+                path
+            );
+        }
+    }
+
+    let aggregate_info: AggregateInfo = serde_json::from_reader(File::open(&info_path)?)?;
+    if aggregate_info.n_proofs != n_proofs {
+        bail!(
+            "The aggregate circuit verifies {} proofs, but {} proofs are expected.",
+            aggregate_info.n_proofs,
+            n_proofs
+        );
+    }
+
+    let params = load_or_build_unsafe_params::<Bn256>(aggregate_k, Some(&params_path));
+    let vkey = load_vkey::<Bn256, AggregatorCircuit<G1Affine>>(&params, &vkey_path);
+    let instances = load_instance::<Bn256>(&[aggregate_info.instances], instances_path);
+
+    Ok((params, vkey, instances))
+}
+
+pub fn exec_verify_aggregate_proof(
+    aggregate_k: u32,
+    param_dir: &PathBuf,
+    proof_path: &PathBuf,
+    instances_path: &PathBuf,
+    n_proofs: usize,
+) -> Result<()> {
+    let (params, vkey, instances) =
+        load_aggregate_proof(aggregate_k, param_dir, instances_path, n_proofs)?;
+    let proof = load_proof(&proof_path);
+
+    let params_verifier: ParamsVerifier<Bn256> = params.verifier(instances[0].len()).unwrap();
+
+    native_verifier::verify_single_proof::<Bn256>(
+        &params_verifier,
+        &vkey,
+        &instances,
+        proof,
+        TranscriptHash::Sha,
+    );
+
+    info!("Verifing aggregate proof passed");
+
+    Ok(())
+}
+
+pub fn exec_solidity_aggregate_proof(
+    zkwasm_k: u32,
+    aggregate_k: u32,
+    output_dir: &PathBuf,
+    param_dir: &PathBuf,
+    proof_path: &PathBuf,
+    sol_path: &PathBuf,
+    instances_path: &PathBuf,
+    n_proofs: usize,
+    aux_only: bool,
+) -> Result<()> {
+    let (params, vkey, mut instances) =
+        load_aggregate_proof(aggregate_k, param_dir, instances_path, n_proofs)?;
+    let instances = instances.remove(0);
+    let params_verifier: ParamsVerifier<Bn256> = params.verifier(instances.len()).unwrap();
+
+    let proof = load_proof(&proof_path);
+
+    if !aux_only {
+        let path_in = sol_path.join("templates");
+        let path_out = sol_path.join("contracts");
+
+        solidity_render(
+            &(path_in.to_str().unwrap().to_owned() + "/*"),
+            path_out.to_str().unwrap(),
+            vec![(
+                "AggregatorConfig.sol.tera".to_owned(),
+                "AggregatorConfig.sol".to_owned(),
+            )],
+            "AggregatorVerifierStepStart.sol.tera",
+            "AggregatorVerifierStepEnd.sol.tera",
+            |i| format!("AggregatorVerifierStep{}.sol", i + 1),
+            zkwasm_k,
+            &params_verifier,
+            &vkey,
+            &instances,
+            proof.clone(),
+        );
+
+        info!("Solidity verifier has been written to {:?}.", path_out);
+    }
+
+    let aux_path = output_dir.join(format!("{}.0.aux.data", AGGREGATE_PREFIX));
+    solidity_aux_gen(&params_verifier, &vkey, &instances, proof, &aux_path);
+
+    info!("Aux data has been written to {:?}.", aux_path);
+
     Ok(())
 }
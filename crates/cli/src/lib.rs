@@ -1,4 +1,6 @@
 pub mod app_builder;
 pub mod args;
+pub mod bundle;
 pub mod command;
+pub mod daemon;
 pub mod exec;
@@ -10,6 +10,7 @@ use specs::args::parse_args;
 
 pub mod app_builder;
 pub mod args;
+pub mod bundle;
 pub mod command;
 pub mod exec;
 
@@ -24,24 +25,24 @@ impl ArgBuilder for SampleApp {
             .help("Public arguments of your wasm program arguments of format value:type where type=i64|bytes|bytes-packed")
             .min_values(0)
     }
-    fn parse_single_public_arg(matches: &ArgMatches) -> Vec<u64> {
+    fn parse_single_public_arg(matches: &ArgMatches) -> Result<Vec<u64>> {
         let inputs: Vec<&str> = matches
             .get_many("public")
             .unwrap_or_default()
             .map(|v: &String| v.as_str())
             .collect();
 
-        parse_args(inputs.into())
+        Ok(parse_args(inputs.into())?)
     }
 
     fn aggregate_public_args<'a>() -> Arg<'a> {
         // We only aggregate one proof in the sample program.
         Self::single_public_arg()
     }
-    fn parse_aggregate_public_args(matches: &ArgMatches) -> Vec<Vec<u64>> {
-        let inputs = Self::parse_single_public_arg(matches);
+    fn parse_aggregate_public_args(matches: &ArgMatches) -> Result<Vec<Vec<u64>>> {
+        let inputs = Self::parse_single_public_arg(matches)?;
 
-        vec![inputs]
+        Ok(vec![inputs])
     }
 
     fn single_private_arg<'a>() -> Arg<'a> {
@@ -52,24 +53,24 @@ impl ArgBuilder for SampleApp {
             .help("Private arguments of your wasm program arguments of format value/filename:type where type=i64|bytes|bytes-packed|file")
             .min_values(0)
     }
-    fn parse_single_private_arg(matches: &ArgMatches) -> Vec<u64> {
+    fn parse_single_private_arg(matches: &ArgMatches) -> Result<Vec<u64>> {
         let inputs: Vec<&str> = matches
             .get_many("private")
             .unwrap_or_default()
             .map(|v: &String| v.as_str())
             .collect();
 
-        parse_args(inputs.into())
+        Ok(parse_args(inputs.into())?)
     }
 
     fn aggregate_private_args<'a>() -> Arg<'a> {
         // We only aggregate one proof in the sample program.
         Self::single_private_arg()
     }
-    fn parse_aggregate_private_args(matches: &ArgMatches) -> Vec<Vec<u64>> {
-        let inputs = Self::parse_single_private_arg(matches);
+    fn parse_aggregate_private_args(matches: &ArgMatches) -> Result<Vec<Vec<u64>>> {
+        let inputs = Self::parse_single_private_arg(matches)?;
 
-        vec![inputs]
+        Ok(vec![inputs])
     }
 }
 impl CommandBuilder for SampleApp {}
@@ -11,3 +11,7 @@ RUST_LOG=info cargo run --release --features cuda -- --host default -k 18 --func
 
 RUST_LOG=info cargo run --release --features cuda -- --host default -k 18 --function zkmain --param ./params --output ./output --wasm ../zkwasm/wasm/wasm_output.wasm single-prove --public 133:i64 --public 2:i64
 RUST_LOG=info cargo run --release --features cuda -- --host default -k 18 --function zkmain --param ./params --output ./output --wasm ../zkwasm/wasm/wasm_output.wasm single-verify
+
+# Aggregate test
+RUST_LOG=info cargo run --release --features cuda -- --host default -k 18 --function zkmain --param ./params --output ./output --wasm ../zkwasm/wasm/wasm_output.wasm aggregate-prove --public 133:i64 --public 2:i64
+RUST_LOG=info cargo run --release --features cuda -- --host default -k 18 --function zkmain --param ./params --output ./output --wasm ../zkwasm/wasm/wasm_output.wasm aggregate-verify --proof ./output/aggregate-circuit.0.transcript.data --instances ./output/aggregate-circuit.0.instance.data
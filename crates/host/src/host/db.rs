@@ -0,0 +1,269 @@
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Write;
+use std::path::Path;
+use std::rc::Rc;
+use zkwasm_host_circuits::host::datahash::DataHashRecord;
+use zkwasm_host_circuits::host::db::TreeDB;
+use zkwasm_host_circuits::host::mongomerkle::MerkleRecord;
+
+/// Tree db without any external database, the records are lost once the db is dropped.
+#[derive(Default)]
+pub struct MemoryDB {
+    merkle_records: HashMap<[u8; 32], MerkleRecord>,
+    data_records: HashMap<[u8; 32], DataHashRecord>,
+}
+
+impl TreeDB for MemoryDB {
+    fn get_merkle_record(&self, hash: &[u8; 32]) -> Result<Option<MerkleRecord>> {
+        Ok(self.merkle_records.get(hash).cloned())
+    }
+
+    fn set_merkle_record(&mut self, record: MerkleRecord) -> Result<()> {
+        self.merkle_records.insert(record.hash, record);
+        Ok(())
+    }
+
+    fn set_merkle_records(&mut self, records: &Vec<MerkleRecord>) -> Result<()> {
+        for record in records {
+            self.set_merkle_record(record.clone())?;
+        }
+        Ok(())
+    }
+
+    fn get_data_record(&self, hash: &[u8; 32]) -> Result<Option<DataHashRecord>> {
+        Ok(self.data_records.get(hash).cloned())
+    }
+
+    fn set_data_record(&mut self, record: DataHashRecord) -> Result<()> {
+        self.data_records.insert(record.hash, record);
+        Ok(())
+    }
+}
+
+/// An entry of the log of `FileDB`.
+#[derive(Serialize, Deserialize)]
+enum LogEntry {
+    Merkle {
+        index: u64,
+        hash: [u8; 32],
+        left: Option<[u8; 32]>,
+        right: Option<[u8; 32]>,
+        data: Option<[u8; 32]>,
+    },
+    Data {
+        hash: [u8; 32],
+        data: Vec<u8>,
+    },
+}
+
+/// Tree db backed by an append-only log, each line of which is a JSON encoded record.
+///
+/// The log is replayed into memory when the db is opened, a record written later overrides
+/// the record with the same hash.
+pub struct FileDB {
+    memory: MemoryDB,
+    log: File,
+}
+
+impl FileDB {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut memory = MemoryDB::default();
+
+        if path.as_ref().exists() {
+            for line in BufReader::new(File::open(&path)?).lines() {
+                match serde_json::from_str(&line?)? {
+                    LogEntry::Merkle {
+                        index,
+                        hash,
+                        left,
+                        right,
+                        data,
+                    } => memory.set_merkle_record(MerkleRecord {
+                        index,
+                        hash,
+                        left,
+                        right,
+                        data,
+                    })?,
+                    LogEntry::Data { hash, data } => {
+                        memory.set_data_record(DataHashRecord { hash, data })?
+                    }
+                }
+            }
+        }
+
+        let log = OpenOptions::new().create(true).append(true).open(path)?;
+
+        Ok(FileDB { memory, log })
+    }
+
+    fn append(&mut self, entry: &LogEntry) -> Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        self.log.write_all(&line)?;
+        self.log.flush()?;
+
+        Ok(())
+    }
+}
+
+impl TreeDB for FileDB {
+    fn get_merkle_record(&self, hash: &[u8; 32]) -> Result<Option<MerkleRecord>> {
+        self.memory.get_merkle_record(hash)
+    }
+
+    fn set_merkle_record(&mut self, record: MerkleRecord) -> Result<()> {
+        self.append(&LogEntry::Merkle {
+            index: record.index,
+            hash: record.hash,
+            left: record.left,
+            right: record.right,
+            data: record.data,
+        })?;
+        self.memory.set_merkle_record(record)
+    }
+
+    fn set_merkle_records(&mut self, records: &Vec<MerkleRecord>) -> Result<()> {
+        for record in records {
+            self.set_merkle_record(record.clone())?;
+        }
+        Ok(())
+    }
+
+    fn get_data_record(&self, hash: &[u8; 32]) -> Result<Option<DataHashRecord>> {
+        self.memory.get_data_record(hash)
+    }
+
+    fn set_data_record(&mut self, record: DataHashRecord) -> Result<()> {
+        self.append(&LogEntry::Data {
+            hash: record.hash,
+            data: record.data.clone(),
+        })?;
+        self.memory.set_data_record(record)
+    }
+}
+
+/// Tree db reading through to `base` and keeping its own writes in memory, `base` is never
+/// modified.
+pub struct OverlayDB {
+    base: Rc<RefCell<dyn TreeDB>>,
+    memory: MemoryDB,
+}
+
+impl OverlayDB {
+    pub fn new(base: Rc<RefCell<dyn TreeDB>>) -> Self {
+        OverlayDB {
+            base,
+            memory: MemoryDB::default(),
+        }
+    }
+}
+
+impl TreeDB for OverlayDB {
+    fn get_merkle_record(&self, hash: &[u8; 32]) -> Result<Option<MerkleRecord>> {
+        match self.memory.get_merkle_record(hash)? {
+            Some(record) => Ok(Some(record)),
+            None => self.base.borrow().get_merkle_record(hash),
+        }
+    }
+
+    fn set_merkle_record(&mut self, record: MerkleRecord) -> Result<()> {
+        self.memory.set_merkle_record(record)
+    }
+
+    fn set_merkle_records(&mut self, records: &Vec<MerkleRecord>) -> Result<()> {
+        self.memory.set_merkle_records(records)
+    }
+
+    fn get_data_record(&self, hash: &[u8; 32]) -> Result<Option<DataHashRecord>> {
+        match self.memory.get_data_record(hash)? {
+            Some(record) => Ok(Some(record)),
+            None => self.base.borrow().get_data_record(hash),
+        }
+    }
+
+    fn set_data_record(&mut self, record: DataHashRecord) -> Result<()> {
+        self.memory.set_data_record(record)
+    }
+}
+
+/// The tree db of an execution replayed without writing to `db`. The Mongo db (`None`) is
+/// kept since its records are keyed by their hashes, writing them again doesn't change it.
+pub fn overlay_tree_db(db: Option<Rc<RefCell<dyn TreeDB>>>) -> Option<Rc<RefCell<dyn TreeDB>>> {
+    db.map(|db| Rc::new(RefCell::new(OverlayDB::new(db))) as Rc<RefCell<dyn TreeDB>>)
+}
+
+/// Opens the tree db specified by `memory` or the path of a `FileDB`.
+pub fn open_tree_db(db: &str) -> Result<Rc<RefCell<dyn TreeDB>>> {
+    if db == "memory" {
+        Ok(Rc::new(RefCell::new(MemoryDB::default())))
+    } else {
+        Ok(Rc::new(RefCell::new(FileDB::open(db)?)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_db_replay() {
+        let path = std::env::temp_dir().join(format!("file_db_{}.log", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let record = DataHashRecord {
+            hash: [1; 32],
+            data: vec![1, 2, 3],
+        };
+
+        {
+            let mut db = FileDB::open(&path).unwrap();
+            db.set_data_record(record.clone()).unwrap();
+            db.set_data_record(DataHashRecord {
+                hash: [1; 32],
+                data: vec![4, 5, 6],
+            })
+            .unwrap();
+        }
+
+        let db = FileDB::open(&path).unwrap();
+        let data = db.get_data_record(&[1; 32]).unwrap().unwrap().data;
+        assert_eq!(data, vec![4, 5, 6]);
+        assert!(db.get_data_record(&[2; 32]).unwrap().is_none());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_overlay_db() {
+        let base = open_tree_db("memory").unwrap();
+        base.borrow_mut()
+            .set_data_record(DataHashRecord {
+                hash: [1; 32],
+                data: vec![1, 2, 3],
+            })
+            .unwrap();
+
+        let mut overlay = OverlayDB::new(base.clone());
+        overlay
+            .set_data_record(DataHashRecord {
+                hash: [2; 32],
+                data: vec![4, 5, 6],
+            })
+            .unwrap();
+
+        let data = overlay.get_data_record(&[1; 32]).unwrap().unwrap().data;
+        assert_eq!(data, vec![1, 2, 3]);
+        assert!(overlay.get_data_record(&[2; 32]).unwrap().is_some());
+        assert!(base.borrow().get_data_record(&[2; 32]).unwrap().is_none());
+    }
+}
@@ -1,7 +1,7 @@
 use super::bls381_fq_to_limbs;
 use super::fetch_fq;
 use super::fetch_fq2;
-use delphinus_zkwasm::circuits::config::zkwasm_k;
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
@@ -25,6 +25,7 @@ struct BlsPairContext {
     pub result_cursor: usize,
     pub input_cursor: usize,
     pub used_round: usize,
+    pub k: u32,
 }
 
 impl BlsPairContext {
@@ -60,16 +61,33 @@ impl ForeignContext for BlsPairContext {
     fn get_statics(&self) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: Bls381PairChip::max_rounds(zkwasm_k() as usize),
+            max_round: Bls381PairChip::max_rounds(self.k as usize),
         })
     }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some(Bls381PairChip::max_rounds(k as usize))
+    }
+
+    fn rounds_of_call(&self, op: usize, _args: &wasmi::RuntimeArgs) -> usize {
+        // The pairing is computed once the last limb of g2 is pushed.
+        if op == ForeignInst::BlsPairG2 as usize && self.input_cursor == 32 {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_blspair_foreign(env: &mut HostEnv) {
-    let foreign_blspair_plugin = env
-        .external_env
-        .register_plugin("foreign_blspair", Box::new(BlsPairContext::default()));
+pub fn register_blspair_foreign(env: &mut HostEnv, circuit_config: &CircuitConfig) {
+    let foreign_blspair_plugin = env.external_env.register_plugin(
+        "foreign_blspair",
+        Box::new(BlsPairContext {
+            k: circuit_config.k(),
+            ..Default::default()
+        }),
+    );
 
     env.external_env.register_function(
         "blspair_g1",
@@ -87,7 +105,7 @@ pub fn register_blspair_foreign(env: &mut HostEnv) {
                     context.limbs.push(args.nth(0));
                     context.input_cursor += 1;
                 }
-                None
+                Ok(None)
             },
         ),
     );
@@ -125,7 +143,7 @@ pub fn register_blspair_foreign(env: &mut HostEnv) {
                     context.limbs.push(args.nth(0));
                     context.input_cursor += 1;
                 };
-                None
+                Ok(None)
             },
         ),
     );
@@ -142,7 +160,7 @@ pub fn register_blspair_foreign(env: &mut HostEnv) {
                     context.result_limbs[context.result_cursor] as i64,
                 ));
                 context.result_cursor += 1;
-                ret
+                Ok(ret)
             },
         ),
     );
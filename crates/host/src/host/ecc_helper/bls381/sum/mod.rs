@@ -1,4 +1,4 @@
-use delphinus_zkwasm::circuits::config::zkwasm_k;
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
@@ -22,6 +22,7 @@ struct BlsSumContext {
     pub result_cursor: usize,
     pub input_cursor: usize,
     pub used_round: usize,
+    pub k: u32,
 }
 
 impl BlsSumContext {
@@ -42,16 +43,24 @@ impl ForeignContext for BlsSumContext {
     fn get_statics(&self) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: Bls381SumChip::max_rounds(zkwasm_k() as usize),
+            max_round: Bls381SumChip::max_rounds(self.k as usize),
         })
     }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some(Bls381SumChip::max_rounds(k as usize))
+    }
 }
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_blssum_foreign(env: &mut HostEnv) {
-    let foreign_blssum_plugin = env
-        .external_env
-        .register_plugin("foreign_blssum", Box::new(BlsSumContext::default()));
+pub fn register_blssum_foreign(env: &mut HostEnv, circuit_config: &CircuitConfig) {
+    let foreign_blssum_plugin = env.external_env.register_plugin(
+        "foreign_blssum",
+        Box::new(BlsSumContext {
+            k: circuit_config.k(),
+            ..Default::default()
+        }),
+    );
 
     env.external_env.register_function(
         "blssum_g1",
@@ -69,7 +78,7 @@ pub fn register_blssum_foreign(env: &mut HostEnv) {
                     context.limbs.push(args.nth(0));
                     context.input_cursor += 1;
                 }
-                None
+                Ok(None)
             },
         ),
     );
@@ -106,7 +115,7 @@ pub fn register_blssum_foreign(env: &mut HostEnv) {
                     limbs[context.result_cursor] as i64,
                 ));
                 context.result_cursor += 1;
-                ret
+                Ok(ret)
             },
         ),
     );
@@ -1,6 +1,7 @@
 pub mod pair;
 pub mod sum;
 use ark_std::Zero;
+use delphinus_zkwasm::runtime::host::ForeignError;
 use halo2_proofs::arithmetic::CurveAffine;
 use halo2_proofs::pairing::bn256::Fq as BN254Fq;
 use halo2_proofs::pairing::bn256::Fq2 as BN254Fq2;
@@ -17,12 +18,19 @@ const LIMBNB: usize = 5;
 use super::bn_to_field;
 use super::field_to_bn;
 
-fn fetch_fr(limbs: &Vec<u64>) -> Fr {
+fn fetch_fr(limbs: &Vec<u64>) -> Result<Fr, ForeignError> {
+    if limbs.len() != 4 {
+        return Err(ForeignError::InvalidArgument(format!(
+            "a scalar has 4 limbs, {} are pushed",
+            limbs.len()
+        )));
+    }
+
     let mut bn = BigUint::zero();
     for i in 0..4 {
         bn.add_assign(BigUint::from_u64(limbs[i]).unwrap() << (i * 64))
     }
-    bn_to_field(&bn)
+    Ok(bn_to_field(&bn))
 }
 
 pub fn fetch_fq(limbs: &Vec<u64>, index: usize) -> BN254Fq {
@@ -41,14 +49,22 @@ pub fn fetch_fq2(limbs: &Vec<u64>, index: usize) -> BN254Fq2 {
 }
 
 /// decode g1 from limbs where limbs[11] indicates whether the point is identity
-fn fetch_g1(limbs: &Vec<u64>) -> G1Affine {
-    assert_eq!(limbs.len(), LIMBNB * 2 + 1);
+fn fetch_g1(limbs: &Vec<u64>) -> Result<G1Affine, ForeignError> {
+    if limbs.len() != LIMBNB * 2 + 1 {
+        return Err(ForeignError::InvalidArgument(format!(
+            "a g1 point has {} limbs, {} are pushed",
+            LIMBNB * 2 + 1,
+            limbs.len()
+        )));
+    }
     let g1_identity = limbs[LIMBNB * 2];
     if g1_identity == 1 {
-        G1Affine::generator()
+        Ok(G1Affine::generator())
     } else {
         let opt: Option<_> = G1Affine::from_xy(fetch_fq(limbs, 0), fetch_fq(limbs, 1)).into();
-        opt.expect("from xy failed, not on curve")
+        opt.ok_or_else(|| {
+            ForeignError::InvalidArgument("the g1 point is not on the curve".to_string())
+        })
     }
 }
 
@@ -1,4 +1,4 @@
-use delphinus_zkwasm::circuits::config::zkwasm_k;
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
@@ -32,6 +32,7 @@ struct BN254PairContext {
     pub result_cursor: usize,
     pub input_cursor: usize,
     pub used_round: usize,
+    pub k: u32,
 }
 
 impl BN254PairContext {
@@ -67,16 +68,24 @@ impl ForeignContext for BN254PairContext {
     fn get_statics(&self) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: Bn256PairChip::max_rounds(zkwasm_k() as usize),
+            max_round: Bn256PairChip::max_rounds(self.k as usize),
         })
     }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some(Bn256PairChip::max_rounds(k as usize))
+    }
 }
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_bn254pair_foreign(env: &mut HostEnv) {
-    let foreign_blspair_plugin = env
-        .external_env
-        .register_plugin("foreign_blspair", Box::new(BN254PairContext::default()));
+pub fn register_bn254pair_foreign(env: &mut HostEnv, circuit_config: &CircuitConfig) {
+    let foreign_blspair_plugin = env.external_env.register_plugin(
+        "foreign_blspair",
+        Box::new(BN254PairContext {
+            k: circuit_config.k(),
+            ..Default::default()
+        }),
+    );
 
     env.external_env.register_function(
         "bn254pair_g1",
@@ -94,7 +103,7 @@ pub fn register_bn254pair_foreign(env: &mut HostEnv) {
                     context.limbs.push(args.nth(0));
                     context.input_cursor += 1;
                 }
-                None
+                Ok(None)
             },
         ),
     );
@@ -138,7 +147,7 @@ pub fn register_bn254pair_foreign(env: &mut HostEnv) {
                     context.limbs.push(args.nth(0));
                     context.input_cursor += 1;
                 };
-                None
+                Ok(None)
             },
         ),
     );
@@ -160,7 +169,7 @@ pub fn register_bn254pair_foreign(env: &mut HostEnv) {
                     context.result_limbs[context.result_cursor] as i64,
                 ));
                 context.result_cursor += 1;
-                ret
+                Ok(ret)
             },
         ),
     );
@@ -1,6 +1,7 @@
-use delphinus_zkwasm::circuits::config::zkwasm_k;
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignError;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
 use halo2_proofs::pairing::bn256::G1Affine;
 use halo2_proofs::pairing::group::prime::PrimeCurveAffine;
@@ -25,6 +26,7 @@ struct BN254SumContext {
     pub result_limbs: Option<Vec<u64>>,
     pub result_cursor: usize,
     pub used_round: usize,
+    pub k: u32,
 }
 
 impl BN254SumContext {
@@ -40,7 +42,7 @@ impl BN254SumContext {
         }
     }
 
-    pub fn default() -> Self {
+    pub fn new(k: u32) -> Self {
         BN254SumContext {
             acc: G1Affine::identity(),
             limbs: vec![],
@@ -48,6 +50,7 @@ impl BN254SumContext {
             result_limbs: None,
             result_cursor: 0,
             used_round: 0,
+            k,
         }
     }
 
@@ -78,9 +81,21 @@ impl ForeignContext for BN254SumContext {
     fn get_statics(&self) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: Bn256SumChip::max_rounds(zkwasm_k() as usize),
+            max_round: Bn256SumChip::max_rounds(self.k as usize),
         })
     }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some(Bn256SumChip::max_rounds(k as usize))
+    }
+
+    fn rounds_of_call(&self, op: usize, _args: &wasmi::RuntimeArgs) -> usize {
+        if op == Bn254SumNew as usize {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 /*
@@ -91,10 +106,11 @@ impl ForeignContext for BN254SumContext {
  */
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_bn254sum_foreign(env: &mut HostEnv) {
-    let foreign_bn254sum_plugin = env
-        .external_env
-        .register_plugin("foreign_bn254sum", Box::new(BN254SumContext::default()));
+pub fn register_bn254sum_foreign(env: &mut HostEnv, circuit_config: &CircuitConfig) {
+    let foreign_bn254sum_plugin = env.external_env.register_plugin(
+        "foreign_bn254sum",
+        Box::new(BN254SumContext::new(circuit_config.k())),
+    );
 
     env.external_env.register_function(
         "bn254_sum_new",
@@ -105,7 +121,7 @@ pub fn register_bn254sum_foreign(env: &mut HostEnv) {
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BN254SumContext>().unwrap();
                 context.bn254_sum_new(args.nth::<u64>(0) as usize);
-                None
+                Ok(None)
             },
         ),
     );
@@ -119,7 +135,7 @@ pub fn register_bn254sum_foreign(env: &mut HostEnv) {
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BN254SumContext>().unwrap();
                 context.bn254_sum_push_scalar(args.nth::<u64>(0));
-                None
+                Ok(None)
             },
         ),
     );
@@ -133,7 +149,7 @@ pub fn register_bn254sum_foreign(env: &mut HostEnv) {
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BN254SumContext>().unwrap();
                 context.bn254_sum_push_limb(args.nth::<u64>(0));
-                None
+                Ok(None)
             },
         ),
     );
@@ -147,25 +163,26 @@ pub fn register_bn254sum_foreign(env: &mut HostEnv) {
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BN254SumContext>().unwrap();
                 log::debug!("calculate finalize");
-                context.result_limbs.clone().map_or_else(
-                    || {
-                        let coeff = fetch_fr(&context.coeffs);
-                        log::debug!("coeff is {:?}", coeff);
-                        let g1 = fetch_g1(&context.limbs);
-                        log::debug!("g1 is {:?}", g1);
-                        let next = g1 * coeff;
-                        let g1result = context.acc.add(next).into();
-                        log::debug!("msm result: {:?}", g1result);
-                        context.bn254_result_to_limbs(g1result);
-                    },
-                    |_| (),
-                );
-                let limbs = context.result_limbs.clone().unwrap();
-                let ret = Some(wasmi::RuntimeValue::I64(
-                    limbs[context.result_cursor] as i64,
-                ));
+                if context.result_limbs.is_none() {
+                    let coeff = fetch_fr(&context.coeffs)?;
+                    log::debug!("coeff is {:?}", coeff);
+                    let g1 = fetch_g1(&context.limbs)?;
+                    log::debug!("g1 is {:?}", g1);
+                    let next = g1 * coeff;
+                    let g1result = context.acc.add(next).into();
+                    log::debug!("msm result: {:?}", g1result);
+                    context.bn254_result_to_limbs(g1result);
+                }
+                let limbs = context.result_limbs.as_ref().unwrap();
+                let limb = *limbs.get(context.result_cursor).ok_or_else(|| {
+                    ForeignError::InvalidArgument(format!(
+                        "bn254_sum_finalize is invoked {} times, the result has {} limbs",
+                        context.result_cursor + 1,
+                        limbs.len()
+                    ))
+                })?;
                 context.result_cursor += 1;
-                ret
+                Ok(Some(wasmi::RuntimeValue::I64(limb as i64)))
             },
         ),
     );
@@ -1,4 +1,4 @@
-use delphinus_zkwasm::circuits::config::zkwasm_k;
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
@@ -37,10 +37,11 @@ pub struct BabyJubjubSumContext {
     pub result_cursor: usize,
     pub input_cursor: usize,
     pub used_round: usize,
+    pub k: u32,
 }
 
 impl BabyJubjubSumContext {
-    pub fn default() -> Self {
+    pub fn new(k: u32) -> Self {
         BabyJubjubSumContext {
             acc: jubjub::Point::identity(),
             limbs: vec![],
@@ -49,6 +50,7 @@ impl BabyJubjubSumContext {
             result_cursor: 0,
             input_cursor: 0,
             used_round: 0,
+            k,
         }
     }
 
@@ -117,16 +119,28 @@ impl ForeignContext for BabyJubjubSumContext {
     fn get_statics(&self) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: AltJubChip::max_rounds(zkwasm_k() as usize),
+            max_round: AltJubChip::max_rounds(self.k as usize),
         })
     }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some(AltJubChip::max_rounds(k as usize))
+    }
+
+    fn rounds_of_call(&self, op: usize, _args: &wasmi::RuntimeArgs) -> usize {
+        if op == JubjubSumNew as usize {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_babyjubjubsum_foreign(env: &mut HostEnv) {
+pub fn register_babyjubjubsum_foreign(env: &mut HostEnv, circuit_config: &CircuitConfig) {
     let foreign_babyjubjubsum_plugin = env.external_env.register_plugin(
         "foreign_babyjubjubsum",
-        Box::new(BabyJubjubSumContext::default()),
+        Box::new(BabyJubjubSumContext::new(circuit_config.k())),
     );
 
     env.external_env.register_function(
@@ -138,7 +152,7 @@ pub fn register_babyjubjubsum_foreign(env: &mut HostEnv) {
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BabyJubjubSumContext>().unwrap();
                 context.babyjubjub_sum_new(args.nth::<u64>(0) as usize);
-                None
+                Ok(None)
             },
         ),
     );
@@ -152,7 +166,7 @@ pub fn register_babyjubjubsum_foreign(env: &mut HostEnv) {
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<BabyJubjubSumContext>().unwrap();
                 context.babyjubjub_sum_push(args.nth(0));
-                None
+                Ok(None)
             },
         ),
     );
@@ -168,7 +182,7 @@ pub fn register_babyjubjubsum_foreign(env: &mut HostEnv) {
                 let ret = Some(wasmi::RuntimeValue::I64(
                     context.babyjubjub_sum_finalize() as i64
                 ));
-                ret
+                Ok(ret)
             },
         ),
     );
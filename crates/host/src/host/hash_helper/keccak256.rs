@@ -1,6 +1,7 @@
-use delphinus_zkwasm::circuits::config::zkwasm_k;
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignError;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
 use std::rc::Rc;
 use wasmi::tracer::Observer;
@@ -19,10 +20,16 @@ struct Generator {
 }
 
 impl Generator {
-    fn gen(&mut self) -> u64 {
-        let r = self.values[self.cursor];
+    fn gen(&mut self) -> Result<u64, ForeignError> {
+        let r = *self.values.get(self.cursor).ok_or_else(|| {
+            ForeignError::InvalidArgument(format!(
+                "keccak_finalize is invoked {} times, the hash has {} words",
+                self.cursor + 1,
+                self.values.len()
+            ))
+        })?;
         self.cursor += 1;
-        r
+        Ok(r)
     }
 }
 
@@ -31,10 +38,11 @@ struct Keccak256Context {
     pub generator: Generator,
     pub buf: Vec<u64>,
     pub used_round: usize,
+    pub k: u32,
 }
 
 impl Keccak256Context {
-    fn default() -> Self {
+    fn new(k: u32) -> Self {
         Keccak256Context {
             hasher: None,
             generator: Generator {
@@ -43,6 +51,7 @@ impl Keccak256Context {
             },
             buf: vec![],
             used_round: 0,
+            k,
         }
     }
 
@@ -59,8 +68,13 @@ impl Keccak256Context {
         self.buf.push(v);
     }
 
-    pub fn keccak_finalize(&mut self) -> u64 {
-        assert!(self.buf.len() == 17);
+    pub fn keccak_finalize(&mut self) -> Result<u64, ForeignError> {
+        if self.buf.len() != 17 {
+            return Err(ForeignError::InvalidArgument(format!(
+                "keccak_finalize expects 17 pushed words, {} are pushed",
+                self.buf.len()
+            )));
+        }
         if self.generator.cursor == 0 {
             self.hasher.as_mut().map(|s| {
                 log::debug!("perform hash with {:?}", self.buf);
@@ -76,16 +90,29 @@ impl ForeignContext for Keccak256Context {
     fn get_statics(&self) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: KeccakChip::max_rounds(zkwasm_k() as usize),
+            max_round: KeccakChip::max_rounds(self.k as usize),
         })
     }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some(KeccakChip::max_rounds(k as usize))
+    }
+
+    fn rounds_of_call(&self, op: usize, args: &wasmi::RuntimeArgs) -> usize {
+        if op == Keccak256New as usize && args.nth::<u64>(0) != 0 {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_keccak_foreign(env: &mut HostEnv) {
-    let foreign_keccak_plugin = env
-        .external_env
-        .register_plugin("foreign_keccak", Box::new(Keccak256Context::default()));
+pub fn register_keccak_foreign(env: &mut HostEnv, circuit_config: &CircuitConfig) {
+    let foreign_keccak_plugin = env.external_env.register_plugin(
+        "foreign_keccak",
+        Box::new(Keccak256Context::new(circuit_config.k())),
+    );
 
     env.external_env.register_function(
         "keccak_new",
@@ -97,7 +124,7 @@ pub fn register_keccak_foreign(env: &mut HostEnv) {
                 let context = context.downcast_mut::<Keccak256Context>().unwrap();
                 log::debug!("buf len is {}", context.buf.len());
                 context.keccak_new(args.nth::<u64>(0) as usize);
-                None
+                Ok(None)
             },
         ),
     );
@@ -111,7 +138,7 @@ pub fn register_keccak_foreign(env: &mut HostEnv) {
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<Keccak256Context>().unwrap();
                 context.keccak_push(args.nth::<u64>(0) as u64);
-                None
+                Ok(None)
             },
         ),
     );
@@ -124,7 +151,9 @@ pub fn register_keccak_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<Keccak256Context>().unwrap();
-                Some(wasmi::RuntimeValue::I64(context.keccak_finalize() as i64))
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.keccak_finalize()? as i64
+                )))
             },
         ),
     );
@@ -1,6 +1,7 @@
-use delphinus_zkwasm::circuits::config::zkwasm_k;
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignError;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
 use ff::PrimeField;
 use halo2_proofs::pairing::bn256::Fr;
@@ -44,13 +45,15 @@ pub struct Generator {
 }
 
 impl Generator {
-    pub fn gen(&mut self) -> u64 {
-        let r = self.values[self.cursor];
+    pub fn gen(&mut self) -> Result<u64, ForeignError> {
+        let r = *self.values.get(self.cursor).ok_or_else(|| {
+            ForeignError::InvalidArgument("the hash is finalized before it is created".to_string())
+        })?;
         self.cursor += 1;
         if self.cursor == 4 {
             self.cursor = 0;
         }
-        r
+        Ok(r)
     }
 }
 
@@ -64,10 +67,11 @@ pub struct PoseidonContext {
     pub buf: Vec<Fr>,
     pub fieldreducer: Reduce<Fr>,
     pub used_round: usize,
+    pub k: u32,
 }
 
 impl PoseidonContext {
-    pub fn default() -> Self {
+    pub fn new(k: u32) -> Self {
         PoseidonContext {
             hasher: None,
             fieldreducer: new_reduce(vec![ReduceRule::Field(Fr::zero(), 64)]),
@@ -77,6 +81,7 @@ impl PoseidonContext {
                 values: vec![],
             },
             used_round: 0,
+            k,
         }
     }
 
@@ -96,8 +101,13 @@ impl PoseidonContext {
         }
     }
 
-    pub fn poseidon_finalize(&mut self) -> u64 {
-        assert!(self.buf.len() == 8);
+    pub fn poseidon_finalize(&mut self) -> Result<u64, ForeignError> {
+        if self.buf.len() != 8 {
+            return Err(ForeignError::InvalidArgument(format!(
+                "poseidon_finalize expects 8 pushed fields, {} are pushed",
+                self.buf.len()
+            )));
+        }
         if self.generator.cursor == 0 {
             self.hasher.as_mut().map(|s| {
                 log::debug!("perform hash with {:?}", self.buf);
@@ -117,16 +127,29 @@ impl ForeignContext for PoseidonContext {
     fn get_statics(&self) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: PoseidonChip::max_rounds(zkwasm_k() as usize),
+            max_round: PoseidonChip::max_rounds(self.k as usize),
         })
     }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some(PoseidonChip::max_rounds(k as usize))
+    }
+
+    fn rounds_of_call(&self, op: usize, args: &wasmi::RuntimeArgs) -> usize {
+        if op == PoseidonNew as usize && args.nth::<u64>(0) != 0 {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_poseidon_foreign(env: &mut HostEnv) {
-    let foreign_poseidon_plugin = env
-        .external_env
-        .register_plugin("foreign_poseidon", Box::new(PoseidonContext::default()));
+pub fn register_poseidon_foreign(env: &mut HostEnv, circuit_config: &CircuitConfig) {
+    let foreign_poseidon_plugin = env.external_env.register_plugin(
+        "foreign_poseidon",
+        Box::new(PoseidonContext::new(circuit_config.k())),
+    );
 
     env.external_env.register_function(
         "poseidon_new",
@@ -138,7 +161,7 @@ pub fn register_poseidon_foreign(env: &mut HostEnv) {
                 let context = context.downcast_mut::<PoseidonContext>().unwrap();
                 log::debug!("buf len is {}", context.buf.len());
                 context.poseidon_new(args.nth::<u64>(0) as usize);
-                None
+                Ok(None)
             },
         ),
     );
@@ -152,7 +175,7 @@ pub fn register_poseidon_foreign(env: &mut HostEnv) {
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<PoseidonContext>().unwrap();
                 context.poseidon_push(args.nth::<u64>(0) as u64);
-                None
+                Ok(None)
             },
         ),
     );
@@ -165,8 +188,29 @@ pub fn register_poseidon_foreign(env: &mut HostEnv) {
         Rc::new(
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<PoseidonContext>().unwrap();
-                Some(wasmi::RuntimeValue::I64(context.poseidon_finalize() as i64))
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.poseidon_finalize()? as i64
+                )))
             },
         ),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use delphinus_zkwasm::circuits::config::MIN_K;
+
+    #[test]
+    fn test_poseidon_finalize_incomplete() {
+        let mut context = PoseidonContext::new(MIN_K);
+
+        context.poseidon_new(1);
+        context.poseidon_push(1);
+
+        assert!(matches!(
+            context.poseidon_finalize(),
+            Err(ForeignError::InvalidArgument(_))
+        ));
+    }
+}
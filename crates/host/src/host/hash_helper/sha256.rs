@@ -1,5 +1,6 @@
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignError;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
 use sha2::Digest;
 use std::rc::Rc;
@@ -42,10 +43,16 @@ struct Generator {
 }
 
 impl Generator {
-    fn gen(&mut self) -> u64 {
-        let r = self.values[self.cursor];
+    fn gen(&mut self) -> Result<u64, ForeignError> {
+        let r = *self.values.get(self.cursor).ok_or_else(|| {
+            ForeignError::InvalidArgument(format!(
+                "sha256_finalize is invoked {} times, the hash has {} words",
+                self.cursor + 1,
+                self.values.len()
+            ))
+        })?;
         self.cursor += 1;
-        r
+        Ok(r)
     }
 }
 
@@ -56,7 +63,7 @@ struct Sha256Context {
 }
 
 impl Sha256Context {
-    fn default() -> Self {
+    fn new() -> Self {
         Sha256Context {
             hasher: None,
             generator: Generator {
@@ -70,7 +77,8 @@ impl Sha256Context {
 
 impl ForeignContext for Sha256Context {
     fn get_statics(&self) -> Option<ForeignStatics> {
-        // we did not support full sha256 as host yet
+        // There is no host circuit of sha256 yet, hence the calls are not proven and no
+        // rounds are counted.
         None
     }
 }
@@ -79,7 +87,7 @@ use specs::external_host_call_table::ExternalHostCallSignature;
 pub fn register_sha256_foreign(env: &mut HostEnv) {
     let foreign_sha256_plugin = env
         .external_env
-        .register_plugin("foreign_sh256", Box::new(Sha256Context::default()));
+        .register_plugin("foreign_sh256", Box::new(Sha256Context::new()));
 
     env.external_env.register_function(
         "sha256_new",
@@ -97,7 +105,7 @@ pub fn register_sha256_foreign(env: &mut HostEnv) {
                     context.hasher = Some(s);
                     context.size = args.nth::<u64>(0) as usize;
                 });
-                None
+                Ok(None)
             },
         ),
     );
@@ -123,7 +131,7 @@ pub fn register_sha256_foreign(env: &mut HostEnv) {
                     r.truncate(sz);
                     s.update(r);
                 });
-                None
+                Ok(None)
             },
         ),
     );
@@ -142,9 +150,12 @@ pub fn register_sha256_foreign(env: &mut HostEnv) {
                         .chunks(8)
                         .map(|x| u64::from_le_bytes(x.to_vec().try_into().unwrap()))
                         .collect::<Vec<u64>>();
+                    context.generator.cursor = 0;
                 });
                 context.hasher = None;
-                Some(wasmi::RuntimeValue::I64(context.generator.gen() as i64))
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.generator.gen()? as i64
+                )))
             },
         ),
     );
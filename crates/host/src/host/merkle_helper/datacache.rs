@@ -127,7 +127,7 @@ pub fn register_datacache_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<CacheContext>().unwrap();
                 context.set_mode(args.nth(0));
-                None
+                Ok(None)
             },
         ),
     );
@@ -141,7 +141,7 @@ pub fn register_datacache_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<CacheContext>().unwrap();
                 context.set_data_hash(args.nth(0));
-                None
+                Ok(None)
             },
         ),
     );
@@ -155,7 +155,7 @@ pub fn register_datacache_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<CacheContext>().unwrap();
                 context.store_data(args.nth(0));
-                None
+                Ok(None)
             },
         ),
     );
@@ -169,7 +169,7 @@ pub fn register_datacache_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<CacheContext>().unwrap();
                 let ret = Some(wasmi::RuntimeValue::I64(context.fetch_data() as i64));
-                ret
+                Ok(ret)
             },
         ),
     );
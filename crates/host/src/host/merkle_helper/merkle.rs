@@ -1,6 +1,7 @@
-use delphinus_zkwasm::circuits::config::zkwasm_k;
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignError;
 use delphinus_zkwasm::runtime::host::ForeignStatics;
 use halo2_proofs::pairing::bn256::Fr;
 use std::cell::RefCell;
@@ -20,6 +21,8 @@ use zkwasm_host_circuits::host::ForeignInst::MerkleSetRoot;
 use zkwasm_host_circuits::host::Reduce;
 use zkwasm_host_circuits::host::ReduceRule;
 
+use super::state::MerkleRoots;
+
 const MERKLE_TREE_HEIGHT: usize = 32;
 
 pub struct MerkleContext {
@@ -33,7 +36,9 @@ pub struct MerkleContext {
     pub mongo_merkle: Option<merklehelper::MongoMerkle<MERKLE_TREE_HEIGHT>>,
     pub mongo_datahash: datahelper::MongoDataHash,
     pub tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+    pub roots: Rc<RefCell<MerkleRoots>>,
     pub used_round: usize,
+    pub k: u32,
 }
 
 fn new_reduce(rules: Vec<ReduceRule<Fr>>) -> Reduce<Fr> {
@@ -41,7 +46,11 @@ fn new_reduce(rules: Vec<ReduceRule<Fr>>) -> Reduce<Fr> {
 }
 
 impl MerkleContext {
-    pub fn new(tree_db: Option<Rc<RefCell<dyn TreeDB>>>) -> Self {
+    pub fn new(
+        k: u32,
+        tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+        roots: Rc<RefCell<MerkleRoots>>,
+    ) -> Self {
         MerkleContext {
             set_root: new_reduce(vec![ReduceRule::Bytes(vec![], 4)]),
             get_root: new_reduce(vec![ReduceRule::Bytes(vec![], 4)]),
@@ -53,7 +62,9 @@ impl MerkleContext {
             mongo_merkle: None,
             mongo_datahash: datahelper::MongoDataHash::construct([0; 32], tree_db.clone()),
             tree_db,
+            roots,
             used_round: 0,
+            k,
         }
     }
 
@@ -61,24 +72,30 @@ impl MerkleContext {
         self.set_root.reduce(v);
         if self.set_root.cursor == 0 {
             log::debug!("set root: {:?}", &self.set_root.rules[0].bytes_value());
+            let root: [u8; 32] = self.set_root.rules[0]
+                .bytes_value()
+                .unwrap()
+                .try_into()
+                .unwrap();
             self.mongo_merkle = Some(merklehelper::MongoMerkle::construct(
                 [0; 32],
-                self.set_root.rules[0]
-                    .bytes_value()
-                    .unwrap()
-                    .try_into()
-                    .unwrap(),
+                root,
                 self.tree_db.clone(),
             ));
+            self.roots.borrow_mut().set_root(root);
         }
     }
 
-    pub fn merkle_getroot(&mut self) -> u64 {
-        let mt = self
-            .mongo_merkle
-            .as_ref()
-            .expect("merkle db not initialized");
-        let hash = mt.get_root_hash();
+    fn merkle_tree(
+        &mut self,
+    ) -> Result<&mut merklehelper::MongoMerkle<MERKLE_TREE_HEIGHT>, ForeignError> {
+        self.mongo_merkle
+            .as_mut()
+            .ok_or_else(|| ForeignError::InvalidArgument("the merkle root is not set".to_string()))
+    }
+
+    pub fn merkle_getroot(&mut self) -> Result<u64, ForeignError> {
+        let hash = self.merkle_tree()?.get_root_hash();
         let values = hash
             .chunks(8)
             .into_iter()
@@ -86,7 +103,7 @@ impl MerkleContext {
             .collect::<Vec<u64>>();
         let cursor = self.get_root.cursor;
         self.get_root.reduce(values[self.get_root.cursor]);
-        values[cursor]
+        Ok(values[cursor])
     }
 
     /// reset the address of merkle op together with the data and data_cursor
@@ -100,38 +117,51 @@ impl MerkleContext {
         self.address.reduce(v);
     }
 
-    pub fn merkle_set(&mut self, v: u64) {
+    pub fn merkle_set(&mut self, v: u64) -> Result<(), ForeignError> {
         self.set.reduce(v);
         if self.set.cursor == 0 {
             let address = self.address.rules[0].u64_value().unwrap() as u32;
             let index = (address as u64) + (1u64 << MERKLE_TREE_HEIGHT) - 1;
-            let mt = self
-                .mongo_merkle
-                .as_mut()
-                .expect("merkle db not initialized");
             let hash = self.set.rules[0].bytes_value().unwrap();
-            mt.update_leaf_data_with_proof(index, &hash)
-                .expect("Unexpected failure: update leaf with proof fail");
+            let mt = self.merkle_tree()?;
+            mt.update_leaf_data_with_proof(index, &hash).map_err(|e| {
+                ForeignError::InvalidArgument(format!(
+                    "failed to update the leaf {}: {:?}",
+                    address, e
+                ))
+            })?;
+            let root = mt.get_root_hash();
+            self.roots.borrow_mut().update_root(root);
         }
+
+        Ok(())
     }
 
-    pub fn merkle_get(&mut self) -> u64 {
+    pub fn merkle_get(&mut self) -> Result<u64, ForeignError> {
         let address = self.address.rules[0].u64_value().unwrap() as u32;
         let index = (address as u64) + (1u64 << MERKLE_TREE_HEIGHT) - 1;
-        let mt = self
-            .mongo_merkle
-            .as_ref()
-            .expect("merkle db not initialized");
-        let (leaf, _) = mt
+        let (leaf, _) = self
+            .merkle_tree()?
             .get_leaf_with_proof(index)
-            .expect("Unexpected failure: get leaf fail");
+            .map_err(|e| {
+                ForeignError::InvalidArgument(format!(
+                    "failed to get the leaf {}: {:?}",
+                    address, e
+                ))
+            })?;
         let values = leaf.data_as_u64();
         if self.data_cursor == 0 {
             self.data = values;
         }
-        let v = values[self.data_cursor];
+        let v = *values.get(self.data_cursor).ok_or_else(|| {
+            ForeignError::InvalidArgument(format!(
+                "merkle_get is invoked {} times, the leaf has {} words",
+                self.data_cursor + 1,
+                values.len()
+            ))
+        })?;
         self.data_cursor += 1;
-        return v;
+        Ok(v)
     }
 }
 
@@ -141,16 +171,35 @@ impl ForeignContext for MerkleContext {
     fn get_statics(&self) -> Option<ForeignStatics> {
         Some(ForeignStatics {
             used_round: self.used_round,
-            max_round: MerkleChip::<Fr, MERKLE_TREE_HEIGHT>::max_rounds(zkwasm_k() as usize),
+            max_round: MerkleChip::<Fr, MERKLE_TREE_HEIGHT>::max_rounds(self.k as usize),
         })
     }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some(MerkleChip::<Fr, MERKLE_TREE_HEIGHT>::max_rounds(k as usize))
+    }
+
+    fn rounds_of_call(&self, op: usize, _args: &wasmi::RuntimeArgs) -> usize {
+        // Each merkle op starts with its address.
+        if op == MerkleAddress as usize && self.address.cursor == 0 {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 use specs::external_host_call_table::ExternalHostCallSignature;
-pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn TreeDB>>>) {
-    let foreign_merkle_plugin = env
-        .external_env
-        .register_plugin("foreign_merkle", Box::new(MerkleContext::new(tree_db)));
+pub fn register_merkle_foreign(
+    env: &mut HostEnv,
+    circuit_config: &CircuitConfig,
+    tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+    roots: Rc<RefCell<MerkleRoots>>,
+) {
+    let foreign_merkle_plugin = env.external_env.register_plugin(
+        "foreign_merkle",
+        Box::new(MerkleContext::new(circuit_config.k(), tree_db, roots)),
+    );
 
     env.external_env.register_function(
         "merkle_setroot",
@@ -161,7 +210,7 @@ pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<MerkleContext>().unwrap();
                 context.merkle_setroot(args.nth(0));
-                None
+                Ok(None)
             },
         ),
     );
@@ -174,7 +223,9 @@ pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn
         Rc::new(
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<MerkleContext>().unwrap();
-                Some(wasmi::RuntimeValue::I64(context.merkle_getroot() as i64))
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.merkle_getroot()? as i64
+                )))
             },
         ),
     );
@@ -188,7 +239,7 @@ pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<MerkleContext>().unwrap();
                 context.merkle_address(args.nth(0));
-                None
+                Ok(None)
             },
         ),
     );
@@ -201,8 +252,8 @@ pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn
         Rc::new(
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<MerkleContext>().unwrap();
-                context.merkle_set(args.nth(0));
-                None
+                context.merkle_set(args.nth(0))?;
+                Ok(None)
             },
         ),
     );
@@ -215,9 +266,36 @@ pub fn register_merkle_foreign(env: &mut HostEnv, tree_db: Option<Rc<RefCell<dyn
         Rc::new(
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<MerkleContext>().unwrap();
-                let ret = Some(wasmi::RuntimeValue::I64(context.merkle_get() as i64));
-                ret
+                let ret = Some(wasmi::RuntimeValue::I64(context.merkle_get()? as i64));
+                Ok(ret)
             },
         ),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::host::db::open_tree_db;
+    use delphinus_zkwasm::circuits::config::MIN_K;
+
+    #[test]
+    fn test_merkle_get_without_root() {
+        let mut context = MerkleContext::new(
+            MIN_K,
+            Some(open_tree_db("memory").unwrap()),
+            Rc::new(RefCell::new(MerkleRoots::default())),
+        );
+
+        context.merkle_address(0);
+
+        assert!(matches!(
+            context.merkle_get(),
+            Err(ForeignError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            context.merkle_getroot(),
+            Err(ForeignError::InvalidArgument(_))
+        ));
+    }
+}
@@ -1,2 +1,3 @@
 pub mod datacache;
 pub mod merkle;
+pub mod state;
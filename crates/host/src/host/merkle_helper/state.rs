@@ -0,0 +1,237 @@
+use anyhow::bail;
+use anyhow::Result;
+use halo2_proofs::pairing::bn256::Fr;
+use specs::trap::TRAP_OUTCOME_FLAG_SHIFT;
+
+use crate::host::ecc_helper::field_to_bn;
+
+/// Number of the `u64` values of a merkle root.
+pub const MERKLE_ROOT_WORDS: usize = 4;
+
+/// Roots of the merkle tree observed during an execution.
+#[derive(Clone, Debug, Default)]
+pub struct MerkleRoots {
+    /// The first root set by `merkle_setroot`
+    pub initial: Option<[u8; 32]>,
+    /// The root after the last `merkle_setroot` or `merkle_set`
+    pub last: Option<[u8; 32]>,
+}
+
+impl MerkleRoots {
+    pub fn set_root(&mut self, root: [u8; 32]) {
+        if self.initial.is_none() {
+            self.initial = Some(root);
+        }
+        self.last = Some(root);
+    }
+
+    pub fn update_root(&mut self, root: [u8; 32]) {
+        self.last = Some(root);
+    }
+}
+
+/// The roots of the merkle tree at the beginning and the end of an execution.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RootTransition {
+    pub initial_root: [u8; 32],
+    pub final_root: [u8; 32],
+}
+
+impl RootTransition {
+    /// Reads the roots from the instances of a proof, the initial root is the first
+    /// `MERKLE_ROOT_WORDS` public inputs and the final root is the last `MERKLE_ROOT_WORDS`
+    /// outputs of the execution. The outputs are followed by the trap outcome if the execution
+    /// traps, which is rejected since the execution has no final root.
+    pub fn from_instances(instances: &[Fr]) -> Result<Self> {
+        let instances = instances
+            .iter()
+            .map(decode_instance)
+            .collect::<Result<Vec<_>>>()?;
+
+        let instances = match instances.split_last() {
+            Some(((outcome, true), _)) => bail!(
+                "The execution traps with the outcome {:#x}, it has no final merkle root.",
+                outcome
+            ),
+            _ => instances
+                .iter()
+                .map(|(value, is_trap_outcome)| {
+                    if *is_trap_outcome {
+                        bail!("The trap outcome {:#x} is not the last instance.", value);
+                    }
+
+                    Ok(*value)
+                })
+                .collect::<Result<Vec<_>>>()?,
+        };
+
+        if instances.len() < 2 * MERKLE_ROOT_WORDS {
+            bail!(
+                "The proof has {} instances, at least {} are required for the merkle roots.",
+                instances.len(),
+                2 * MERKLE_ROOT_WORDS
+            );
+        }
+
+        Ok(RootTransition {
+            initial_root: u64s_to_root(&instances[..MERKLE_ROOT_WORDS]),
+            final_root: u64s_to_root(&instances[instances.len() - MERKLE_ROOT_WORDS..]),
+        })
+    }
+
+    /// Checks the roots of the instances against the roots observed during the execution.
+    pub fn check_observed(&self, roots: &MerkleRoots) -> Result<()> {
+        if roots.initial != Some(self.initial_root) {
+            bail!(
+                "The execution starts from the merkle root {:?} instead of the root {:?} of the public inputs.",
+                roots.initial,
+                self.initial_root
+            );
+        }
+
+        if roots.last != Some(self.final_root) {
+            bail!(
+                "The execution ends with the merkle root {:?}, but the root {:?} is output.",
+                roots.last,
+                self.final_root
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The merkle roots of sequential executions, each execution starts from the final root of
+/// the previous one.
+#[derive(Clone, Debug, Default)]
+pub struct MerkleState {
+    pub transitions: Vec<RootTransition>,
+}
+
+impl MerkleState {
+    /// The root which the next execution starts from.
+    pub fn latest_root(&self) -> Option<[u8; 32]> {
+        self.transitions
+            .last()
+            .map(|transition| transition.final_root)
+    }
+
+    /// Appends the roots of an execution, which must start from the latest root.
+    pub fn push(&mut self, transition: RootTransition) -> Result<()> {
+        if let Some(latest_root) = self.latest_root() {
+            if latest_root != transition.initial_root {
+                bail!(
+                    "Execution {} starts from the root {:?} instead of the final root {:?} of the previous execution.",
+                    self.transitions.len(),
+                    transition.initial_root,
+                    latest_root
+                );
+            }
+        }
+
+        self.transitions.push(transition);
+
+        Ok(())
+    }
+}
+
+/// Splits the root into the `u64` values read by `merkle_setroot`.
+pub fn root_to_u64s(root: &[u8; 32]) -> Vec<u64> {
+    root.chunks(8)
+        .map(|x| u64::from_le_bytes(x.try_into().unwrap()))
+        .collect()
+}
+
+/// Decodes an instance into its `u64` value, and whether it is a trap outcome, which is tagged
+/// by the bit `TRAP_OUTCOME_FLAG_SHIFT`.
+fn decode_instance(instance: &Fr) -> Result<(u64, bool)> {
+    let mut value = field_to_bn(instance);
+
+    let is_trap_outcome = value.bit(TRAP_OUTCOME_FLAG_SHIFT as u64);
+    value.set_bit(TRAP_OUTCOME_FLAG_SHIFT as u64, false);
+
+    match u64::try_from(&value) {
+        Ok(value) => Ok((value, is_trap_outcome)),
+        Err(_) => bail!(
+            "Instance {:?} is neither a u64 value nor a trap outcome.",
+            instance
+        ),
+    }
+}
+
+fn u64s_to_root(words: &[u64]) -> [u8; 32] {
+    let mut root = [0; 32];
+
+    for (chunk, word) in root.chunks_mut(8).zip(words.iter()) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use specs::trap::TrapCode;
+    use specs::trap::TrapOutcome;
+
+    use super::*;
+    use crate::host::ecc_helper::bn_to_field;
+
+    fn transition(initial: u8, last: u8) -> RootTransition {
+        RootTransition {
+            initial_root: [initial; 32],
+            final_root: [last; 32],
+        }
+    }
+
+    #[test]
+    fn test_chain_of_roots() {
+        let mut state = MerkleState::default();
+
+        state.push(transition(0, 1)).unwrap();
+        state.push(transition(1, 2)).unwrap();
+        assert_eq!(state.transitions.len(), 2);
+        assert_eq!(state.latest_root(), Some([2; 32]));
+
+        assert!(state.push(transition(1, 3)).is_err());
+    }
+
+    fn instances(words: &[u64]) -> Vec<Fr> {
+        words.iter().map(|word| Fr::from(*word)).collect()
+    }
+
+    #[test]
+    fn test_roots_of_instances() {
+        let mut words = root_to_u64s(&[1; 32]);
+        words.push(42);
+        words.extend(root_to_u64s(&[2; 32]));
+
+        let transition = RootTransition::from_instances(&instances(&words)).unwrap();
+        assert_eq!(transition, self::transition(1, 2));
+
+        assert!(RootTransition::from_instances(&instances(&words[..5])).is_err());
+    }
+
+    #[test]
+    fn test_roots_of_trapping_instances() {
+        let mut words = root_to_u64s(&[1; 32]);
+        words.extend(root_to_u64s(&[2; 32]));
+
+        let trap_outcome = bn_to_field(
+            &TrapOutcome {
+                code: TrapCode::Unreachable,
+                fid: 1,
+                iid: 2,
+            }
+            .encode_instance(),
+        );
+
+        let mut trapping_instances = instances(&words);
+        trapping_instances.push(trap_outcome);
+        assert!(RootTransition::from_instances(&trapping_instances).is_err());
+
+        let mut instances = instances(&words);
+        instances.insert(MERKLE_ROOT_WORDS, trap_outcome);
+        assert!(RootTransition::from_instances(&instances).is_err());
+    }
+}
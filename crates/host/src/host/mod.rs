@@ -1,3 +1,4 @@
+pub mod db;
 pub mod ecc_helper;
 pub mod hash_helper;
 pub mod merkle_helper;
@@ -1,4 +1,5 @@
 use delphinus_zkwasm::runtime::host::ForeignContext;
+use delphinus_zkwasm::runtime::host::ForeignError;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
@@ -35,8 +36,8 @@ impl WitnessContext {
         self.buf.insert(0, new);
     }
 
-    pub fn witness_pop(&mut self) -> u64 {
-        self.buf.pop().unwrap()
+    pub fn witness_pop(&mut self) -> Result<u64, ForeignError> {
+        self.buf.pop().ok_or(ForeignError::WitnessUnderflow)
     }
 
     pub fn witness_set_index(&mut self, index: u64) {
@@ -63,10 +64,11 @@ impl WitnessContext {
         }
     }
 
-    pub fn witness_indexed_pop(&mut self) -> u64 {
+    pub fn witness_indexed_pop(&mut self) -> Result<u64, ForeignError> {
         let mut bind = self.indexed_buf.borrow_mut();
-        let buf = bind.get_mut(&self.focus).unwrap();
-        buf.pop().unwrap()
+        bind.get_mut(&self.focus)
+            .and_then(|buf| buf.pop())
+            .ok_or(ForeignError::WitnessUnderflow)
     }
 }
 
@@ -87,7 +89,7 @@ pub fn register_witness_foreign(env: &mut HostEnv, index_map: Rc<RefCell<HashMap
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<WitnessContext>().unwrap();
                 context.witness_insert(args.nth::<u64>(0) as u64);
-                None
+                Ok(None)
             },
         ),
     );
@@ -101,7 +103,7 @@ pub fn register_witness_foreign(env: &mut HostEnv, index_map: Rc<RefCell<HashMap
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<WitnessContext>().unwrap();
                 context.witness_set_index(args.nth::<u64>(0) as u64);
-                None
+                Ok(None)
             },
         ),
     );
@@ -115,7 +117,7 @@ pub fn register_witness_foreign(env: &mut HostEnv, index_map: Rc<RefCell<HashMap
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<WitnessContext>().unwrap();
                 context.witness_indexed_insert(args.nth::<u64>(0) as u64);
-                None
+                Ok(None)
             },
         ),
     );
@@ -129,7 +131,7 @@ pub fn register_witness_foreign(env: &mut HostEnv, index_map: Rc<RefCell<HashMap
             |_obs: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<WitnessContext>().unwrap();
                 context.witness_indexed_push(args.nth::<u64>(0) as u64);
-                None
+                Ok(None)
             },
         ),
     );
@@ -142,7 +144,9 @@ pub fn register_witness_foreign(env: &mut HostEnv, index_map: Rc<RefCell<HashMap
         Rc::new(
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<WitnessContext>().unwrap();
-                Some(wasmi::RuntimeValue::I64(context.witness_pop() as i64))
+                Ok(Some(
+                    wasmi::RuntimeValue::I64(context.witness_pop()? as i64),
+                ))
             },
         ),
     );
@@ -155,9 +159,9 @@ pub fn register_witness_foreign(env: &mut HostEnv, index_map: Rc<RefCell<HashMap
         Rc::new(
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                 let context = context.downcast_mut::<WitnessContext>().unwrap();
-                Some(wasmi::RuntimeValue::I64(
-                    context.witness_indexed_pop() as i64
-                ))
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.witness_indexed_pop()? as i64,
+                )))
             },
         ),
     );
@@ -169,7 +173,7 @@ pub fn register_witness_foreign(env: &mut HostEnv, index_map: Rc<RefCell<HashMap
         foreign_witness_plugin.clone(),
         Rc::new(
             |obs: &Observer, _context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
-                Some(wasmi::RuntimeValue::I64(obs.counter as i64))
+                Ok(Some(wasmi::RuntimeValue::I64(obs.counter as i64)))
             },
         ),
     );
@@ -2,28 +2,34 @@ pub mod host;
 use std::cell::RefCell;
 use std::rc::Rc;
 
+use delphinus_zkwasm::circuits::config::CircuitConfig;
 use delphinus_zkwasm::foreign::context::runtime::register_context_foreign;
 use delphinus_zkwasm::foreign::log_helper::register_log_foreign;
 use delphinus_zkwasm::foreign::require_helper::register_require_foreign;
 use delphinus_zkwasm::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+use delphinus_zkwasm::foreign::wasm_input_helper::source::PrivateInputSource;
 use delphinus_zkwasm::runtime::wasmi_interpreter::WasmRuntimeIO;
+use host::merkle_helper::state::MerkleRoots;
 
 use delphinus_zkwasm::runtime::host::host_env::HostEnv;
 use delphinus_zkwasm::runtime::host::ContextOutput;
 use delphinus_zkwasm::runtime::host::HostEnvBuilder;
 use serde::Deserialize;
 use serde::Serialize;
+use specs::manifest::EncodedInputs;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::Mutex;
+use strum_macros::Display;
+use strum_macros::EnumString;
 use zkwasm_host_circuits::host::db::TreeDB;
-use zkwasm_host_circuits::proof::OpType;
 
 pub struct ExecutionArg {
     /// Public inputs for `wasm_input(1)`
     pub public_inputs: Vec<u64>,
     /// Private inputs for `wasm_input(0)`
-    pub private_inputs: Vec<u64>,
+    pub private_inputs: Box<dyn PrivateInputSource>,
     /// Context inputs for `wasm_read_context()`
     pub context_inputs: Vec<u64>,
     /// Context outputs for `wasm_write_context()`
@@ -32,6 +38,22 @@ pub struct ExecutionArg {
     pub indexed_witness: Rc<RefCell<HashMap<u64, Vec<u64>>>>,
     /// db src
     pub tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+    /// Roots of the merkle tree observed during the execution
+    pub merkle_roots: Rc<RefCell<MerkleRoots>>,
+}
+
+impl From<EncodedInputs> for ExecutionArg {
+    fn from(inputs: EncodedInputs) -> Self {
+        ExecutionArg {
+            public_inputs: inputs.public_inputs,
+            private_inputs: inputs.private_inputs.into(),
+            context_inputs: inputs.context_inputs,
+            context_outputs: Arc::new(Mutex::new(vec![])),
+            indexed_witness: Rc::new(RefCell::new(HashMap::new())),
+            tree_db: None,
+            merkle_roots: Rc::new(RefCell::new(MerkleRoots::default())),
+        }
+    }
 }
 
 impl ContextOutput for ExecutionArg {
@@ -40,45 +62,124 @@ impl ContextOutput for ExecutionArg {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Ops of the standard host environment, the ops backed by a host circuit are named after
+/// their `zkwasm_host_circuits::proof::OpType`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize, EnumString, Display)]
+#[strum(ascii_case_insensitive)]
+pub enum HostOp {
+    BLS381PAIR,
+    BLS381SUM,
+    BN256PAIR,
+    BN256SUM,
+    POSEIDONHASH,
+    MERKLE,
+    JUBJUBSUM,
+    KECCAKHASH,
+    /// There is no host circuit of sha256, the calls are not proven and not counted in the
+    /// round statistics.
+    SHA256,
+}
+
+impl HostOp {
+    /// Whether the calls of the op are constrained by a host circuit.
+    pub fn is_proven(&self) -> bool {
+        !matches!(self, HostOp::SHA256)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct HostEnvConfig {
-    pub ops: Vec<OpType>,
+    pub ops: Vec<HostOp>,
+}
+
+/// The order of ops doesn't affect the image.
+impl PartialEq for HostEnvConfig {
+    fn eq(&self, other: &Self) -> bool {
+        self.ops.iter().collect::<HashSet<_>>() == other.ops.iter().collect::<HashSet<_>>()
+    }
 }
 
 impl Default for HostEnvConfig {
     fn default() -> Self {
         HostEnvConfig {
             ops: vec![
-                OpType::POSEIDONHASH,
-                OpType::MERKLE,
-                OpType::JUBJUBSUM,
-                OpType::KECCAKHASH,
-                OpType::BN256SUM,
+                HostOp::POSEIDONHASH,
+                HostOp::MERKLE,
+                HostOp::JUBJUBSUM,
+                HostOp::KECCAKHASH,
+                HostOp::BN256SUM,
             ],
         }
     }
 }
 
 impl HostEnvConfig {
-    fn register_op(op: &OpType, env: &mut HostEnv) {
+    /// Ops whose results are not constrained by the proof.
+    pub fn unproven_ops(&self) -> Vec<HostOp> {
+        self.ops
+            .iter()
+            .filter(|op| !op.is_proven())
+            .copied()
+            .collect()
+    }
+
+    fn register_op(
+        op: &HostOp,
+        env: &mut HostEnv,
+        circuit_config: &CircuitConfig,
+        tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+        merkle_roots: Rc<RefCell<MerkleRoots>>,
+    ) {
         match op {
-            OpType::BLS381PAIR => host::ecc_helper::bls381::pair::register_blspair_foreign(env),
-            OpType::BLS381SUM => host::ecc_helper::bls381::sum::register_blssum_foreign(env),
-            OpType::BN256PAIR => host::ecc_helper::bn254::pair::register_bn254pair_foreign(env),
-            OpType::BN256SUM => host::ecc_helper::bn254::sum::register_bn254sum_foreign(env),
-            OpType::POSEIDONHASH => host::hash_helper::poseidon::register_poseidon_foreign(env),
-            OpType::MERKLE => {
-                host::merkle_helper::merkle::register_merkle_foreign(env, None);
-                host::merkle_helper::datacache::register_datacache_foreign(env, None);
+            HostOp::BLS381PAIR => {
+                host::ecc_helper::bls381::pair::register_blspair_foreign(env, circuit_config)
+            }
+            HostOp::BLS381SUM => {
+                host::ecc_helper::bls381::sum::register_blssum_foreign(env, circuit_config)
+            }
+            HostOp::BN256PAIR => {
+                host::ecc_helper::bn254::pair::register_bn254pair_foreign(env, circuit_config)
+            }
+            HostOp::BN256SUM => {
+                host::ecc_helper::bn254::sum::register_bn254sum_foreign(env, circuit_config)
+            }
+            HostOp::POSEIDONHASH => {
+                host::hash_helper::poseidon::register_poseidon_foreign(env, circuit_config)
             }
-            OpType::JUBJUBSUM => host::ecc_helper::jubjub::sum::register_babyjubjubsum_foreign(env),
-            OpType::KECCAKHASH => host::hash_helper::keccak256::register_keccak_foreign(env),
+            HostOp::MERKLE => {
+                host::merkle_helper::merkle::register_merkle_foreign(
+                    env,
+                    circuit_config,
+                    tree_db.clone(),
+                    merkle_roots,
+                );
+                host::merkle_helper::datacache::register_datacache_foreign(env, tree_db);
+            }
+            HostOp::JUBJUBSUM => {
+                host::ecc_helper::jubjub::sum::register_babyjubjubsum_foreign(env, circuit_config)
+            }
+            HostOp::KECCAKHASH => {
+                host::hash_helper::keccak256::register_keccak_foreign(env, circuit_config)
+            }
+            HostOp::SHA256 => host::hash_helper::sha256::register_sha256_foreign(env),
         }
     }
 
-    fn register_ops(&self, env: &mut HostEnv) {
+    fn register_ops(
+        &self,
+        env: &mut HostEnv,
+        circuit_config: &CircuitConfig,
+        tree_db: Option<Rc<RefCell<dyn TreeDB>>>,
+        merkle_roots: Rc<RefCell<MerkleRoots>>,
+    ) {
         for op in &self.ops {
-            Self::register_op(op, env);
+            Self::register_op(
+                op,
+                env,
+                circuit_config,
+                tree_db.clone(),
+                merkle_roots.clone(),
+            );
         }
     }
 }
@@ -89,13 +190,21 @@ impl HostEnvBuilder for StandardHostEnvBuilder {
     type Arg = ExecutionArg;
     type HostConfig = HostEnvConfig;
 
-    fn create_env_without_value(envconfig: Self::HostConfig) -> (HostEnv, WasmRuntimeIO) {
+    fn create_env_without_value(
+        circuit_config: &CircuitConfig,
+        envconfig: Self::HostConfig,
+    ) -> (HostEnv, WasmRuntimeIO) {
         let mut env = HostEnv::new();
         let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], vec![]);
         register_require_foreign(&mut env);
         register_log_foreign(&mut env);
         register_context_foreign(&mut env, vec![], Arc::new(Mutex::new(vec![])));
-        envconfig.register_ops(&mut env);
+        envconfig.register_ops(
+            &mut env,
+            circuit_config,
+            None,
+            Rc::new(RefCell::new(MerkleRoots::default())),
+        );
         host::witness_helper::register_witness_foreign(
             &mut env,
             Rc::new(RefCell::new(HashMap::new())),
@@ -105,7 +214,11 @@ impl HostEnvBuilder for StandardHostEnvBuilder {
         (env, wasm_runtime_io)
     }
 
-    fn create_env(arg: Self::Arg, envconfig: Self::HostConfig) -> (HostEnv, WasmRuntimeIO) {
+    fn create_env(
+        circuit_config: &CircuitConfig,
+        arg: Self::Arg,
+        envconfig: Self::HostConfig,
+    ) -> (HostEnv, WasmRuntimeIO) {
         let mut env = HostEnv::new();
         let wasm_runtime_io =
             register_wasm_input_foreign(&mut env, arg.public_inputs, arg.private_inputs);
@@ -113,7 +226,7 @@ impl HostEnvBuilder for StandardHostEnvBuilder {
         register_log_foreign(&mut env);
         register_context_foreign(&mut env, arg.context_inputs, arg.context_outputs);
         host::witness_helper::register_witness_foreign(&mut env, arg.indexed_witness);
-        envconfig.register_ops(&mut env);
+        envconfig.register_ops(&mut env, circuit_config, arg.tree_db, arg.merkle_roots);
         env.finalize();
 
         (env, wasm_runtime_io)
@@ -10,6 +10,7 @@
  * Since the phantom function will not produce any traces, memory/global
  * writing is invisible to prover,
  * *** the function MUST NOT have these operations ***.
+ * The loader rejects phantom functions with these operations or host calls.
  */
 __attribute__((noinline)) int search(int *arr, int size, int v)
 {
@@ -16,7 +16,7 @@ fn main() -> Result<()> {
     let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])?;
     let arg = ExecutionArg {
         public_inputs: vec![],
-        private_inputs: vec![],
+        private_inputs: vec![].into(),
         context_inputs: context_in,
         context_outputs: context_outputs.clone(),
     };
@@ -27,7 +27,7 @@ fn main() -> Result<()> {
 
     let arg = ExecutionArg {
         public_inputs: vec![],
-        private_inputs: vec![],
+        private_inputs: vec![].into(),
         context_inputs: context_outputs.lock().unwrap().to_vec(),
         context_outputs: Arc::new(Mutex::new(vec![])),
     };
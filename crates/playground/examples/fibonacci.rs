@@ -13,7 +13,7 @@ fn main() -> Result<()> {
     let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])?;
     let result = loader.run(ExecutionArg {
         public_inputs: vec![5],
-        private_inputs: vec![],
+        private_inputs: vec![].into(),
         context_inputs: vec![],
         context_outputs: Arc::new(Mutex::new(vec![])),
     }, (), false, true)?;
@@ -18,7 +18,7 @@ fn main() -> Result<()> {
 
     let result = loader.run(ExecutionArg {
         public_inputs: vec![2],
-        private_inputs: vec![],
+        private_inputs: vec![].into(),
         context_inputs: vec![],
         context_outputs: Arc::new(Mutex::new(vec![])),
     }, (), false, true)?;
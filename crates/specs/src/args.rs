@@ -1,63 +1,87 @@
-pub fn parse_args(values: Vec<&str>) -> Vec<u64> {
-    values
-        .into_iter()
-        .map(|v| {
-            let [v, t] = v.split(":").collect::<Vec<&str>>()[..] else { todo!() };
-            match t {
-                "i64" => {
-                    if v.starts_with("0x") {
-                        vec![
-                            u64::from_str_radix(String::from(v).trim_start_matches("0x"), 16)
-                                .unwrap(),
-                        ]
-                    } else {
-                        vec![v.parse::<u64>().unwrap()]
-                    }
-                }
-                "bytes" => {
-                    if !v.starts_with("0x") {
-                        panic!("bytes input need start with 0x");
-                    }
-                    let bytes = hex::decode(String::from(v).trim_start_matches("0x")).unwrap();
-                    bytes
-                        .into_iter()
-                        .map(|x| u64::from(x))
-                        .collect::<Vec<u64>>()
-                }
-                "bytes-packed" => {
-                    if !v.starts_with("0x") {
-                        panic!("bytes input need start with 0x");
-                    }
-                    let bytes = hex::decode(String::from(v).trim_start_matches("0x")).unwrap();
-                    let bytes = bytes.chunks(8);
-                    bytes
-                        .into_iter()
-                        .map(|x| {
-                            let mut data = [0u8; 8];
-                            data[..x.len()].copy_from_slice(x);
-
-                            u64::from_le_bytes(data)
-                        })
-                        .collect::<Vec<u64>>()
+use crate::manifest::InputError;
+
+/// Parses the `value:type` arguments, where type is i64, bytes, bytes-packed or file.
+pub fn parse_args(values: Vec<&str>) -> Result<Vec<u64>, InputError> {
+    let mut inputs = vec![];
+
+    for arg in values {
+        let invalid = |reason: String| InputError::InvalidArgument {
+            arg: arg.to_string(),
+            reason,
+        };
+
+        let [v, t] = arg.split(":").collect::<Vec<&str>>()[..] else {
+            return Err(invalid("the argument is not of format value:type".to_string()));
+        };
+
+        match t {
+            "i64" => {
+                let v = if v.starts_with("0x") {
+                    u64::from_str_radix(String::from(v).trim_start_matches("0x"), 16)
+                } else {
+                    v.parse::<u64>()
                 }
-                "file" => {
-                    let bytes = std::fs::read(v).unwrap();
-                    let bytes = bytes.chunks(8);
-                    bytes
-                        .into_iter()
-                        .map(|x| {
-                            let mut data = [0u8; 8];
-                            data[..x.len()].copy_from_slice(x);
-
-                            u64::from_be_bytes(data)
-                        })
-                        .collect()
+                .map_err(|e| invalid(e.to_string()))?;
+
+                inputs.push(v);
+            }
+            "bytes" => {
+                if !v.starts_with("0x") {
+                    return Err(invalid("bytes input need start with 0x".to_string()));
                 }
-                _ => {
-                    panic!("Unsupported input data type: {}", t)
+                let bytes = hex::decode(String::from(v).trim_start_matches("0x"))
+                    .map_err(|e| invalid(e.to_string()))?;
+
+                inputs.extend(bytes.into_iter().map(|x| u64::from(x)));
+            }
+            "bytes-packed" => {
+                if !v.starts_with("0x") {
+                    return Err(invalid("bytes input need start with 0x".to_string()));
                 }
+                let bytes = hex::decode(String::from(v).trim_start_matches("0x"))
+                    .map_err(|e| invalid(e.to_string()))?;
+
+                inputs.extend(bytes.chunks(8).map(|x| {
+                    let mut data = [0u8; 8];
+                    data[..x.len()].copy_from_slice(x);
+
+                    u64::from_le_bytes(data)
+                }));
+            }
+            "file" => {
+                let bytes = std::fs::read(v).map_err(|e| invalid(e.to_string()))?;
+
+                inputs.extend(bytes.chunks(8).map(|x| {
+                    let mut data = [0u8; 8];
+                    data[..x.len()].copy_from_slice(x);
+
+                    u64::from_be_bytes(data)
+                }));
+            }
+            _ => return Err(invalid(format!("Unsupported input data type: {}", t))),
+        }
+    }
+
+    Ok(inputs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_args;
+    use crate::manifest::InputError;
+
+    #[test]
+    fn test_invalid_args() {
+        assert_eq!(
+            parse_args(vec!["0x10:i64", "0x0102:bytes-packed"]).unwrap(),
+            vec![0x10, 0x0201]
+        );
+
+        for arg in ["1", "1:i64:i64", "x:i64", "0102:bytes", "1:u128"] {
+            match parse_args(vec![arg]) {
+                Err(InputError::InvalidArgument { arg: invalid, .. }) => assert_eq!(invalid, arg),
+                r => panic!("unexpected result {:?} of {}", r, arg),
             }
-        })
-        .flatten()
-        .collect()
+        }
+    }
 }
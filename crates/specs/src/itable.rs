@@ -76,7 +76,7 @@ impl OpcodeClass {
             OpcodeClass::BrIf => 1,
             OpcodeClass::BrIfEqz => 1,
             OpcodeClass::BrTable => 1,
-            OpcodeClass::Unreachable => todo!(),
+            OpcodeClass::Unreachable => 0,
             OpcodeClass::Call => 0,
             OpcodeClass::CallHost => 1, // Push or pop
             OpcodeClass::CallIndirect => 1,
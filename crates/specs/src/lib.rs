@@ -30,8 +30,10 @@ pub mod host_function;
 pub mod imtable;
 pub mod itable;
 pub mod jtable;
+pub mod manifest;
 pub mod mtable;
 pub mod step;
+pub mod trap;
 pub mod types;
 
 #[derive(Default, Serialize, Deserialize, Debug, Clone)]
@@ -0,0 +1,307 @@
+use std::fmt::Display;
+use std::path::Path;
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Inputs of an execution declared with explicit types, e.g.
+///
+/// ```json
+/// {
+///   "public": [{ "type": "u64", "value": 1 }],
+///   "private": [
+///     { "type": "u8", "value": [[1, 2], [3]] },
+///     { "type": "hex", "value": "0x0102", "endian": "big" }
+///   ],
+///   "context": [{ "type": "base64", "value": "AQI=" }]
+/// }
+/// ```
+///
+/// Each integer of `u8`, `u32`, `u64` and `i64` is an input of `wasm_input`, the value may be
+/// a nested array of integers. `hex` and `base64` are byte blobs packed into 8-byte inputs.
+///
+/// The same manifest can be written in TOML, e.g.
+///
+/// ```toml
+/// public = [{ type = "u64", value = "0xff" }]
+///
+/// [[private]]
+/// type = "hex"
+/// value = "0x0102"
+/// endian = "big"
+/// ```
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct InputManifest {
+    #[serde(default)]
+    pub public: Vec<Input>,
+    #[serde(default)]
+    pub private: Vec<Input>,
+    #[serde(default)]
+    pub context: Vec<Input>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Input {
+    #[serde(rename = "type")]
+    pub ty: InputType,
+    pub value: Value,
+    /// Byte order of a byte blob within each 8-byte input
+    #[serde(default)]
+    pub endian: Endian,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputType {
+    U8,
+    U32,
+    U64,
+    I64,
+    Hex,
+    Base64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Endian {
+    #[default]
+    Little,
+    Big,
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    /// The manifest can't be read or doesn't match the schema.
+    Manifest(String),
+    /// The value at `path`, e.g. `private[1][0]`, doesn't match its type.
+    InvalidValue { path: String, reason: String },
+    /// The `value:type` argument can't be parsed.
+    InvalidArgument { arg: String, reason: String },
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputError::Manifest(reason) => write!(f, "invalid input manifest: {}", reason),
+            InputError::InvalidValue { path, reason } => {
+                write!(f, "invalid input at {}: {}", path, reason)
+            }
+            InputError::InvalidArgument { arg, reason } => {
+                write!(f, "invalid input argument {:?}: {}", arg, reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+/// Inputs encoded into the values consumed by `wasm_input` and `wasm_read_context`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EncodedInputs {
+    pub public_inputs: Vec<u64>,
+    pub private_inputs: Vec<u64>,
+    pub context_inputs: Vec<u64>,
+}
+
+impl InputManifest {
+    pub fn from_json(json: &str) -> Result<Self, InputError> {
+        serde_json::from_str(json).map_err(|e| InputError::Manifest(e.to_string()))
+    }
+
+    pub fn from_toml(toml: &str) -> Result<Self, InputError> {
+        toml::from_str(toml).map_err(|e| InputError::Manifest(e.to_string()))
+    }
+
+    /// Loads a TOML manifest if the extension of `path` is `toml`, otherwise a JSON manifest.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, InputError> {
+        let content = std::fs::read_to_string(&path).map_err(|e| {
+            InputError::Manifest(format!("failed to read {:?}: {}", path.as_ref(), e))
+        })?;
+
+        match path.as_ref().extension() {
+            Some(extension) if extension == "toml" => Self::from_toml(&content),
+            _ => Self::from_json(&content),
+        }
+    }
+
+    /// Validates and encodes all inputs, the first invalid value is reported.
+    pub fn encode(&self) -> Result<EncodedInputs, InputError> {
+        let encode_all = |name: &str, inputs: &Vec<Input>| -> Result<Vec<u64>, InputError> {
+            let mut values = vec![];
+            for (index, input) in inputs.iter().enumerate() {
+                input.encode(&format!("{}[{}]", name, index), &input.value, &mut values)?;
+            }
+            Ok(values)
+        };
+
+        Ok(EncodedInputs {
+            public_inputs: encode_all("public", &self.public)?,
+            private_inputs: encode_all("private", &self.private)?,
+            context_inputs: encode_all("context", &self.context)?,
+        })
+    }
+}
+
+impl Input {
+    fn encode(&self, path: &str, value: &Value, values: &mut Vec<u64>) -> Result<(), InputError> {
+        let invalid = |reason: String| InputError::InvalidValue {
+            path: path.to_string(),
+            reason,
+        };
+
+        if let Value::Array(elements) = value {
+            for (index, element) in elements.iter().enumerate() {
+                self.encode(&format!("{}[{}]", path, index), element, values)?;
+            }
+
+            return Ok(());
+        }
+
+        match self.ty {
+            InputType::U8 | InputType::U32 | InputType::U64 => {
+                let max = match self.ty {
+                    InputType::U8 => u8::MAX as u64,
+                    InputType::U32 => u32::MAX as u64,
+                    _ => u64::MAX,
+                };
+
+                let v = parse_u64(value).map_err(invalid)?;
+                if v > max {
+                    return Err(invalid(format!("{} exceeds the maximal value {}", v, max)));
+                }
+                values.push(v);
+            }
+            InputType::I64 => {
+                let v = value
+                    .as_i64()
+                    .ok_or_else(|| invalid(format!("{} is not an i64", value)))?;
+                values.push(v as u64);
+            }
+            InputType::Hex | InputType::Base64 => {
+                let s = value
+                    .as_str()
+                    .ok_or_else(|| invalid(format!("{} is not a string", value)))?;
+
+                let bytes = if self.ty == InputType::Hex {
+                    hex::decode(s.trim_start_matches("0x")).map_err(|e| invalid(e.to_string()))?
+                } else {
+                    BASE64.decode(s).map_err(|e| invalid(e.to_string()))?
+                };
+
+                values.extend(bytes.chunks(8).map(|chunk| {
+                    let mut data = [0u8; 8];
+                    data[..chunk.len()].copy_from_slice(chunk);
+
+                    match self.endian {
+                        Endian::Little => u64::from_le_bytes(data),
+                        Endian::Big => u64::from_be_bytes(data),
+                    }
+                }));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Integers may be JSON numbers or strings in decimal or `0x` prefixed hex, since JSON parsers
+/// may lose the precision of large numbers.
+fn parse_u64(value: &Value) -> Result<u64, String> {
+    match value {
+        Value::Number(n) => n
+            .as_u64()
+            .ok_or_else(|| format!("{} is not an unsigned integer", n)),
+        Value::String(s) => match s.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16),
+            None => s.parse::<u64>(),
+        }
+        .map_err(|e| format!("{:?} is not an unsigned integer: {}", s, e)),
+        _ => Err(format!("{} is not an unsigned integer", value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EncodedInputs;
+    use super::InputError;
+    use super::InputManifest;
+
+    #[test]
+    fn test_encode_manifest() {
+        let manifest = InputManifest::from_json(
+            r#"{
+                "public": [{ "type": "u64", "value": "0xff" }, { "type": "i64", "value": -1 }],
+                "private": [
+                    { "type": "u8", "value": [[1, 2], [3]] },
+                    { "type": "hex", "value": "0x0102", "endian": "big" }
+                ],
+                "context": [{ "type": "base64", "value": "AQI=" }]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.encode().unwrap(),
+            EncodedInputs {
+                public_inputs: vec![0xff, u64::MAX],
+                private_inputs: vec![1, 2, 3, 0x0102 << 48],
+                context_inputs: vec![0x0201],
+            }
+        );
+    }
+
+    #[test]
+    fn test_encode_toml_manifest() {
+        let manifest = InputManifest::from_toml(
+            r#"
+            public = [{ type = "u64", value = "0xff" }, { type = "i64", value = -1 }]
+            context = [{ type = "base64", value = "AQI=" }]
+
+            [[private]]
+            type = "u8"
+            value = [[1, 2], [3]]
+
+            [[private]]
+            type = "hex"
+            value = "0x0102"
+            endian = "big"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.encode().unwrap(),
+            EncodedInputs {
+                public_inputs: vec![0xff, u64::MAX],
+                private_inputs: vec![1, 2, 3, 0x0102 << 48],
+                context_inputs: vec![0x0201],
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_manifest() {
+        let manifest =
+            InputManifest::from_json(r#"{ "private": [{ "type": "u8", "value": [1, 256] }] }"#)
+                .unwrap();
+
+        match manifest.encode() {
+            Err(InputError::InvalidValue { path, .. }) => assert_eq!(path, "private[0][1]"),
+            r => panic!("unexpected result {:?}", r),
+        }
+
+        assert!(matches!(
+            InputManifest::from_json(r#"{ "public": [{ "type": "u128", "value": 1 }] }"#),
+            Err(InputError::Manifest(_))
+        ));
+        assert!(matches!(
+            InputManifest::from_toml(r#"public = [{ type = "u64" }]"#),
+            Err(InputError::Manifest(_))
+        ));
+    }
+}
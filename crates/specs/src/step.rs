@@ -49,6 +49,7 @@ pub enum StepInfo {
     },
 
     Drop,
+    Unreachable,
     Select {
         val1: u64,
         val2: u64,
@@ -0,0 +1,109 @@
+use num_bigint::BigUint;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::configure_table::WASM_BYTES_PER_PAGE;
+use crate::etable::EventTableEntry;
+use crate::host_function::HostPlugin;
+use crate::itable::BinOp;
+use crate::step::StepInfo;
+
+pub const TRAP_CODE_SHIFT: usize = 56;
+pub const TRAP_FID_SHIFT: usize = 32;
+/// Guest outputs are u64 values, the instance of a trap outcome sets the bit above them.
+pub const TRAP_OUTCOME_FLAG_SHIFT: usize = 64;
+
+/// Traps which terminate the execution with a provable outcome.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrapCode {
+    Unreachable = 1,
+    RequireFailed = 2,
+    MemoryAccessOutOfBounds = 3,
+    DivisionByZero = 4,
+    IntegerOverflow = 5,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrapOutcome {
+    pub code: TrapCode,
+    pub fid: u32,
+    pub iid: u32,
+}
+
+impl TrapOutcome {
+    /// `fid` and `iid` are bounded by the common range so that the fields never overlap.
+    pub fn encode(&self) -> u64 {
+        ((self.code as u64) << TRAP_CODE_SHIFT)
+            + ((self.fid as u64) << TRAP_FID_SHIFT)
+            + self.iid as u64
+    }
+
+    /// The outcome is published as the last instance.
+    pub fn encode_instance(&self) -> BigUint {
+        (BigUint::from(1u64) << TRAP_OUTCOME_FLAG_SHIFT) + self.encode()
+    }
+}
+
+impl EventTableEntry {
+    /// The trap raised by the step, a trap step terminates the event table.
+    pub fn trap_code(&self) -> Option<TrapCode> {
+        let is_out_of_bounds = |effective_address: u32, size: u64| {
+            effective_address as u64 + size
+                > self.allocated_memory_pages as u64 * WASM_BYTES_PER_PAGE
+        };
+
+        match &self.step_info {
+            StepInfo::Unreachable => Some(TrapCode::Unreachable),
+            StepInfo::CallHost {
+                plugin: HostPlugin::Require,
+                args,
+                ..
+            } if args[0] == 0 => Some(TrapCode::RequireFailed),
+            StepInfo::Load {
+                load_size,
+                effective_address,
+                ..
+            } if is_out_of_bounds(*effective_address, load_size.byte_size() as u64) => {
+                Some(TrapCode::MemoryAccessOutOfBounds)
+            }
+            StepInfo::Store {
+                store_size,
+                effective_address,
+                ..
+            } if is_out_of_bounds(*effective_address, store_size.byte_size()) => {
+                Some(TrapCode::MemoryAccessOutOfBounds)
+            }
+            StepInfo::I32BinOp {
+                class: BinOp::UnsignedDiv | BinOp::UnsignedRem | BinOp::SignedDiv | BinOp::SignedRem,
+                right: 0,
+                ..
+            }
+            | StepInfo::I64BinOp {
+                class: BinOp::UnsignedDiv | BinOp::UnsignedRem | BinOp::SignedDiv | BinOp::SignedRem,
+                right: 0,
+                ..
+            } => Some(TrapCode::DivisionByZero),
+            StepInfo::I32BinOp {
+                class: BinOp::SignedDiv,
+                left: i32::MIN,
+                right: -1,
+                ..
+            }
+            | StepInfo::I64BinOp {
+                class: BinOp::SignedDiv,
+                left: i64::MIN,
+                right: -1,
+                ..
+            } => Some(TrapCode::IntegerOverflow),
+            _ => None,
+        }
+    }
+
+    pub fn trap_outcome(&self) -> Option<TrapOutcome> {
+        self.trap_code().map(|code| TrapOutcome {
+            code,
+            fid: self.fid,
+            iid: self.iid,
+        })
+    }
+}
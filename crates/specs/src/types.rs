@@ -11,13 +11,21 @@ pub enum ValueType {
     I64,
 }
 
-impl From<parity_wasm::elements::ValueType> for ValueType {
-    fn from(v: parity_wasm::elements::ValueType) -> Self {
+/// A wasm value type without a circuit representation. Float types are lowered into integer ones
+/// by the loader, the precheck rejects any of them left.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnsupportedValueType(pub parity_wasm::elements::ValueType);
+
+impl TryFrom<parity_wasm::elements::ValueType> for ValueType {
+    type Error = UnsupportedValueType;
+
+    fn try_from(v: parity_wasm::elements::ValueType) -> Result<Self, Self::Error> {
         match v {
-            parity_wasm::elements::ValueType::I32 => ValueType::I32,
-            parity_wasm::elements::ValueType::I64 => ValueType::I64,
-            parity_wasm::elements::ValueType::F32 => todo!(),
-            parity_wasm::elements::ValueType::F64 => todo!(),
+            parity_wasm::elements::ValueType::I32 => Ok(ValueType::I32),
+            parity_wasm::elements::ValueType::I64 => Ok(ValueType::I64),
+            parity_wasm::elements::ValueType::F32 | parity_wasm::elements::ValueType::F64 => {
+                Err(UnsupportedValueType(v))
+            }
         }
     }
 }
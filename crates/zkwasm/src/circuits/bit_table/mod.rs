@@ -50,7 +50,7 @@ pub struct BitTableConfig<F: FieldExt> {
     _mark: PhantomData<F>,
 }
 
-pub(in crate::circuits) const STEP_SIZE: usize = 11;
+pub(crate) const STEP_SIZE: usize = 11;
 pub(self) const BLOCK_SEL_OFFSET: usize = 1;
 pub(self) const U32_OFFSET: [usize; 2] = [1, 6];
 pub(self) const U8_OFFSET: [usize; 8] = [2, 3, 4, 5, 7, 8, 9, 10];
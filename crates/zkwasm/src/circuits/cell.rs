@@ -9,7 +9,6 @@ use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::VirtualCells;
 use num_bigint::BigUint;
 
-use crate::circuits::config::zkwasm_k;
 use crate::circuits::utils::bn_to_field;
 use crate::circuits::utils::Context;
 use crate::nextn;
@@ -96,7 +95,7 @@ pub(crate) struct AllocatedU64CellWithFlagBitDynSign<F: FieldExt> {
 }
 
 macro_rules! define_cell {
-    ($x: ident, $limit: expr) => {
+    ($x: ident, |$ctx: ident| $limit: expr) => {
         #[derive(Debug, Clone, Copy)]
         pub(crate) struct $x<F: FieldExt>(pub(crate) AllocatedCell<F>);
 
@@ -110,24 +109,32 @@ macro_rules! define_cell {
                 ctx: &mut Context<'_, F>,
                 value: F,
             ) -> Result<AssignedCell<F, F>, Error> {
+                let limit: F = {
+                    let $ctx = &*ctx;
+                    $limit
+                };
+
                 assert!(
-                    value <= $limit,
+                    value <= limit,
                     "assigned value {:?} exceeds the limit {:?}",
                     value,
-                    $limit
+                    limit
                 );
 
                 self.0.assign(ctx, value)
             }
         }
     };
+    ($x: ident, $limit: expr) => {
+        define_cell!($x, |_ctx| $limit);
+    };
 }
 
 define_cell!(AllocatedBitCell, F::one());
-define_cell!(
-    AllocatedCommonRangeCell,
-    F::from((1u64 << (zkwasm_k() - 1)) - 1)
-);
+// The common range table of a circuit of size K contains the values less than 2^(K-1).
+define_cell!(AllocatedCommonRangeCell, |ctx| F::from(
+    (1u64 << (ctx.k - 1)) - 1
+));
 define_cell!(AllocatedU8Cell, F::from(u8::MAX as u64));
 define_cell!(AllocatedU16Cell, F::from(u16::MAX as u64));
 define_cell!(AllocatedUnlimitedCell, -F::one());
@@ -1,30 +1,85 @@
-use std::env;
-use std::sync::Mutex;
+use anyhow::anyhow;
+use anyhow::Result;
+use specs::configure_table::WASM_BYTES_PER_PAGE;
+
+use crate::loader::err::ConfigErr;
+use crate::loader::err::Error;
 
 pub const POW_TABLE_POWER_START: u64 = 128;
 
 pub const MIN_K: u32 = 18;
-const MAX_K: u32 = 25;
+pub const MAX_K: u32 = 25;
 
-lazy_static! {
-    static ref ZKWASM_K: Mutex<u32> =
-        Mutex::new(env::var("ZKWASM_K").map_or(MIN_K, |k| k.parse().unwrap()));
+/// The size of the circuit and the limits derived from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitConfig {
+    k: u32,
+    provable_trap: bool,
 }
 
-pub fn set_zkwasm_k(k: u32) {
-    assert!(k >= MIN_K);
-    assert!(k <= MAX_K);
+impl CircuitConfig {
+    /// Fails if `k` is out of `MIN_K..=MAX_K`.
+    pub fn new(k: u32) -> Result<Self> {
+        if !(MIN_K..=MAX_K).contains(&k) {
+            return Err(anyhow!(Error::Config(ConfigErr::KOutOfRange {
+                k,
+                min_k: MIN_K,
+                max_k: MAX_K,
+            })));
+        }
 
-    let mut zkwasm_k = (*ZKWASM_K).lock().unwrap();
-    *zkwasm_k = k;
-}
+        Ok(CircuitConfig {
+            k,
+            provable_trap: false,
+        })
+    }
+
+    /// Accept executions terminated by a trap, the flag is fixed in the verifying key.
+    pub fn with_provable_trap(self, provable_trap: bool) -> Self {
+        CircuitConfig {
+            provable_trap,
+            ..self
+        }
+    }
+
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+
+    pub fn provable_trap(&self) -> bool {
+        self.provable_trap
+    }
+
+    /// Values in the common range table are in `[0, common_range_rows)`.
+    pub fn common_range_rows(&self) -> usize {
+        1 << (self.k - 1)
+    }
+
+    /// Heap pages addressable by the circuit. The offset of an 8-byte memory block is bounded by
+    /// the common range, which is also the end offset of the init memory entries, see
+    /// `InitMemoryTable::new`.
+    pub fn maximal_memory_pages(&self) -> u32 {
+        (self.common_range_rows() as u64 * 8 / WASM_BYTES_PER_PAGE) as u32
+    }
+
+    /// The eid which never occurs in the execution, used as the end eid of a memory
+    /// writing entry which is never overwritten.
+    pub fn maximal_eid(&self) -> u32 {
+        (1u32 << (self.k - 1)) - 1
+    }
 
-pub fn zkwasm_k() -> u32 {
-    *ZKWASM_K.lock().unwrap()
+    pub fn foreign_table_enable_lines(&self) -> usize {
+        1 << (self.k - 1)
+    }
 }
 
-pub fn init_zkwasm_runtime(k: u32) {
-    set_zkwasm_k(k);
+impl Default for CircuitConfig {
+    fn default() -> Self {
+        CircuitConfig {
+            k: MIN_K,
+            provable_trap: false,
+        }
+    }
 }
 
 pub(crate) fn max_image_table_rows() -> u32 {
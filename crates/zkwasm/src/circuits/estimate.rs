@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use halo2_proofs::arithmetic::FieldExt;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::ConstraintSystem;
+use serde::Serialize;
+use specs::itable::UnaryOp;
+use specs::mtable::LocationType;
+use specs::step::StepInfo;
+use specs::Tables;
+
+use crate::circuits::bit_table::STEP_SIZE;
+use crate::circuits::config::max_image_table_rows;
+use crate::circuits::config::CircuitConfig;
+use crate::circuits::config::MAX_K;
+use crate::circuits::config::MIN_K;
+use crate::circuits::etable::EVENT_TABLE_ENTRY_ROWS;
+use crate::circuits::jtable::JtableOffset;
+use crate::circuits::mtable::MEMORY_TABLE_ENTRY_ROWS;
+use crate::circuits::test_circuit::max_available_rows;
+use crate::circuits::utils::table_entry::MemoryWritingTable;
+use crate::circuits::ZkWasmCircuit;
+use crate::foreign::context::circuits::assign::ExtractContextFromTrace;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum TableKind {
+    EventTable,
+    MemoryTable,
+    JumpTable,
+    BitTable,
+    ExternalHostCallTable,
+    ImageTable,
+    WasmInputHelperTable,
+    ContextHelperTable,
+}
+
+impl Display for TableKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            TableKind::EventTable => "etable",
+            TableKind::MemoryTable => "mtable",
+            TableKind::JumpTable => "jtable",
+            TableKind::BitTable => "bit table",
+            TableKind::ExternalHostCallTable => "external host call table",
+            TableKind::ImageTable => "image table",
+            TableKind::WasmInputHelperTable => "wasm input helper table",
+            TableKind::ContextHelperTable => "context helper table",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TableRows {
+    pub table: TableKind,
+    /// Rows occupied by the table, including the padding rows required by the chip.
+    pub required_rows: usize,
+}
+
+/// Rounds of an external host circuit used by an execution.
+#[derive(Clone, Debug, Serialize)]
+pub struct HostRounds {
+    pub plugin: String,
+    pub used_rounds: usize,
+    /// The maximal rounds of the host circuit for each K in `MIN_K..=MAX_K`.
+    pub max_rounds: BTreeMap<u32, usize>,
+}
+
+impl HostRounds {
+    /// Rounds available to the plugin in a circuit of size `k`.
+    pub fn available_rounds(&self, k: u32) -> usize {
+        self.max_rounds.get(&k).copied().unwrap_or(0)
+    }
+}
+
+/// Rows required by each table and rounds used by each host circuit of an execution, used
+/// to find the minimal K of the circuit before proving.
+#[derive(Clone, Debug, Serialize)]
+pub struct RowEstimate {
+    pub tables: Vec<TableRows>,
+    pub host_rounds: Vec<HostRounds>,
+    blinding_factors: usize,
+}
+
+impl RowEstimate {
+    pub fn new<F: FieldExt>(
+        circuit_config: &CircuitConfig,
+        tables: &Tables,
+        public_inputs_and_outputs: usize,
+        host_rounds: Vec<HostRounds>,
+    ) -> Self {
+        let etable = &tables.execution_tables.etable;
+
+        let bit_table_entries = etable
+            .entries()
+            .iter()
+            .filter(|entry| {
+                matches!(
+                    entry.step_info,
+                    StepInfo::I32BinBitOp { .. }
+                        | StepInfo::I64BinBitOp { .. }
+                        | StepInfo::UnaryOp {
+                            class: UnaryOp::Popcnt,
+                            ..
+                        }
+                )
+            })
+            .count();
+
+        let memory_writing_table = MemoryWritingTable::new(
+            tables.execution_tables.mtable.clone(),
+            circuit_config.maximal_eid(),
+        );
+
+        let image_table_entries = {
+            let compilation_tables = &tables.compilation_tables;
+
+            // Each of instruction, br table and init memory sections starts with a zero entry.
+            3 + compilation_tables.itable.iter().count()
+                + compilation_tables.itable.create_brtable().entries().len()
+                + compilation_tables.elem_table.entries().len()
+                + compilation_tables.imtable.filter(LocationType::Heap).len()
+                + compilation_tables
+                    .imtable
+                    .filter(LocationType::Global)
+                    .len()
+        };
+
+        let context_entries = usize::max(
+            etable.get_context_inputs().len(),
+            etable.get_context_outputs().len(),
+        );
+
+        let jtable_offset_max = JtableOffset::JtableOffsetMax as usize;
+
+        let tables = vec![
+            TableRows {
+                table: TableKind::EventTable,
+                required_rows: etable.entries().len() * EVENT_TABLE_ENTRY_ROWS as usize,
+            },
+            TableRows {
+                table: TableKind::MemoryTable,
+                // The memory table must keep at least one row free for the terminating entry.
+                required_rows: memory_writing_table.0.len() * MEMORY_TABLE_ENTRY_ROWS as usize + 1,
+            },
+            TableRows {
+                table: TableKind::JumpTable,
+                // Two static entries and the terminating entry.
+                required_rows: (tables.execution_tables.jtable.entries().len() + 3)
+                    * jtable_offset_max,
+            },
+            TableRows {
+                table: TableKind::BitTable,
+                required_rows: bit_table_entries * STEP_SIZE,
+            },
+            TableRows {
+                table: TableKind::ExternalHostCallTable,
+                // The first row is reserved for the zero entry.
+                required_rows: etable.filter_external_host_call_table().entries().len() + 1,
+            },
+            TableRows {
+                table: TableKind::ImageTable,
+                required_rows: image_table_entries,
+            },
+            // Foreign helper tables are indexed from 1.
+            TableRows {
+                table: TableKind::WasmInputHelperTable,
+                required_rows: public_inputs_and_outputs + 1,
+            },
+            TableRows {
+                table: TableKind::ContextHelperTable,
+                required_rows: context_entries + 1,
+            },
+        ];
+
+        RowEstimate {
+            tables,
+            host_rounds,
+            blinding_factors: blinding_factors::<F>(),
+        }
+    }
+
+    /// Rows available to `table` in a circuit of size `k`, none if `k` is out of range.
+    pub fn available_rows(&self, table: TableKind, k: u32) -> usize {
+        let circuit_config = match CircuitConfig::new(k) {
+            Ok(circuit_config) => circuit_config,
+            Err(_) => return 0,
+        };
+        let max_available_rows = max_available_rows(&circuit_config, self.blinding_factors);
+
+        match table {
+            TableKind::EventTable => {
+                max_available_rows / EVENT_TABLE_ENTRY_ROWS as usize
+                    * EVENT_TABLE_ENTRY_ROWS as usize
+            }
+            TableKind::MemoryTable => {
+                max_available_rows / MEMORY_TABLE_ENTRY_ROWS as usize
+                    * MEMORY_TABLE_ENTRY_ROWS as usize
+            }
+            TableKind::JumpTable => {
+                max_available_rows / JtableOffset::JtableOffsetMax as usize
+                    * JtableOffset::JtableOffsetMax as usize
+            }
+            TableKind::BitTable => max_available_rows / STEP_SIZE * STEP_SIZE,
+            TableKind::ExternalHostCallTable => max_available_rows,
+            // The image table is padded to a fixed size, which is bounded by the usable rows.
+            TableKind::ImageTable => {
+                usize::min(max_image_table_rows() as usize, max_available_rows)
+            }
+            TableKind::WasmInputHelperTable | TableKind::ContextHelperTable => {
+                circuit_config.foreign_table_enable_lines()
+            }
+        }
+    }
+
+    /// Tables which don't fit in a circuit of size `k`.
+    pub fn overflowed_tables(&self, k: u32) -> Vec<&TableRows> {
+        self.tables
+            .iter()
+            .filter(|rows| rows.required_rows > self.available_rows(rows.table, k))
+            .collect()
+    }
+
+    /// Host circuits whose rounds are exhausted in a circuit of size `k`.
+    pub fn overflowed_host_rounds(&self, k: u32) -> Vec<&HostRounds> {
+        self.host_rounds
+            .iter()
+            .filter(|rounds| rounds.used_rounds > rounds.available_rounds(k))
+            .collect()
+    }
+
+    /// The minimal K in `MIN_K..=MAX_K` which all tables and host rounds fit in, `None` if
+    /// the execution can't be proved by a single circuit.
+    pub fn minimal_k(&self) -> Option<u32> {
+        (MIN_K..=MAX_K).find(|k| {
+            self.overflowed_tables(*k).is_empty() && self.overflowed_host_rounds(*k).is_empty()
+        })
+    }
+}
+
+fn blinding_factors<F: FieldExt>() -> usize {
+    let mut meta = ConstraintSystem::<F>::default();
+    ZkWasmCircuit::<F>::configure(&mut meta);
+
+    meta.blinding_factors()
+}
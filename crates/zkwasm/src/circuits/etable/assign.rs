@@ -7,6 +7,7 @@ use num_bigint::BigUint;
 use specs::configure_table::ConfigureTable;
 use specs::itable::InstructionTable;
 use specs::itable::OpcodeClassPlain;
+use specs::jtable::StaticFrameEntry;
 use specs::step::StepInfo;
 use std::collections::BTreeMap;
 use std::rc::Rc;
@@ -16,6 +17,7 @@ use super::EventTableChip;
 use super::EventTableOpcodeConfig;
 use super::EVENT_TABLE_ENTRY_ROWS;
 use crate::circuits::cell::CellExpression;
+use crate::circuits::jtable::JOPS_SEPARATE;
 use crate::circuits::utils::bn_to_field;
 use crate::circuits::utils::step_status::Status;
 use crate::circuits::utils::step_status::StepStatus;
@@ -36,12 +38,37 @@ impl<F: FieldExt> EventTableChip<F> {
         op_configs: &BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>>,
         itable: &InstructionTable,
         event_table: &EventTableWithMemoryInfo,
+        static_jtable: &Vec<StaticFrameEntry>,
     ) -> Vec<(u32, BigUint)> {
         let mut rest_ops = vec![];
 
-        event_table.0.iter().rev().fold(
-            (0, BigUint::zero()),
-            |(rest_mops_sum, rest_jops_sum), entry| {
+        // A trap step consumes the returns of the frames which are still alive.
+        let trap_jops = match event_table.0.last() {
+            Some(entry) if entry.eentry.trap_code().is_some() => {
+                let (calls, returns) =
+                    event_table
+                        .0
+                        .iter()
+                        .fold((0, 0), |(calls, returns), entry| {
+                            match entry.eentry.step_info {
+                                StepInfo::Call { .. } | StepInfo::CallIndirect { .. } => {
+                                    (calls + 1, returns)
+                                }
+                                StepInfo::Return { .. } => (calls, returns + 1),
+                                _ => (calls, returns),
+                            }
+                        });
+
+                BigUint::from((static_jtable.len() + calls - returns) as u64) << JOPS_SEPARATE
+            }
+            _ => BigUint::zero(),
+        };
+
+        event_table
+            .0
+            .iter()
+            .rev()
+            .fold((0, trap_jops), |(rest_mops_sum, rest_jops_sum), entry| {
                 let instruction = entry.eentry.get_instruction(itable);
 
                 let op_config = op_configs.get(&((&instruction.opcode).into())).unwrap();
@@ -54,8 +81,7 @@ impl<F: FieldExt> EventTableChip<F> {
                 rest_ops.push(acc.clone());
 
                 acc
-            },
-        );
+            });
 
         rest_ops.reverse();
 
@@ -73,6 +99,15 @@ impl<F: FieldExt> EventTableChip<F> {
                 || Ok(F::one()),
             )?;
 
+            if self.provable_trap {
+                ctx.region.assign_fixed(
+                    || "etable: trap sel",
+                    self.config.trap_sel,
+                    ctx.offset,
+                    || Ok(F::one()),
+                )?;
+            }
+
             ctx.step(EVENT_TABLE_ENTRY_ROWS as usize);
         }
 
@@ -213,21 +248,34 @@ impl<F: FieldExt> EventTableChip<F> {
                 })
                 .collect::<Vec<_>>();
 
-            let terminate_status = Status {
-                eid: status.last().unwrap().eid + 1,
-                fid: 0,
-                iid: 0,
-                sp: status.last().unwrap().sp
-                    + if let StepInfo::Return { drop, .. } =
-                        &event_table.0.last().unwrap().eentry.step_info
-                    {
-                        *drop
-                    } else {
-                        unreachable!()
-                    },
-                last_jump_eid: 0,
-                allocated_memory_pages: status.last().unwrap().allocated_memory_pages,
-                itable,
+            let last_entry = &event_table.0.last().unwrap().eentry;
+
+            let terminate_status = if last_entry.trap_code().is_some() {
+                // The trap step keeps the state, the next iid is never looked up.
+                Status {
+                    eid: last_entry.eid + 1,
+                    fid: last_entry.fid,
+                    iid: last_entry.iid + 1,
+                    sp: last_entry.sp,
+                    last_jump_eid: last_entry.last_jump_eid,
+                    allocated_memory_pages: last_entry.allocated_memory_pages,
+                    itable,
+                }
+            } else {
+                Status {
+                    eid: status.last().unwrap().eid + 1,
+                    fid: 0,
+                    iid: 0,
+                    sp: status.last().unwrap().sp
+                        + if let StepInfo::Return { drop, .. } = &last_entry.step_info {
+                            *drop
+                        } else {
+                            unreachable!()
+                        },
+                    last_jump_eid: 0,
+                    allocated_memory_pages: status.last().unwrap().allocated_memory_pages,
+                    itable,
+                }
             };
 
             status.push(terminate_status);
@@ -288,6 +336,18 @@ impl<F: FieldExt> EventTableChip<F> {
             if op_config.is_host_public_input(&entry.eentry) {
                 host_public_inputs += 1;
             }
+            if let Some(outcome) = entry.eentry.trap_outcome() {
+                assign_advice!(
+                    public_input_lookup_index_cell,
+                    F::from(host_public_inputs as u64)
+                );
+                assign_advice!(
+                    public_input_lookup_value_cell,
+                    bn_to_field(&outcome.encode_instance())
+                );
+
+                host_public_inputs += 1;
+            }
             if op_config.is_context_input_op(&entry.eentry) {
                 context_in_index += 1;
             }
@@ -340,12 +400,17 @@ impl<F: FieldExt> EventTableChip<F> {
         event_table: &EventTableWithMemoryInfo,
         configure_table: &ConfigureTable,
         fid_of_entry: u32,
+        static_jtable: &Vec<StaticFrameEntry>,
     ) -> Result<EventTablePermutationCells, Error> {
         debug!("size of execution table: {}", event_table.0.len());
         assert!(event_table.0.len() * EVENT_TABLE_ENTRY_ROWS as usize <= self.max_available_rows);
 
-        let rest_ops =
-            self.compute_rest_mops_and_jops(&self.config.op_configs, itable, event_table);
+        let rest_ops = self.compute_rest_mops_and_jops(
+            &self.config.op_configs,
+            itable,
+            event_table,
+            static_jtable,
+        );
 
         self.init(ctx)?;
         ctx.reset();
@@ -38,10 +38,14 @@ use crate::circuits::etable::op_configure::op_select::SelectConfigBuilder;
 use crate::circuits::etable::op_configure::op_store::StoreConfigBuilder;
 use crate::circuits::etable::op_configure::op_test::TestConfigBuilder;
 use crate::circuits::etable::op_configure::op_unary::UnaryConfigBuilder;
+use crate::circuits::etable::op_configure::op_unreachable::UnreachableConfigBuilder;
+use crate::circuits::utils::bn_to_field;
 use crate::constant_from;
+use crate::constant_from_bn;
 use crate::fixed_curr;
 use crate::foreign::context::etable_op_configure::ETableContextHelperTableConfigBuilder;
 use crate::foreign::require_helper::etable_op_configure::ETableRequireHelperTableConfigBuilder;
+use crate::foreign::wasm_input_helper::circuits::WASM_INPUT_FOREIGN_TABLE_KEY;
 use crate::foreign::wasm_input_helper::etable_op_configure::ETableWasmInputHelperTableConfigBuilder;
 use crate::foreign::EventTableForeignCallConfigBuilder;
 use crate::foreign::ForeignTableConfig;
@@ -60,6 +64,9 @@ use specs::encode::instruction_table::encode_instruction_table_entry;
 use specs::etable::EventTableEntry;
 use specs::itable::OpcodeClass;
 use specs::itable::OpcodeClassPlain;
+use specs::trap::TRAP_CODE_SHIFT;
+use specs::trap::TRAP_FID_SHIFT;
+use specs::trap::TRAP_OUTCOME_FLAG_SHIFT;
 use std::collections::BTreeMap;
 use std::rc::Rc;
 
@@ -72,7 +79,7 @@ pub(crate) mod constraint_builder;
 pub(crate) const EVENT_TABLE_ENTRY_ROWS: i32 = 4;
 pub(crate) const OP_CAPABILITY: usize = 32;
 
-const FOREIGN_LOOKUP_CAPABILITY: usize = 6;
+const FOREIGN_LOOKUP_CAPABILITY: usize = 4;
 
 #[derive(Clone)]
 pub struct EventTableCommonConfig<F: FieldExt> {
@@ -100,6 +107,9 @@ pub struct EventTableCommonConfig<F: FieldExt> {
     pow_table_lookup_power_cell: AllocatedUnlimitedCell<F>,
     bit_table_lookup_cells: AllocatedBitTableLookupCells<F>,
     external_foreign_call_lookup_cell: AllocatedUnlimitedCell<F>,
+    // (input_index, value) looked up in the public inputs and outputs
+    pub(crate) public_input_lookup_index_cell: AllocatedUnlimitedCell<F>,
+    pub(crate) public_input_lookup_value_cell: AllocatedUnlimitedCell<F>,
 }
 
 pub(in crate::circuits::etable) trait EventTableOpcodeConfigBuilder<F: FieldExt> {
@@ -202,11 +212,19 @@ pub trait EventTableOpcodeConfig<F: FieldExt> {
     fn is_external_host_call(&self, _entry: &EventTableEntry) -> bool {
         false
     }
+
+    /// Whether the step traps, and the code of the trap. A trap step terminates the execution
+    /// and publishes its outcome as the next public output.
+    fn trap(&self, _meta: &mut VirtualCells<'_, F>) -> Option<(Expression<F>, Expression<F>)> {
+        None
+    }
 }
 
 #[derive(Clone)]
 pub struct EventTableConfig<F: FieldExt> {
     pub step_sel: Column<Fixed>,
+    // Enabled iff the circuit accepts executions terminated by a trap.
+    trap_sel: Column<Fixed>,
     pub common_config: EventTableCommonConfig<F>,
     op_configs: BTreeMap<OpcodeClassPlain, Rc<Box<dyn EventTableOpcodeConfig<F>>>>,
 }
@@ -224,6 +242,7 @@ impl<F: FieldExt> EventTableConfig<F> {
         foreign_table_configs: &BTreeMap<&'static str, Box<dyn ForeignTableConfig<F>>>,
     ) -> EventTableConfig<F> {
         let step_sel = meta.fixed_column();
+        let trap_sel = meta.fixed_column();
 
         let mut allocator =
             EventTableCellAllocator::new(meta, step_sel, rtable, mtable, jtable, cols);
@@ -276,6 +295,8 @@ impl<F: FieldExt> EventTableConfig<F> {
         let pow_table_lookup_power_cell = allocator.alloc_unlimited_cell();
         let external_foreign_call_lookup_cell = allocator.alloc_unlimited_cell();
         let bit_table_lookup_cells = allocator.alloc_bit_table_lookup_cells();
+        let public_input_lookup_index_cell = allocator.alloc_unlimited_cell();
+        let public_input_lookup_value_cell = allocator.alloc_unlimited_cell();
 
         let mut foreign_table_reserved_lookup_cells = [(); FOREIGN_LOOKUP_CAPABILITY]
             .map(|_| allocator.alloc_unlimited_cell())
@@ -304,6 +325,8 @@ impl<F: FieldExt> EventTableConfig<F> {
             pow_table_lookup_power_cell,
             bit_table_lookup_cells,
             external_foreign_call_lookup_cell,
+            public_input_lookup_index_cell,
+            public_input_lookup_value_cell,
         };
 
         let mut op_bitmaps: BTreeMap<OpcodeClassPlain, usize> = BTreeMap::new();
@@ -359,6 +382,7 @@ impl<F: FieldExt> EventTableConfig<F> {
         configure!(OpcodeClass::MemoryGrow, MemoryGrowConfigBuilder);
         configure!(OpcodeClass::BrTable, BrTableConfigBuilder);
         configure!(OpcodeClass::CallIndirect, CallIndirectConfigBuilder);
+        configure!(OpcodeClass::Unreachable, UnreachableConfigBuilder);
 
         macro_rules! configure_foreign {
             ($x:ident, $i:expr) => {
@@ -461,7 +485,17 @@ impl<F: FieldExt> EventTableConfig<F> {
             vec![sum_ops_expr_with_init(
                 rest_jops_cell.next_expr(meta) - rest_jops_cell.curr_expr(meta),
                 meta,
-                &|meta, config: &Rc<Box<dyn EventTableOpcodeConfig<F>>>| config.jops_expr(meta),
+                &|meta, config: &Rc<Box<dyn EventTableOpcodeConfig<F>>>| {
+                    // A trap step consumes the jops of the frames which never return.
+                    let trap_jops = config
+                        .trap(meta)
+                        .map(|(is_trap, _)| is_trap * rest_jops_cell.curr_expr(meta));
+
+                    [config.jops_expr(meta), trap_jops]
+                        .into_iter()
+                        .flatten()
+                        .reduce(|acc, x| acc + x)
+                },
                 None,
             )]
         });
@@ -471,7 +505,15 @@ impl<F: FieldExt> EventTableConfig<F> {
                 input_index_cell.curr_expr(meta) - input_index_cell.next_expr(meta),
                 meta,
                 &|meta, config: &Rc<Box<dyn EventTableOpcodeConfig<F>>>| {
-                    config.input_index_increase(meta, &common_config)
+                    let trap_output = config.trap(meta).map(|(is_trap, _)| is_trap);
+
+                    [
+                        config.input_index_increase(meta, &common_config),
+                        trap_output,
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .reduce(|acc, x| acc + x)
                 },
                 Some(&|meta| enabled_cell.curr_expr(meta)),
             )]
@@ -617,6 +659,16 @@ impl<F: FieldExt> EventTableConfig<F> {
             },
         );
 
+        foreign_table_configs
+            .get(WASM_INPUT_FOREIGN_TABLE_KEY)
+            .unwrap()
+            .configure_in_table(meta, "c8h. public_input_lookup in input table", &|meta| {
+                vec![
+                    public_input_lookup_index_cell.curr_expr(meta) * fixed_curr!(meta, step_sel),
+                    public_input_lookup_value_cell.curr_expr(meta) * fixed_curr!(meta, step_sel),
+                ]
+            });
+
         bit_table.configure_in_table(meta, "c8f: bit_table_lookup in bit_table", |meta| {
             (
                 fixed_curr!(meta, step_sel),
@@ -636,8 +688,67 @@ impl<F: FieldExt> EventTableConfig<F> {
             ]
         });
 
+        meta.create_gate("c10a. trap is disabled", |meta| {
+            vec![sum_ops_expr_with_init(
+                constant_from!(0),
+                meta,
+                &|meta, config: &Rc<Box<dyn EventTableOpcodeConfig<F>>>| {
+                    config.trap(meta).map(|(is_trap, _)| {
+                        is_trap * (constant_from!(1) - fixed_curr!(meta, trap_sel))
+                    })
+                },
+                None,
+            )]
+        });
+
+        meta.create_gate("c10b. trap terminates", |meta| {
+            vec![sum_ops_expr_with_init(
+                constant_from!(0),
+                meta,
+                &|meta, config: &Rc<Box<dyn EventTableOpcodeConfig<F>>>| {
+                    config
+                        .trap(meta)
+                        .map(|(is_trap, _)| is_trap * enabled_cell.next_expr(meta))
+                },
+                None,
+            )]
+        });
+
+        meta.create_gate("c10c. trap outcome", |meta| {
+            vec![
+                sum_ops_expr_with_init(
+                    constant_from!(0),
+                    meta,
+                    &|meta, config: &Rc<Box<dyn EventTableOpcodeConfig<F>>>| {
+                        config.trap(meta).map(|(is_trap, _)| {
+                            public_input_lookup_index_cell.curr_expr(meta)
+                                - is_trap * input_index_cell.curr_expr(meta)
+                        })
+                    },
+                    None,
+                ),
+                sum_ops_expr_with_init(
+                    constant_from!(0),
+                    meta,
+                    &|meta, config: &Rc<Box<dyn EventTableOpcodeConfig<F>>>| {
+                        config.trap(meta).map(|(is_trap, code)| {
+                            let outcome = constant_from_bn!(
+                                &(BigUint::from(1u64) << TRAP_OUTCOME_FLAG_SHIFT)
+                            ) + code * constant_from!(1u64 << TRAP_CODE_SHIFT)
+                                + fid_cell.curr_expr(meta) * constant_from!(1u64 << TRAP_FID_SHIFT)
+                                + iid_cell.curr_expr(meta);
+
+                            public_input_lookup_value_cell.curr_expr(meta) - is_trap * outcome
+                        })
+                    },
+                    None,
+                ),
+            ]
+        });
+
         Self {
             step_sel,
+            trap_sel,
             common_config,
             op_configs,
         }
@@ -647,14 +758,20 @@ impl<F: FieldExt> EventTableConfig<F> {
 pub struct EventTableChip<F: FieldExt> {
     config: EventTableConfig<F>,
     max_available_rows: usize,
+    provable_trap: bool,
 }
 
 impl<F: FieldExt> EventTableChip<F> {
-    pub(super) fn new(config: EventTableConfig<F>, max_available_rows: usize) -> Self {
+    pub(super) fn new(
+        config: EventTableConfig<F>,
+        max_available_rows: usize,
+        provable_trap: bool,
+    ) -> Self {
         Self {
             config,
             max_available_rows: max_available_rows / EVENT_TABLE_ENTRY_ROWS as usize
                 * EVENT_TABLE_ENTRY_ROWS as usize,
+            provable_trap,
         }
     }
 }
@@ -25,3 +25,4 @@ pub mod op_select;
 pub mod op_store;
 pub mod op_test;
 pub mod op_unary;
+pub mod op_unreachable;
@@ -25,6 +25,7 @@ use specs::itable::OPCODE_CLASS_SHIFT;
 use specs::mtable::LocationType;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
+use specs::trap::TrapCode;
 
 pub struct BinConfig<F: FieldExt> {
     lhs: AllocatedU64CellWithFlagBitDyn<F>,
@@ -48,6 +49,9 @@ pub struct BinConfig<F: FieldExt> {
     is_rem_s: AllocatedBitCell<F>,
     is_div_s_or_rem_s: AllocatedBitCell<F>,
 
+    is_trap: AllocatedBitCell<F>,
+    is_integer_overflow: AllocatedBitCell<F>,
+
     res_flag: AllocatedUnlimitedCell<F>,
     size_modulus: AllocatedUnlimitedCell<F>,
     normalized_lhs: AllocatedUnlimitedCell<F>,
@@ -92,6 +96,9 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
 
         let is_div_s_or_rem_s = allocator.alloc_bit_cell();
 
+        let is_trap = allocator.alloc_bit_cell();
+        let is_integer_overflow = allocator.alloc_bit_cell();
+
         let d_leading_u16 = allocator.alloc_unlimited_cell();
         let normalized_lhs = allocator.alloc_unlimited_cell();
         let normalized_rhs = allocator.alloc_unlimited_cell();
@@ -133,7 +140,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
                 move |____| constant_from!(LocationType::Stack as u64),
                 move |meta| sp.expr(meta) + constant_from!(2),
                 move |meta| is_i32.expr(meta),
-                move |____| constant_from!(1),
+                move |meta| constant_from!(1) - is_trap.expr(meta),
             );
 
         let res = memory_table_lookup_stack_write.value_cell;
@@ -154,6 +161,37 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
             }),
         );
 
+        // A division by zero, or the signed division of the minimal integer by -1, traps
+        // instead of writing the result.
+        constraint_builder.push(
+            "bin: trap",
+            Box::new(move |meta| {
+                vec![
+                    is_trap.expr(meta)
+                        * (constant_from!(1) - is_integer_overflow.expr(meta))
+                        * rhs.u64_cell.expr(meta),
+                    is_trap.expr(meta)
+                        * (is_add.expr(meta) + is_sub.expr(meta) + is_mul.expr(meta)),
+                ]
+            }),
+        );
+
+        constraint_builder.push(
+            "bin: integer overflow trap",
+            Box::new(move |meta| {
+                let int_min = constant_from!(1u64 << 63)
+                    - is_i32.expr(meta) * constant_from!((1u64 << 63) - (1u64 << 31));
+
+                vec![
+                    is_integer_overflow.expr(meta) * (constant_from!(1) - is_trap.expr(meta)),
+                    is_integer_overflow.expr(meta) * (constant_from!(1) - is_div_s.expr(meta)),
+                    is_integer_overflow.expr(meta) * (lhs.u64_cell.expr(meta) - int_min),
+                    is_integer_overflow.expr(meta)
+                        * (rhs.u64_cell.expr(meta) + constant_from!(1) - size_modulus.expr(meta)),
+                ]
+            }),
+        );
+
         // cs: size_modulus = if is_i32 { 1 << 32 } else { 1 << 64 }
         constraint_builder.push(
             "bin: size modulus",
@@ -214,6 +252,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
                         * (is_rem_u.expr(meta) + is_div_u.expr(meta)),
                     (aux1.u64_cell.expr(meta) + aux2.u64_cell.expr(meta) + constant_from!(1)
                         - rhs.u64_cell.expr(meta))
+                        * (constant_from!(1) - is_trap.expr(meta))
                         * (is_rem_u.expr(meta) + is_div_u.expr(meta)),
                     (res.expr(meta) - d.u64_cell.expr(meta)) * is_div_u.expr(meta),
                     (res.expr(meta) - aux1.u64_cell.expr(meta)) * is_rem_u.expr(meta),
@@ -265,6 +304,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
                         * is_div_s_or_rem_s.expr(meta),
                     (aux1.u64_cell.expr(meta) + aux2.u64_cell.expr(meta) + constant_from!(1)
                         - normalized_rhs.expr(meta))
+                        * (constant_from!(1) - is_trap.expr(meta))
                         * is_div_s_or_rem_s.expr(meta),
                 ]
             }),
@@ -327,6 +367,8 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for BinConfigBuilder {
             is_div_s,
             is_rem_s,
             is_div_s_or_rem_s,
+            is_trap,
+            is_integer_overflow,
             memory_table_lookup_stack_read_lhs,
             memory_table_lookup_stack_read_rhs,
             memory_table_lookup_stack_write,
@@ -415,6 +457,23 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig<F> {
             _ => unreachable!(),
         };
 
+        let trap_code = entry.eentry.trap_code();
+        let is_trap = trap_code.is_some();
+        // The result of a trapping division is never written, it's assigned as the quotient and
+        // the remainder of a division by zero: 0 and the left operand.
+        let value = if is_trap {
+            self.is_trap.assign(ctx, F::one())?;
+            self.is_integer_overflow
+                .assign_bool(ctx, trap_code == Some(TrapCode::IntegerOverflow))?;
+
+            match class {
+                BinOp::UnsignedRem | BinOp::SignedRem => left,
+                _ => 0,
+            }
+        } else {
+            value
+        };
+
         self.lhs
             .assign(ctx, left.into(), var_type == VarType::I32)?;
         self.rhs
@@ -499,9 +558,15 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig<F> {
 
         match class {
             BinOp::UnsignedDiv | BinOp::UnsignedRem => {
-                self.d.assign(ctx, left / right)?;
-                self.aux1.assign(ctx, left % right)?;
-                self.aux2.assign(ctx, right - left % right - 1)?;
+                if is_trap {
+                    self.d.assign(ctx, 0)?;
+                    self.aux1.assign(ctx, left)?;
+                    self.aux2.assign(ctx, 0)?;
+                } else {
+                    self.d.assign(ctx, left / right)?;
+                    self.aux1.assign(ctx, left % right)?;
+                    self.aux2.assign(ctx, right - left % right - 1)?;
+                }
             }
             BinOp::SignedDiv | BinOp::SignedRem => {
                 let left_flag = left >> (shift - 1) != 0;
@@ -518,8 +583,14 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig<F> {
                 } else {
                     right
                 };
-                let d = normalized_lhs / normalized_rhs;
-                let rem = normalized_lhs % normalized_rhs;
+                let (d, rem) = if is_trap {
+                    (0, normalized_lhs)
+                } else {
+                    (
+                        normalized_lhs / normalized_rhs,
+                        normalized_lhs % normalized_rhs,
+                    )
+                };
                 let d_leading_u16 = d >> (shift - 16);
 
                 self.degree_helper1
@@ -540,7 +611,9 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig<F> {
                 )?;
                 self.d.assign(ctx, d)?;
                 self.aux1.assign(ctx, rem)?;
-                self.aux2.assign(ctx, normalized_rhs - rem - 1)?;
+                if !is_trap {
+                    self.aux2.assign(ctx, normalized_rhs - rem - 1)?;
+                }
             }
             _ => {}
         }
@@ -574,28 +647,49 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for BinConfig<F> {
             left,
         )?;
 
-        self.memory_table_lookup_stack_write.assign(
-            ctx,
-            step.current.eid,
-            entry.memory_rw_entires[2].end_eid,
-            step.current.sp + 2,
-            LocationType::Stack,
-            var_type == VarType::I32,
-            value,
-        )?;
+        if is_trap {
+            self.memory_table_lookup_stack_write
+                .value_cell
+                .assign(ctx, value.into())?;
+        } else {
+            self.memory_table_lookup_stack_write.assign(
+                ctx,
+                step.current.eid,
+                entry.memory_rw_entires[2].end_eid,
+                step.current.sp + 2,
+                LocationType::Stack,
+                var_type == VarType::I32,
+                value,
+            )?;
+        }
 
         Ok(())
     }
 
-    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(1))
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) - self.is_trap.expr(meta))
     }
 
-    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
-        1
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        if entry.trap_code().is_some() {
+            0
+        } else {
+            1
+        }
+    }
+
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) - self.is_trap.expr(meta))
     }
 
-    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant!(F::one()))
+    fn trap(&self, meta: &mut VirtualCells<'_, F>) -> Option<(Expression<F>, Expression<F>)> {
+        let is_integer_overflow = self.is_integer_overflow.expr(meta);
+
+        Some((
+            self.is_trap.expr(meta),
+            constant_from!(TrapCode::DivisionByZero as u64)
+                * (constant_from!(1) - is_integer_overflow.clone())
+                + constant_from!(TrapCode::IntegerOverflow as u64) * is_integer_overflow,
+        ))
     }
 }
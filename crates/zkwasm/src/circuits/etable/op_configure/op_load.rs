@@ -30,6 +30,7 @@ use specs::itable::OPCODE_CLASS_SHIFT;
 use specs::mtable::LocationType;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
+use specs::trap::TrapCode;
 
 pub struct LoadConfig<F: FieldExt> {
     // offset in opcode
@@ -82,6 +83,8 @@ pub struct LoadConfig<F: FieldExt> {
     address_within_allocated_pages_helper: AllocatedCommonRangeCell<F>,
 
     degree_helper: AllocatedBitCell<F>,
+
+    is_trap: AllocatedBitCell<F>,
 }
 
 pub struct LoadConfigBuilder;
@@ -132,6 +135,8 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
 
         let degree_helper = allocator.alloc_bit_cell();
 
+        let is_trap = allocator.alloc_bit_cell();
+
         let sp = common_config.sp_cell;
         let eid = common_config.eid_cell;
 
@@ -154,7 +159,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
                 move |____| constant_from!(LocationType::Heap as u64),
                 move |meta| load_block_index.expr(meta),
                 move |____| constant_from!(0),
-                move |____| constant_from!(1),
+                move |meta| constant_from!(1) - is_trap.expr(meta),
             );
 
         let memory_table_lookup_heap_read2 = allocator
@@ -165,7 +170,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
                 move |____| constant_from!(LocationType::Heap as u64),
                 move |meta| load_block_index.expr(meta) + constant_from!(1),
                 move |____| constant_from!(0),
-                move |meta| is_cross_block.expr(meta),
+                move |meta| is_cross_block.expr(meta) * (constant_from!(1) - is_trap.expr(meta)),
             );
 
         let memory_table_lookup_stack_write = allocator.alloc_memory_table_lookup_write_cell(
@@ -176,7 +181,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
             move |meta| sp.expr(meta) + constant_from!(1),
             move |meta| is_i32.expr(meta),
             move |meta| res.expr(meta),
-            move |____| constant_from!(1),
+            move |meta| constant_from!(1) - is_trap.expr(meta),
         );
 
         let load_base = memory_table_lookup_stack_read.value_cell;
@@ -362,7 +367,14 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
                         + constant_from!(1)
                         + address_within_allocated_pages_helper.expr(meta)
                         - current_memory_page_size.expr(meta)
-                            * constant_from!(WASM_BLOCKS_PER_PAGE)),
+                            * constant_from!(WASM_BLOCKS_PER_PAGE))
+                        * (constant_from!(1) - is_trap.expr(meta)),
+                    // The last byte of an out of bounds access is beyond the allocated pages.
+                    (load_block_index.expr(meta) + is_cross_block.expr(meta)
+                        - address_within_allocated_pages_helper.expr(meta)
+                        - current_memory_page_size.expr(meta)
+                            * constant_from!(WASM_BLOCKS_PER_PAGE))
+                        * is_trap.expr(meta),
                 ]
             }),
         );
@@ -403,6 +415,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for LoadConfigBuilder {
             load_tailing_diff,
 
             degree_helper,
+            is_trap,
         })
     }
 }
@@ -531,10 +544,18 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for LoadConfig<F> {
                     F::from(load_size.is_sign()) * F::from(load_picked_leading_u8 >> 7),
                 )?;
 
+                let is_trap = entry.eentry.trap_code().is_some();
+                self.is_trap.assign_bool(ctx, is_trap)?;
+
                 self.address_within_allocated_pages_helper.assign_u32(
                     ctx,
-                    step.current.allocated_memory_pages * WASM_BLOCKS_PER_PAGE
-                        - (block_start_index + is_cross_block as u32 + 1),
+                    if is_trap {
+                        block_start_index + is_cross_block as u32
+                            - step.current.allocated_memory_pages * WASM_BLOCKS_PER_PAGE
+                    } else {
+                        step.current.allocated_memory_pages * WASM_BLOCKS_PER_PAGE
+                            - (block_start_index + is_cross_block as u32 + 1)
+                    },
                 )?;
 
                 let mut i = 0;
@@ -550,6 +571,10 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for LoadConfig<F> {
                 )?;
                 i += 1;
 
+                if is_trap {
+                    return Ok(());
+                }
+
                 self.memory_table_lookup_heap_read1.assign(
                     ctx,
                     entry.memory_rw_entires[i].start_eid,
@@ -592,11 +617,22 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for LoadConfig<F> {
         }
     }
 
-    fn mops(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(1))
+    fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) - self.is_trap.expr(meta))
+    }
+
+    fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        if entry.trap_code().is_some() {
+            0
+        } else {
+            1
+        }
     }
 
-    fn memory_writing_ops(&self, _: &EventTableEntry) -> u32 {
-        1
+    fn trap(&self, meta: &mut VirtualCells<'_, F>) -> Option<(Expression<F>, Expression<F>)> {
+        Some((
+            self.is_trap.expr(meta),
+            constant_from!(TrapCode::MemoryAccessOutOfBounds as u64),
+        ))
     }
 }
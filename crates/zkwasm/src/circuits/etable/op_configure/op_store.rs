@@ -30,6 +30,7 @@ use specs::itable::OPCODE_CLASS_SHIFT;
 use specs::mtable::LocationType;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
+use specs::trap::TrapCode;
 
 pub struct StoreConfig<F: FieldExt> {
     // offset in opcode
@@ -78,6 +79,8 @@ pub struct StoreConfig<F: FieldExt> {
     lookup_pow_power: AllocatedUnlimitedCell<F>,
 
     address_within_allocated_pages_helper: AllocatedCommonRangeCell<F>,
+
+    is_trap: AllocatedBitCell<F>,
 }
 
 pub struct StoreConfigBuilder;
@@ -119,6 +122,8 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
         let is_eight_bytes = allocator.alloc_bit_cell();
         let is_i32 = allocator.alloc_bit_cell();
 
+        let is_trap = allocator.alloc_bit_cell();
+
         let sp = common_config.sp_cell;
         let eid = common_config.eid_cell;
 
@@ -152,7 +157,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
                 move |____| constant_from!(LocationType::Heap as u64),
                 move |meta| load_block_index.expr(meta),
                 move |____| constant_from!(0),
-                move |____| constant_from!(1),
+                move |meta| constant_from!(1) - is_trap.expr(meta),
             );
 
         let memory_table_lookup_heap_read2 = allocator
@@ -163,7 +168,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
                 move |____| constant_from!(LocationType::Heap as u64),
                 move |meta| load_block_index.expr(meta) + constant_from!(1),
                 move |____| constant_from!(0),
-                move |meta| is_cross_block.expr(meta),
+                move |meta| is_cross_block.expr(meta) * (constant_from!(1) - is_trap.expr(meta)),
             );
 
         let memory_table_lookup_heap_write1 = allocator
@@ -174,7 +179,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
                 move |____| constant_from!(LocationType::Heap as u64),
                 move |meta| load_block_index.expr(meta),
                 move |____| constant_from!(0),
-                move |____| constant_from!(1),
+                move |meta| constant_from!(1) - is_trap.expr(meta),
             );
 
         let memory_table_lookup_heap_write2 = allocator
@@ -185,7 +190,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
                 move |____| constant_from!(LocationType::Heap as u64),
                 move |meta| load_block_index.expr(meta) + constant_from!(1),
                 move |____| constant_from!(0),
-                move |meta| is_cross_block.expr(meta),
+                move |meta| is_cross_block.expr(meta) * (constant_from!(1) - is_trap.expr(meta)),
             );
 
         let store_base = memory_table_lookup_stack_read_pos.value_cell;
@@ -389,7 +394,13 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
                         + constant_from!(1)
                         + address_within_allocated_pages_helper.expr(meta)
                         - current_memory_page_size.expr(meta)
-                            * constant_from!(WASM_BLOCKS_PER_PAGE)),
+                            * constant_from!(WASM_BLOCKS_PER_PAGE))
+                        * (constant_from!(1) - is_trap.expr(meta)),
+                    (load_block_index.expr(meta) + is_cross_block.expr(meta)
+                        - address_within_allocated_pages_helper.expr(meta)
+                        - current_memory_page_size.expr(meta)
+                            * constant_from!(WASM_BLOCKS_PER_PAGE))
+                        * is_trap.expr(meta),
                 ]
             }),
         );
@@ -428,6 +439,7 @@ impl<F: FieldExt> EventTableOpcodeConfigBuilder<F> for StoreConfigBuilder {
             load_tailing_diff,
             len,
             len_modulus,
+            is_trap,
         })
     }
 }
@@ -548,10 +560,18 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig<F> {
                 self.len.assign(ctx, (len as u64).into())?;
                 self.is_i32.assign_bool(ctx, vtype == VarType::I32)?;
 
+                let is_trap = entry.eentry.trap_code().is_some();
+                self.is_trap.assign_bool(ctx, is_trap)?;
+
                 self.address_within_allocated_pages_helper.assign_u32(
                     ctx,
-                    step.current.allocated_memory_pages * WASM_BLOCKS_PER_PAGE
-                        - (block_start_index + is_cross_block as u32 + 1),
+                    if is_trap {
+                        block_start_index + is_cross_block as u32
+                            - step.current.allocated_memory_pages * WASM_BLOCKS_PER_PAGE
+                    } else {
+                        step.current.allocated_memory_pages * WASM_BLOCKS_PER_PAGE
+                            - (block_start_index + is_cross_block as u32 + 1)
+                    },
                 )?;
 
                 self.memory_table_lookup_stack_read_val.assign(
@@ -576,6 +596,21 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig<F> {
                     raw_address as u64,
                 )?;
 
+                if is_trap {
+                    // The heap is never written, the blocks only satisfy the value constraints.
+                    let updated_value = BigUint::from(value_wrapped) << (inner_byte_index * 8);
+                    let mut updated_blocks = updated_value.to_u64_digits().into_iter();
+
+                    self.memory_table_lookup_heap_write1
+                        .value_cell
+                        .assign(ctx, updated_blocks.next().unwrap_or(0).into())?;
+                    self.memory_table_lookup_heap_write2
+                        .value_cell
+                        .assign(ctx, updated_blocks.next().unwrap_or(0).into())?;
+
+                    return Ok(());
+                }
+
                 self.memory_table_lookup_heap_read1.assign(
                     ctx,
                     entry.memory_rw_entires[2].start_eid,
@@ -625,15 +660,22 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig<F> {
         }
     }
 
-    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(2))
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(2) * (constant_from!(1) - self.is_trap.expr(meta)))
     }
 
     fn mops(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(1) + self.is_cross_block.expr(meta))
+        Some(
+            (constant_from!(1) + self.is_cross_block.expr(meta))
+                * (constant_from!(1) - self.is_trap.expr(meta)),
+        )
     }
 
     fn memory_writing_ops(&self, entry: &EventTableEntry) -> u32 {
+        if entry.trap_code().is_some() {
+            return 0;
+        }
+
         match entry.step_info {
             StepInfo::Store {
                 store_size,
@@ -650,4 +692,11 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for StoreConfig<F> {
             _ => unreachable!(),
         }
     }
+
+    fn trap(&self, meta: &mut VirtualCells<'_, F>) -> Option<(Expression<F>, Expression<F>)> {
+        Some((
+            self.is_trap.expr(meta),
+            constant_from!(TrapCode::MemoryAccessOutOfBounds as u64),
+        ))
+    }
 }
@@ -214,10 +214,11 @@ pub struct ImageTableConfig<F: FieldExt> {
 #[derive(Clone)]
 pub struct ImageTableChip<F: FieldExt> {
     config: ImageTableConfig<F>,
+    k: u32,
 }
 
 impl<F: FieldExt> ImageTableChip<F> {
-    pub fn new(config: ImageTableConfig<F>) -> Self {
-        ImageTableChip { config }
+    pub fn new(config: ImageTableConfig<F>, k: u32) -> Self {
+        ImageTableChip { config, k }
     }
 }
@@ -1,3 +1,4 @@
+use crate::circuits::config::CircuitConfig;
 use crate::circuits::utils::Context;
 
 use halo2_proofs::arithmetic::FieldExt;
@@ -11,12 +12,13 @@ use std::marker::PhantomData;
 pub(crate) mod cell;
 pub(crate) mod etable;
 
-mod bit_table;
+pub(crate) mod bit_table;
 mod external_host_call_table;
-mod mtable;
+pub(crate) mod mtable;
 mod traits;
 
 pub mod config;
+pub mod estimate;
 pub mod image_table;
 pub mod jtable;
 pub mod rtable;
@@ -28,13 +30,15 @@ pub type ExecutionTable = specs::ExecutionTable;
 
 #[derive(Default, Clone)]
 pub struct ZkWasmCircuit<F: FieldExt> {
+    pub circuit_config: CircuitConfig,
     pub tables: Tables,
     _data: PhantomData<F>,
 }
 
 impl<F: FieldExt> ZkWasmCircuit<F> {
-    pub fn new(tables: Tables) -> Self {
+    pub fn new(circuit_config: CircuitConfig, tables: Tables) -> Self {
         ZkWasmCircuit {
+            circuit_config,
             tables,
             _data: PhantomData,
         }
@@ -59,12 +63,13 @@ pub(self) trait Lookup<F: FieldExt> {
 }
 
 pub struct ZkWasmCircuitBuilder {
+    pub circuit_config: CircuitConfig,
     pub tables: Tables,
     pub public_inputs_and_outputs: Vec<u64>,
 }
 
 impl ZkWasmCircuitBuilder {
     pub fn build_circuit<F: FieldExt>(&self) -> ZkWasmCircuit<F> {
-        ZkWasmCircuit::new(self.tables.clone())
+        ZkWasmCircuit::new(self.circuit_config, self.tables.clone())
     }
 }
@@ -1,4 +1,3 @@
-use super::config::zkwasm_k;
 use super::config::POW_TABLE_POWER_START;
 use super::utils::bn_to_field;
 use crate::circuits::bit_table::BitTableOp;
@@ -48,7 +47,7 @@ struct OpTable {
 
 #[derive(Clone)]
 pub struct RangeTableConfig<F: FieldExt> {
-    // [0 .. 1 << zkwasm_k() - 1)
+    // [0 .. 1 << (k - 1))
     common_range_col: TableColumn,
     // [0 .. 65536)
     u16_col: TableColumn,
@@ -152,18 +151,22 @@ impl<F: FieldExt> RangeTableConfig<F> {
 
 pub struct RangeTableChip<F: FieldExt> {
     config: RangeTableConfig<F>,
+    common_range_rows: usize,
 }
 
 impl<F: FieldExt> RangeTableChip<F> {
-    pub fn new(config: RangeTableConfig<F>) -> Self {
-        RangeTableChip { config }
+    pub fn new(config: RangeTableConfig<F>, common_range_rows: usize) -> Self {
+        RangeTableChip {
+            config,
+            common_range_rows,
+        }
     }
 
     pub fn init(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
         layouter.assign_table(
             || "common range table",
             |mut table| {
-                for i in 0..(1 << (zkwasm_k() - 1)) {
+                for i in 0..self.common_range_rows {
                     table.assign_cell(
                         || "range table",
                         self.config.common_range_col,
@@ -38,12 +38,11 @@ use crate::foreign::context::circuits::assign::ContextContHelperTableChip;
 use crate::foreign::context::circuits::assign::ExtractContextFromTrace;
 use crate::foreign::context::circuits::ContextContHelperTableConfig;
 use crate::foreign::context::circuits::CONTEXT_FOREIGN_TABLE_KEY;
-use crate::foreign::foreign_table_enable_lines;
 use crate::foreign::wasm_input_helper::circuits::WasmInputHelperTableConfig;
 use crate::foreign::wasm_input_helper::circuits::WASM_INPUT_FOREIGN_TABLE_KEY;
 use crate::foreign::ForeignTableConfig;
 
-use super::config::zkwasm_k;
+use super::config::CircuitConfig;
 use super::image_table::ImageTableConfig;
 
 pub const VAR_COLUMNS: usize = 51;
@@ -65,7 +64,11 @@ pub struct ZkWasmCircuitConfig<F: FieldExt> {
 
     foreign_table_from_zero_index: Column<Fixed>,
 
-    max_available_rows: usize,
+    blinding_factors: usize,
+}
+
+pub(crate) fn max_available_rows(circuit_config: &CircuitConfig, blinding_factors: usize) -> usize {
+    (1 << circuit_config.k()) - (blinding_factors + 1 + RESERVE_ROWS)
 }
 
 impl<F: FieldExt> Circuit<F> for ZkWasmCircuit<F> {
@@ -74,10 +77,13 @@ impl<F: FieldExt> Circuit<F> for ZkWasmCircuit<F> {
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        ZkWasmCircuit::new(Tables {
-            compilation_tables: self.tables.compilation_tables.clone(),
-            execution_tables: ExecutionTable::default(),
-        })
+        ZkWasmCircuit::new(
+            self.circuit_config,
+            Tables {
+                compilation_tables: self.tables.compilation_tables.clone(),
+                execution_tables: ExecutionTable::default(),
+            },
+        )
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
@@ -130,8 +136,7 @@ impl<F: FieldExt> Circuit<F> for ZkWasmCircuit<F> {
 
         assert_eq!(cols.count(), 0);
 
-        let max_available_rows = (1 << zkwasm_k()) - (meta.blinding_factors() + 1 + RESERVE_ROWS);
-        debug!("max_available_rows: {:?}", max_available_rows);
+        let blinding_factors = meta.blinding_factors();
 
         Self::Config {
             rtable,
@@ -144,7 +149,7 @@ impl<F: FieldExt> Circuit<F> for ZkWasmCircuit<F> {
             context_helper_table,
             foreign_table_from_zero_index,
 
-            max_available_rows,
+            blinding_factors,
         }
     }
 
@@ -155,20 +160,27 @@ impl<F: FieldExt> Circuit<F> for ZkWasmCircuit<F> {
     ) -> Result<(), Error> {
         let assign_timer = start_timer!(|| "Assign");
 
-        let rchip = RangeTableChip::new(config.rtable);
-        let image_chip = ImageTableChip::new(config.image_table);
-        let mchip = MemoryTableChip::new(config.mtable, config.max_available_rows);
-        let jchip = JumpTableChip::new(config.jtable, config.max_available_rows);
-        let echip = EventTableChip::new(config.etable, config.max_available_rows);
-        let bit_chip = BitTableChip::new(config.bit_table, config.max_available_rows);
+        let max_available_rows = max_available_rows(&self.circuit_config, config.blinding_factors);
+        debug!("max_available_rows: {:?}", max_available_rows);
+
+        let rchip = RangeTableChip::new(config.rtable, self.circuit_config.common_range_rows());
+        let image_chip = ImageTableChip::new(config.image_table, self.circuit_config.k());
+        let mchip = MemoryTableChip::new(config.mtable, max_available_rows);
+        let jchip = JumpTableChip::new(config.jtable, max_available_rows);
+        let echip = EventTableChip::new(
+            config.etable,
+            max_available_rows,
+            self.circuit_config.provable_trap(),
+        );
+        let bit_chip = BitTableChip::new(config.bit_table, max_available_rows);
         let external_host_call_chip =
-            ExternalHostCallChip::new(config.external_host_call_table, config.max_available_rows);
+            ExternalHostCallChip::new(config.external_host_call_table, max_available_rows);
         let context_chip = ContextContHelperTableChip::new(config.context_helper_table);
 
         layouter.assign_region(
             || "foreign helper",
             |mut region| {
-                for offset in 0..foreign_table_enable_lines() {
+                for offset in 0..self.circuit_config.foreign_table_enable_lines() {
                     region.assign_fixed(
                         || "foreign table from zero index",
                         config.foreign_table_from_zero_index,
@@ -199,10 +211,12 @@ impl<F: FieldExt> Circuit<F> for ZkWasmCircuit<F> {
             layouter.assign_region(
                 || "jtable mtable etable",
                 |region| {
-                    let mut ctx = Context::new(region);
+                    let mut ctx = Context::new(region, self.circuit_config.k());
 
-                    let memory_writing_table: MemoryWritingTable =
-                        self.tables.execution_tables.mtable.clone().into();
+                    let memory_writing_table = MemoryWritingTable::new(
+                        self.tables.execution_tables.mtable.clone(),
+                        self.circuit_config.maximal_eid(),
+                    );
 
                     let etable = exec_with_profile!(
                         || "Prepare memory info for etable",
@@ -220,6 +234,7 @@ impl<F: FieldExt> Circuit<F> for ZkWasmCircuit<F> {
                             &etable,
                             &self.tables.compilation_tables.configure_table,
                             self.tables.compilation_tables.fid_of_entry,
+                            &self.tables.compilation_tables.static_jtable,
                         )?
                     );
 
@@ -16,14 +16,17 @@ pub struct Context<'a, F: FieldExt> {
     pub region: Box<Region<'a, F>>,
     pub offset: usize,
     records: Vec<usize>,
+    /// K of the circuit, which bounds the values of the common range cells
+    pub k: u32,
 }
 
 impl<'a, F: FieldExt> Context<'a, F> {
-    pub fn new(region: Region<'a, F>) -> Self {
+    pub fn new(region: Region<'a, F>, k: u32) -> Self {
         Self {
             region: Box::new(region),
             offset: 0usize,
             records: vec![],
+            k,
         }
     }
 
@@ -11,7 +11,6 @@ use std::env;
 use std::io::Write;
 use std::path::PathBuf;
 
-use crate::circuits::config::zkwasm_k;
 use crate::runtime::memory_event_of_step;
 
 #[derive(Clone, Debug, Serialize)]
@@ -30,9 +29,9 @@ impl MemoryWritingEntry {
 #[derive(Debug, Serialize)]
 pub struct MemoryWritingTable(pub(in crate::circuits) Vec<MemoryWritingEntry>);
 
-impl From<MTable> for MemoryWritingTable {
-    fn from(value: MTable) -> Self {
-        let maximal_eid = (1u32 << (zkwasm_k() - 1)) - 1;
+impl MemoryWritingTable {
+    /// `maximal_eid` is the end eid of the entries which are never overwritten.
+    pub fn new(value: MTable, maximal_eid: u32) -> Self {
         let mut index = 0;
 
         let mut entries: Vec<MemoryWritingEntry> = value
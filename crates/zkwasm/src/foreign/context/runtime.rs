@@ -10,6 +10,7 @@ use wasmi::RuntimeArgs;
 
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
+use crate::runtime::host::ForeignError;
 
 use super::Op;
 
@@ -33,10 +34,8 @@ impl Context {
         self.outputs.lock().unwrap().push(value)
     }
 
-    pub fn read_context(&mut self) -> u64 {
-        self.inputs
-            .pop()
-            .expect("Failed to pop value from context_in array, please check you inputs")
+    pub fn read_context(&mut self) -> Result<u64, ForeignError> {
+        self.inputs.pop().ok_or(ForeignError::ContextUnderflow)
     }
 }
 
@@ -65,7 +64,9 @@ pub fn register_context_foreign(
             |_obs: &Observer, context: &mut dyn ForeignContext, _args: RuntimeArgs| {
                 let context = context.downcast_mut::<Context>().unwrap();
 
-                Some(wasmi::RuntimeValue::I64(context.read_context() as i64))
+                Ok(Some(wasmi::RuntimeValue::I64(
+                    context.read_context()? as i64
+                )))
             },
         ),
     );
@@ -85,7 +86,7 @@ pub fn register_context_foreign(
                 let value: i64 = args.nth(0);
                 context.write_context(value as u64);
 
-                None
+                Ok(None)
             },
         ),
     );
@@ -20,7 +20,7 @@ pub fn register_log_foreign(env: &mut HostEnv) {
         |_observer: &Observer, _context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
             let value: u64 = args.nth(0);
             println!("{}", value);
-            None
+            Ok(None)
         },
     );
 
@@ -28,7 +28,7 @@ pub fn register_log_foreign(env: &mut HostEnv) {
         |_observer: &Observer, _context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
             let value: u64 = args.nth(0);
             print!("{}", value as u8 as char);
-            None
+            Ok(None)
         },
     );
 
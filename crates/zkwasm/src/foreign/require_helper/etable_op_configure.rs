@@ -7,7 +7,9 @@ use specs::itable::OpcodeClass;
 use specs::itable::OPCODE_CLASS_SHIFT;
 use specs::mtable::LocationType;
 use specs::step::StepInfo;
+use specs::trap::TrapCode;
 
+use crate::circuits::cell::AllocatedBitCell;
 use crate::circuits::cell::AllocatedU64Cell;
 use crate::circuits::cell::AllocatedUnlimitedCell;
 use crate::circuits::cell::CellExpression;
@@ -31,6 +33,8 @@ pub struct ETableRequireHelperTableConfig<F: FieldExt> {
     cond: AllocatedU64Cell<F>,
     cond_inv: AllocatedUnlimitedCell<F>,
 
+    is_trap: AllocatedBitCell<F>,
+
     memory_table_lookup_read_stack: AllocatedMemoryTableLookupReadCell<F>,
 }
 
@@ -55,9 +59,17 @@ impl<F: FieldExt> EventTableForeignCallConfigBuilder<F> for ETableRequireHelperT
         let cond = allocator.alloc_u64_cell();
         let cond_inv = allocator.alloc_unlimited_cell();
 
+        // A failed requirement traps.
+        let is_trap = allocator.alloc_bit_cell();
+
         constraint_builder.push(
             "require: cond is not zero",
-            Box::new(move |meta| vec![(cond.expr(meta) * cond_inv.expr(meta) - constant_from!(1))]),
+            Box::new(move |meta| {
+                vec![
+                    cond.expr(meta) * cond_inv.expr(meta) - constant_from!(1) + is_trap.expr(meta),
+                    is_trap.expr(meta) * cond.expr(meta),
+                ]
+            }),
         );
 
         let eid = common_config.eid_cell;
@@ -78,6 +90,7 @@ impl<F: FieldExt> EventTableForeignCallConfigBuilder<F> for ETableRequireHelperT
             plugin_index: self.index,
             cond,
             cond_inv,
+            is_trap,
             memory_table_lookup_read_stack,
         })
     }
@@ -104,6 +117,7 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableRequireHelperTableConfig<F
                 self.cond.assign(ctx, cond)?;
                 self.cond_inv
                     .assign(ctx, F::from(cond).invert().unwrap_or(F::zero()))?;
+                self.is_trap.assign_bool(ctx, cond == 0)?;
                 self.memory_table_lookup_read_stack.assign(
                     ctx,
                     entry.memory_rw_entires[0].start_eid,
@@ -122,7 +136,14 @@ impl<F: FieldExt> EventTableOpcodeConfig<F> for ETableRequireHelperTableConfig<F
         }
     }
 
-    fn sp_diff(&self, _meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
-        Some(constant_from!(1))
+    fn sp_diff(&self, meta: &mut VirtualCells<'_, F>) -> Option<Expression<F>> {
+        Some(constant_from!(1) - self.is_trap.expr(meta))
+    }
+
+    fn trap(&self, meta: &mut VirtualCells<'_, F>) -> Option<(Expression<F>, Expression<F>)> {
+        Some((
+            self.is_trap.expr(meta),
+            constant_from!(TrapCode::RequireFailed as u64),
+        ))
     }
 }
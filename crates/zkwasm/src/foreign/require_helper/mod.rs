@@ -6,6 +6,7 @@ use wasmi::tracer::Observer;
 
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
+use crate::runtime::host::ForeignError;
 
 pub mod etable_op_configure;
 
@@ -17,15 +18,13 @@ pub fn register_require_foreign(env: &mut HostEnv) {
         |_observer: &Observer, _context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
             let cond: u32 = args.nth(0);
 
+            // A false assertion in the wasm code, please check the logic of
+            // your image or input.
             if cond == 0 {
-                panic!(
-                    "require is not satisfied, which is a \
-                false assertion in the wasm code. Please check \
-                the logic of your image or input."
-                )
+                return Err(ForeignError::RequireFailed);
             }
 
-            None
+            Ok(None)
         },
     );
 
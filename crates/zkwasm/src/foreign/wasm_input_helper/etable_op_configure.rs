@@ -11,8 +11,6 @@ use specs::mtable::LocationType;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
 
-use super::circuits::WASM_INPUT_FOREIGN_TABLE_KEY;
-
 use crate::circuits::cell::AllocatedBitCell;
 use crate::circuits::cell::AllocatedU64Cell;
 use crate::circuits::cell::AllocatedUnlimitedCell;
@@ -67,7 +65,7 @@ impl<F: FieldExt> EventTableForeignCallConfigBuilder<F>
         common_config: &EventTableCommonConfig<F>,
         allocator: &mut EventTableCellAllocator<F>,
         constraint_builder: &mut ConstraintBuilder<F>,
-        lookup_cells: &mut (impl Iterator<Item = AllocatedUnlimitedCell<F>> + Clone),
+        _lookup_cells: &mut (impl Iterator<Item = AllocatedUnlimitedCell<F>> + Clone),
     ) -> Box<dyn EventTableOpcodeConfig<F>> {
         let eid = common_config.eid_cell;
         let sp = common_config.sp_cell;
@@ -80,8 +78,9 @@ impl<F: FieldExt> EventTableForeignCallConfigBuilder<F>
         let value = allocator.alloc_u64_cell();
 
         let enable_input_table_lookup = allocator.alloc_bit_cell();
-        let public_input_index_for_lookup = lookup_cells.next().unwrap();
-        let value_for_lookup = lookup_cells.next().unwrap();
+        // The lookup of the cells in the input table is configured by the event table.
+        let public_input_index_for_lookup = common_config.public_input_lookup_index_cell;
+        let value_for_lookup = common_config.public_input_lookup_value_cell;
 
         let lookup_read_stack = allocator.alloc_memory_table_lookup_read_cell(
             "wasm input stack read",
@@ -136,17 +135,6 @@ impl<F: FieldExt> EventTableForeignCallConfigBuilder<F>
             }),
         );
 
-        constraint_builder.lookup(
-            WASM_INPUT_FOREIGN_TABLE_KEY,
-            "lookup input table",
-            Box::new(move |meta| {
-                vec![
-                    public_input_index_for_lookup.expr(meta),
-                    value_for_lookup.expr(meta),
-                ]
-            }),
-        );
-
         Box::new(ETableWasmInputHelperTableConfig {
             plugin_index: self.index,
             is_wasm_input_op,
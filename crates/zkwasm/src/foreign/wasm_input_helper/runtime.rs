@@ -1,5 +1,4 @@
 use std::cell::RefCell;
-use std::collections::VecDeque;
 use std::rc::Rc;
 
 use specs::host_function::HostPlugin;
@@ -8,14 +7,16 @@ use wasmi::tracer::Observer;
 
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
+use crate::runtime::host::ForeignError;
 use crate::runtime::host::ForeignStatics;
 use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
 
+use super::source::PrivateInputSource;
 use super::Op;
 
 pub struct Context {
     pub public_inputs: Vec<u64>,
-    pub private_inputs: VecDeque<u64>,
+    pub private_inputs: Box<dyn PrivateInputSource>,
     pub instance: Rc<RefCell<Vec<u64>>>,
     pub output: Rc<RefCell<Vec<u64>>>,
 }
@@ -23,30 +24,30 @@ pub struct Context {
 impl Context {
     pub fn new(
         public_inputs: Vec<u64>,
-        private_inputs: Vec<u64>,
+        private_inputs: Box<dyn PrivateInputSource>,
         instance: Rc<RefCell<Vec<u64>>>,
         output: Rc<RefCell<Vec<u64>>>,
     ) -> Self {
         Context {
             public_inputs,
-            private_inputs: private_inputs.into(),
+            private_inputs,
             instance,
             output,
         }
     }
 
-    pub fn pop_public(&mut self) -> u64 {
+    pub fn pop_public(&mut self) -> Result<u64, ForeignError> {
         if self.public_inputs.is_empty() {
-            panic!("failed to read public input, please checkout your input");
+            return Err(ForeignError::InputExhausted { is_public: true });
         }
-        self.public_inputs.remove(0)
+        Ok(self.public_inputs.remove(0))
     }
 
-    pub fn pop_private(&mut self) -> u64 {
-        if self.private_inputs.is_empty() {
-            panic!("failed to read private input, please checkout your input");
-        }
-        self.private_inputs.pop_front().unwrap()
+    pub fn pop_private(&mut self) -> Result<u64, ForeignError> {
+        self.private_inputs
+            .next_input()
+            .map_err(|e| ForeignError::InputSourceFailed(e.to_string()))?
+            .ok_or(ForeignError::InputExhausted { is_public: false })
     }
 
     fn push_public(&mut self, value: u64) {
@@ -62,18 +63,23 @@ impl Context {
         output.push(value);
     }
 
-    pub fn wasm_input(&mut self, arg: i32) -> u64 {
-        assert!(arg == 0 || arg == 1);
+    pub fn wasm_input(&mut self, arg: i32) -> Result<u64, ForeignError> {
+        if arg != 0 && arg != 1 {
+            return Err(ForeignError::InvalidArgument(format!(
+                "wasm_input expects 0 or 1, but {} is given",
+                arg
+            )));
+        }
 
         let input = if arg == 1 {
-            let value = self.pop_public();
+            let value = self.pop_public()?;
             self.push_public(value);
             value
         } else {
-            self.pop_private()
+            self.pop_private()?
         };
 
-        input
+        Ok(input)
     }
 
     pub fn wasm_output(&mut self, value: u64) {
@@ -87,10 +93,12 @@ impl ForeignContext for Context {
     }
 }
 
+/// Registers `wasm_input` and `wasm_output`, private inputs are either a `Vec<u64>` or a
+/// boxed `PrivateInputSource` read on demand.
 pub fn register_wasm_input_foreign(
     env: &mut HostEnv,
     public_inputs: Vec<u64>,
-    private_inputs: Vec<u64>,
+    private_inputs: impl Into<Box<dyn PrivateInputSource>>,
 ) -> WasmRuntimeIO {
     let public_inputs_and_outputs = Rc::new(RefCell::new(vec![]));
     let outputs = Rc::new(RefCell::new(vec![]));
@@ -99,9 +107,9 @@ pub fn register_wasm_input_foreign(
         |_observer: &Observer, context: &mut dyn ForeignContext, args: wasmi::RuntimeArgs| {
             let context = context.downcast_mut::<Context>().unwrap();
             let arg: i32 = args.nth(0);
-            let input = context.wasm_input(arg);
+            let input = context.wasm_input(arg)?;
 
-            Some(wasmi::RuntimeValue::I64(input as i64))
+            Ok(Some(wasmi::RuntimeValue::I64(input as i64)))
         },
     );
 
@@ -112,7 +120,7 @@ pub fn register_wasm_input_foreign(
             let value: i64 = args.nth(0);
             context.wasm_output(value as u64);
 
-            None
+            Ok(None)
         },
     );
 
@@ -121,7 +129,7 @@ pub fn register_wasm_input_foreign(
         HostPlugin::HostInput,
         Box::new(Context::new(
             public_inputs,
-            private_inputs,
+            private_inputs.into(),
             public_inputs_and_outputs.clone(),
             outputs.clone(),
         )),
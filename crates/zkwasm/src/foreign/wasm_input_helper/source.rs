@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufReader;
+use std::io::ErrorKind;
+use std::io::Read;
+use std::path::PathBuf;
+
+/// Source of the private inputs of `wasm_input(0)`, which are read on demand so the whole
+/// witness doesn't need to be kept in memory.
+pub trait PrivateInputSource {
+    /// Reads the next input, `Ok(None)` if all inputs are consumed.
+    fn next_input(&mut self) -> std::io::Result<Option<u64>>;
+}
+
+impl PrivateInputSource for VecDeque<u64> {
+    fn next_input(&mut self) -> std::io::Result<Option<u64>> {
+        Ok(self.pop_front())
+    }
+}
+
+impl From<Vec<u64>> for Box<dyn PrivateInputSource> {
+    fn from(inputs: Vec<u64>) -> Self {
+        Box::new(VecDeque::from(inputs))
+    }
+}
+
+/// Inputs generated by an iterator.
+pub struct IteratorSource<I: Iterator<Item = u64>>(pub I);
+
+impl<I: Iterator<Item = u64>> PrivateInputSource for IteratorSource<I> {
+    fn next_input(&mut self) -> std::io::Result<Option<u64>> {
+        Ok(self.0.next())
+    }
+}
+
+/// Inputs read from a file in 8-byte big-endian chunks, the same as the `file` input type.
+/// The last chunk is padded with zeros.
+///
+/// The file is opened on the first read, only a buffer of the file is kept in memory.
+pub struct FileSource {
+    path: PathBuf,
+    reader: Option<BufReader<File>>,
+}
+
+impl FileSource {
+    pub fn new(path: PathBuf) -> Self {
+        FileSource { path, reader: None }
+    }
+}
+
+impl PrivateInputSource for FileSource {
+    fn next_input(&mut self) -> std::io::Result<Option<u64>> {
+        if self.reader.is_none() {
+            self.reader = Some(BufReader::new(File::open(&self.path)?));
+        }
+        let reader = self.reader.as_mut().unwrap();
+
+        let mut data = [0u8; 8];
+        let mut len = 0;
+        while len < data.len() {
+            match reader.read(&mut data[len..]) {
+                Ok(0) => break,
+                Ok(n) => len += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if len == 0 {
+            Ok(None)
+        } else {
+            Ok(Some(u64::from_be_bytes(data)))
+        }
+    }
+}
+
+/// Inputs of the sources consumed one after another.
+pub struct ChainedSource {
+    sources: VecDeque<Box<dyn PrivateInputSource>>,
+}
+
+impl ChainedSource {
+    pub fn new(sources: Vec<Box<dyn PrivateInputSource>>) -> Self {
+        ChainedSource {
+            sources: sources.into(),
+        }
+    }
+}
+
+impl PrivateInputSource for ChainedSource {
+    fn next_input(&mut self) -> std::io::Result<Option<u64>> {
+        while let Some(source) = self.sources.front_mut() {
+            if let Some(input) = source.next_input()? {
+                return Ok(Some(input));
+            }
+            self.sources.pop_front();
+        }
+
+        Ok(None)
+    }
+}
@@ -1,6 +1,10 @@
 #[cfg(test)]
 mod tests {
     use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+    use crate::foreign::wasm_input_helper::source::ChainedSource;
+    use crate::foreign::wasm_input_helper::source::FileSource;
+    use crate::foreign::wasm_input_helper::source::IteratorSource;
+    use crate::foreign::wasm_input_helper::source::PrivateInputSource;
     use crate::runtime::host::host_env::HostEnv;
     use crate::test::test_circuit_with_env;
 
@@ -61,4 +65,51 @@ mod tests {
 
         test_circuit_with_env(env, wasm_runtime_io, wasm, "main").unwrap();
     }
+
+    #[test]
+    fn test_foreign_wasm_input_private_source() {
+        let textual_repr = r#"
+                (module
+                    (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+                    (export "main" (func $main))
+                    (func $main (; 1 ;)
+                        (call $wasm_input (i32.const 0))
+                        (call $wasm_input (i32.const 0))
+                        (i64.add)
+                        (drop)
+                    )
+                )
+            "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        // The source is unbounded, only the consumed inputs are generated.
+        let private_inputs: Box<dyn PrivateInputSource> = Box::new(IteratorSource(1..));
+
+        let mut env = HostEnv::new();
+        let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], private_inputs);
+        env.finalize();
+
+        test_circuit_with_env(env, wasm_runtime_io, wasm, "main").unwrap();
+    }
+
+    #[test]
+    fn test_chained_file_source() {
+        let path = std::env::temp_dir().join(format!("private_{}.bin", std::process::id()));
+        std::fs::write(&path, [0, 0, 0, 0, 0, 0, 0, 2, 3]).unwrap();
+
+        let mut source = ChainedSource::new(vec![
+            vec![1].into(),
+            Box::new(FileSource::new(path.clone())),
+        ]);
+
+        let mut inputs = vec![];
+        while let Some(input) = source.next_input().unwrap() {
+            inputs.push(input);
+        }
+        assert_eq!(inputs, vec![1, 2, 3 << 56]);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(FileSource::new(path).next_input().is_err());
+    }
 }
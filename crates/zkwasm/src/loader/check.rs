@@ -0,0 +1,457 @@
+use std::collections::BTreeSet;
+
+use log::warn;
+use parity_wasm::elements::External;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::ValueType;
+use regex::Regex;
+
+use super::err::PreCheckErr;
+use crate::circuits::config::CircuitConfig;
+
+pub(super) fn is_float_type(vtype: &ValueType) -> bool {
+    *vtype == ValueType::F32 || *vtype == ValueType::F64
+}
+
+fn is_unsupported_type(vtype: &ValueType) -> bool {
+    specs::types::ValueType::try_from(*vtype).is_err()
+}
+
+pub(super) fn is_float_instruction(instruction: &Instruction) -> bool {
+    use Instruction::*;
+
+    matches!(
+        instruction,
+        F32Load(..)
+            | F64Load(..)
+            | F32Store(..)
+            | F64Store(..)
+            | F32Const(..)
+            | F64Const(..)
+            | F32Eq
+            | F32Ne
+            | F32Lt
+            | F32Gt
+            | F32Le
+            | F32Ge
+            | F64Eq
+            | F64Ne
+            | F64Lt
+            | F64Gt
+            | F64Le
+            | F64Ge
+            | F32Abs
+            | F32Neg
+            | F32Ceil
+            | F32Floor
+            | F32Trunc
+            | F32Nearest
+            | F32Sqrt
+            | F32Add
+            | F32Sub
+            | F32Mul
+            | F32Div
+            | F32Min
+            | F32Max
+            | F32Copysign
+            | F64Abs
+            | F64Neg
+            | F64Ceil
+            | F64Floor
+            | F64Trunc
+            | F64Nearest
+            | F64Sqrt
+            | F64Add
+            | F64Sub
+            | F64Mul
+            | F64Div
+            | F64Min
+            | F64Max
+            | F64Copysign
+            | I32TruncSF32
+            | I32TruncUF32
+            | I32TruncSF64
+            | I32TruncUF64
+            | I64TruncSF32
+            | I64TruncUF32
+            | I64TruncSF64
+            | I64TruncUF64
+            | F32ConvertSI32
+            | F32ConvertUI32
+            | F32ConvertSI64
+            | F32ConvertUI64
+            | F32DemoteF64
+            | F64ConvertSI32
+            | F64ConvertUI32
+            | F64ConvertSI64
+            | F64ConvertUI64
+            | F64PromoteF32
+            | I32ReinterpretF32
+            | I64ReinterpretF64
+            | F32ReinterpretI32
+            | F64ReinterpretI64
+    )
+}
+
+pub(crate) fn imported_functions(module: &Module) -> u32 {
+    module
+        .import_section()
+        .map_or(0, |section| section.functions() as u32)
+}
+
+fn function_type_ref(module: &Module, fid: u32) -> Option<u32> {
+    if fid < imported_functions(module) {
+        module
+            .import_section()
+            .unwrap()
+            .entries()
+            .iter()
+            .filter_map(|import| match import.external() {
+                External::Function(type_ref) => Some(*type_ref),
+                _ => None,
+            })
+            .nth(fid as usize)
+    } else {
+        module.function_section().and_then(|section| {
+            section
+                .entries()
+                .get((fid - imported_functions(module)) as usize)
+                .map(|func| func.type_ref())
+        })
+    }
+}
+
+pub(crate) fn function_name(module: &Module, fid: u32) -> Option<String> {
+    module
+        .names_section()
+        .and_then(|section| section.functions())
+        .and_then(|functions| functions.names().get(fid))
+        .cloned()
+}
+
+pub(super) fn check_zkmain(module: &Module, entry: &str) -> Vec<PreCheckErr> {
+    let export = module.export_section();
+
+    let fid = match export.and_then(|export| {
+        export
+            .entries()
+            .iter()
+            .find(|export_entry| export_entry.field() == entry)
+    }) {
+        Some(export_entry) => match export_entry.internal() {
+            Internal::Function(fid) => *fid,
+            _ => return vec![PreCheckErr::ZkmainIsNotFunction],
+        },
+        None => return vec![PreCheckErr::ZkmainNotExists],
+    };
+
+    let is_unit_function = function_type_ref(module, fid)
+        .and_then(|type_ref| {
+            module
+                .type_section()
+                .and_then(|section| section.types().get(type_ref as usize))
+        })
+        .map_or(false, |Type::Function(func_type)| {
+            func_type.params().is_empty() && func_type.results().is_empty()
+        });
+
+    if is_unit_function {
+        vec![]
+    } else {
+        vec![PreCheckErr::ZkmainTypeNotMatch]
+    }
+}
+
+pub(crate) fn check_value_types(module: &Module) -> Vec<PreCheckErr> {
+    let mut errors = vec![];
+
+    if let Some(section) = module.global_section() {
+        for (global_index, global) in section.entries().iter().enumerate() {
+            if is_unsupported_type(&global.global_type().content_type()) {
+                errors.push(PreCheckErr::UnsupportedGlobalType {
+                    global_index: global_index as u32,
+                })
+            }
+        }
+    }
+
+    let imported_functions = imported_functions(module);
+    let types = module
+        .type_section()
+        .map_or(vec![], |section| section.types().to_vec());
+
+    let functions = module
+        .function_section()
+        .map_or(vec![], |section| section.entries().to_vec());
+    let bodies = module
+        .code_section()
+        .map_or(vec![], |section| section.bodies().to_vec());
+
+    for (index, (func, body)) in functions.iter().zip(bodies.iter()).enumerate() {
+        let fid = imported_functions + index as u32;
+
+        let signature_is_unsupported =
+            types
+                .get(func.type_ref() as usize)
+                .map_or(false, |Type::Function(func_type)| {
+                    func_type.params().iter().any(is_unsupported_type)
+                        || func_type.results().iter().any(is_unsupported_type)
+                });
+        let locals_are_unsupported = body
+            .locals()
+            .iter()
+            .any(|local| is_unsupported_type(&local.value_type()));
+
+        if signature_is_unsupported || locals_are_unsupported {
+            errors.push(PreCheckErr::UnsupportedValueType {
+                fid,
+                function_name: function_name(module, fid),
+            });
+        }
+    }
+
+    errors
+}
+
+pub(crate) fn check_instructions(module: &Module) -> Vec<PreCheckErr> {
+    let mut errors = vec![];
+    let imported_functions = imported_functions(module);
+
+    if let Some(section) = module.code_section() {
+        for (index, body) in section.bodies().iter().enumerate() {
+            let fid = imported_functions + index as u32;
+
+            for (offset, instruction) in body.code().elements().iter().enumerate() {
+                if is_float_instruction(instruction) {
+                    errors.push(PreCheckErr::UnsupportedInstruction {
+                        fid,
+                        function_name: function_name(module, fid),
+                        offset,
+                        instruction: format!("{}", instruction),
+                    });
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+pub(super) fn check_phantom_functions(
+    module: &Module,
+    phantom_functions: &Vec<String>,
+) -> Vec<PreCheckErr> {
+    let names = module
+        .names_section()
+        .and_then(|section| section.functions())
+        .map_or(vec![], |functions| {
+            functions
+                .names()
+                .iter()
+                .map(|(_, name)| name.clone())
+                .collect()
+        });
+
+    phantom_functions
+        .iter()
+        .filter_map(|pattern| match Regex::new(pattern) {
+            Ok(re) => {
+                if names.iter().any(|name| re.is_match(name)) {
+                    None
+                } else {
+                    Some(PreCheckErr::PhantomFunctionNotExists {
+                        pattern: pattern.clone(),
+                    })
+                }
+            }
+            Err(_) => Some(PreCheckErr::InvalidPhantomFunctionPattern {
+                pattern: pattern.clone(),
+            }),
+        })
+        .collect()
+}
+
+/// Instructions whose effects on the memory or the globals are invisible to the prover if
+/// executed in a phantom function.
+fn has_side_effect(instruction: &Instruction) -> bool {
+    use Instruction::*;
+
+    matches!(
+        instruction,
+        I32Store(..)
+            | I64Store(..)
+            | F32Store(..)
+            | F64Store(..)
+            | I32Store8(..)
+            | I32Store16(..)
+            | I64Store8(..)
+            | I64Store16(..)
+            | I64Store32(..)
+            | SetGlobal(..)
+            | GrowMemory(..)
+    )
+}
+
+/// Functions possibly called by the instruction, an indirect call may reach any function in
+/// the table with the same type.
+fn callees(module: &Module, instruction: &Instruction) -> Vec<u32> {
+    match instruction {
+        Instruction::Call(fid) => vec![*fid],
+        Instruction::CallIndirect(type_ref, _) => {
+            module.elements_section().map_or(vec![], |section| {
+                section
+                    .entries()
+                    .iter()
+                    .flat_map(|segment| segment.members().iter().cloned())
+                    .filter(|fid| function_type_ref(module, *fid) == Some(*type_ref))
+                    .collect::<BTreeSet<_>>()
+                    .into_iter()
+                    .collect()
+            })
+        }
+        _ => vec![],
+    }
+}
+
+/// Phantom functions are executed without being traced, reject the ones which write the
+/// memory or the globals, call a host function, or call a non-phantom function doing so.
+/// With `provable_trap`, an `unreachable` reachable from a phantom function is rejected too,
+/// since a trap of an untraced instruction can't be proven.
+pub(super) fn check_phantom_function_side_effects(
+    module: &Module,
+    phantom_functions: &Vec<String>,
+    provable_trap: bool,
+) -> Vec<PreCheckErr> {
+    let imported_functions = imported_functions(module);
+    let patterns = phantom_functions
+        .iter()
+        .filter_map(|pattern| Regex::new(pattern).ok())
+        .collect::<Vec<_>>();
+
+    let phantoms = module
+        .names_section()
+        .and_then(|section| section.functions())
+        .map_or(BTreeSet::new(), |functions| {
+            functions
+                .names()
+                .iter()
+                .filter(|(fid, name)| {
+                    *fid >= imported_functions && patterns.iter().any(|re| re.is_match(name))
+                })
+                .map(|(fid, _)| fid)
+                .collect()
+        });
+
+    let bodies = module
+        .code_section()
+        .map_or(vec![], |section| section.bodies().to_vec());
+
+    let mut errors = vec![];
+
+    for phantom in phantoms.iter() {
+        let phantom_function =
+            function_name(module, *phantom).unwrap_or_else(|| phantom.to_string());
+
+        let mut visited = BTreeSet::new();
+        let mut pending = vec![*phantom];
+
+        while let Some(fid) = pending.pop() {
+            if !visited.insert(fid) {
+                continue;
+            }
+
+            // Imported functions have no body, the calls of them are rejected by the caller.
+            let body = match fid
+                .checked_sub(imported_functions)
+                .and_then(|index| bodies.get(index as usize))
+            {
+                Some(body) => body,
+                None => continue,
+            };
+
+            for (offset, instruction) in body.code().elements().iter().enumerate() {
+                let callees = callees(module, instruction);
+
+                if has_side_effect(instruction)
+                    || callees.iter().any(|callee| *callee < imported_functions)
+                {
+                    errors.push(PreCheckErr::PhantomFunctionHasSideEffect {
+                        phantom_function: phantom_function.clone(),
+                        fid,
+                        function_name: function_name(module, fid),
+                        offset,
+                        instruction: format!("{}", instruction),
+                    });
+
+                    continue;
+                }
+
+                if provable_trap && *instruction == Instruction::Unreachable {
+                    errors.push(PreCheckErr::PhantomFunctionTraps {
+                        phantom_function: phantom_function.clone(),
+                        fid,
+                        function_name: function_name(module, fid),
+                        offset,
+                    });
+
+                    continue;
+                }
+
+                // Other phantom functions are checked on their own.
+                pending.extend(
+                    callees
+                        .into_iter()
+                        .filter(|callee| !phantoms.contains(callee)),
+                );
+            }
+        }
+    }
+
+    errors
+}
+
+/// Rejects the memory whose initial or maximal pages are not addressable by the circuit. A
+/// memory without maximal pages may still fail to grow beyond the limit during execution.
+pub(super) fn check_memory_pages(
+    module: &Module,
+    circuit_config: &CircuitConfig,
+) -> Vec<PreCheckErr> {
+    let maximal_memory_pages_for_k = circuit_config.maximal_memory_pages();
+
+    let limits = match module
+        .memory_section()
+        .and_then(|section| section.entries().first())
+    {
+        Some(memory) => memory.limits(),
+        None => return vec![],
+    };
+
+    let mut errors = vec![];
+
+    if limits.initial() > maximal_memory_pages_for_k {
+        errors.push(PreCheckErr::InitMemoryPagesExceedLimit {
+            init_memory_pages: limits.initial(),
+            maximal_memory_pages_for_k,
+        });
+    }
+
+    match limits.maximum() {
+        Some(maximal_memory_pages) if maximal_memory_pages > maximal_memory_pages_for_k => {
+            errors.push(PreCheckErr::MaximalMemoryPagesExceedLimit {
+                maximal_memory_pages,
+                maximal_memory_pages_for_k,
+            })
+        }
+        Some(_) => (),
+        None => warn!(
+            "The memory has no maximal pages, growing it beyond {} pages fails in the circuit of K = {}.",
+            maximal_memory_pages_for_k,
+            circuit_config.k()
+        ),
+    }
+
+    errors
+}
@@ -1,19 +1,161 @@
 use std::fmt::Display;
 
+use crate::runtime::host::ForeignError;
+
 #[derive(Debug)]
 pub enum PreCheckErr {
     ZkmainNotExists,
     ZkmainIsNotFunction,
-    // ZkmainTypeNotMatch,
+    ZkmainTypeNotMatch,
+    UnsupportedValueType {
+        fid: u32,
+        function_name: Option<String>,
+    },
+    UnsupportedGlobalType {
+        global_index: u32,
+    },
+    UnsupportedInstruction {
+        fid: u32,
+        function_name: Option<String>,
+        /// Index of the instruction within the function body
+        offset: usize,
+        instruction: String,
+    },
+    PhantomFunctionNotExists {
+        pattern: String,
+    },
+    InvalidPhantomFunctionPattern {
+        pattern: String,
+    },
+    /// The instruction of `fid`, which is reachable from the phantom function, writes the
+    /// memory or the globals, or calls a host function.
+    PhantomFunctionHasSideEffect {
+        phantom_function: String,
+        fid: u32,
+        function_name: Option<String>,
+        /// Index of the instruction within the function body
+        offset: usize,
+        instruction: String,
+    },
+    /// The `unreachable` instruction of `fid` is reachable from the phantom function, which
+    /// is not traced, so that the trap is not provable.
+    PhantomFunctionTraps {
+        phantom_function: String,
+        fid: u32,
+        function_name: Option<String>,
+        /// Index of the instruction within the function body
+        offset: usize,
+    },
+    /// The opcode is unknown to the parser, e.g. `0xfd` prefixing a SIMD instruction.
+    UnsupportedOpcode {
+        opcode: u8,
+    },
+    InitMemoryPagesExceedLimit {
+        init_memory_pages: u32,
+        maximal_memory_pages_for_k: u32,
+    },
+    MaximalMemoryPagesExceedLimit {
+        maximal_memory_pages: u32,
+        maximal_memory_pages_for_k: u32,
+    },
+}
+
+/// Location of an instruction in the image.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GuestLocation {
+    pub fid: u32,
+    pub iid: u32,
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub struct GuestFrame {
+    pub location: GuestLocation,
+    pub function_name: Option<String>,
+    /// The instruction at `location`
+    pub instruction: Option<String>,
+}
+
+/// Guest call stack from the trapped instruction to the entry function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Backtrace(pub Vec<GuestFrame>);
+
+impl Display for Backtrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (depth, frame) in self.0.iter().enumerate() {
+            write!(
+                f,
+                "  #{} {} (fid: {}, iid: {})",
+                depth,
+                frame.function_name.as_deref().unwrap_or("<unknown>"),
+                frame.location.fid,
+                frame.location.iid,
+            )?;
+
+            if let Some(instruction) = &frame.instruction {
+                write!(f, " at {}", instruction)?;
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The backtrace is `None` in dry-run mode since no event is traced.
 #[derive(Debug)]
-pub enum RuntimeErr {}
+pub enum RuntimeErr {
+    /// A foreign function traps the execution.
+    ForeignTrap {
+        function: String,
+        error: ForeignError,
+        backtrace: Option<Backtrace>,
+    },
+    /// The guest traps, e.g. `unreachable`, out of bounds memory access or division by zero.
+    Trap {
+        trap: String,
+        backtrace: Option<Backtrace>,
+    },
+}
+
+impl RuntimeErr {
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        match self {
+            RuntimeErr::ForeignTrap { backtrace, .. } | RuntimeErr::Trap { backtrace, .. } => {
+                backtrace.as_ref()
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TraceErr {
+    InvalidMagic,
+    UnsupportedVersion {
+        version: u32,
+        expected: u32,
+    },
+    /// The compilation tables do not match the hash recorded in the trace.
+    CorruptedCompilationTable,
+    KMismatch {
+        trace_k: u32,
+        loader_k: u32,
+    },
+    /// The trace is not generated from the image of the loader.
+    ImageMismatch,
+}
+
+#[derive(Debug)]
+pub enum ConfigErr {
+    KOutOfRange { k: u32, min_k: u32, max_k: u32 },
+}
 
 #[derive(Debug)]
 pub enum Error {
-    PreCheck(PreCheckErr),
-    // Runtime(RuntimeErr),
+    Config(ConfigErr),
+    PreCheck(Vec<PreCheckErr>),
+    Trace(TraceErr),
+    Runtime(RuntimeErr),
 }
 
 impl Display for Error {
@@ -8,12 +8,17 @@ use halo2_proofs::plonk::VerifyingKey;
 use halo2_proofs::poly::commitment::Params;
 use halo2_proofs::poly::commitment::ParamsVerifier;
 use log::warn;
+use parity_wasm::elements::Module;
+use parity_wasm::SerializationError;
+use std::io::Read;
+use std::io::Write;
 use std::marker::PhantomData;
 
 use halo2aggregator_s::circuits::utils::load_or_create_proof;
 use halo2aggregator_s::circuits::utils::TranscriptHash;
 use halo2aggregator_s::transcript::poseidon::PoseidonRead;
 
+use specs::CompilationTable;
 use specs::ExecutionTable;
 use specs::Tables;
 use wasmi::tracer::Tracer;
@@ -23,22 +28,43 @@ use wasmi::RuntimeValue;
 
 use crate::checksum::CompilationTableWithParams;
 use crate::checksum::ImageCheckSum;
-use crate::circuits::config::init_zkwasm_runtime;
-use crate::circuits::config::set_zkwasm_k;
+use crate::circuits::config::CircuitConfig;
+use crate::circuits::config::MAX_K;
+use crate::circuits::config::MIN_K;
+use crate::circuits::estimate::HostRounds;
+use crate::circuits::estimate::RowEstimate;
+use crate::circuits::utils::bn_to_field;
 use crate::circuits::ZkWasmCircuit;
 use crate::circuits::ZkWasmCircuitBuilder;
+use crate::loader::check::check_instructions;
+use crate::loader::check::check_memory_pages;
+use crate::loader::check::check_phantom_function_side_effects;
+use crate::loader::check::check_phantom_functions;
+use crate::loader::check::check_value_types;
+use crate::loader::check::check_zkmain;
 use crate::loader::err::Error;
 use crate::loader::err::PreCheckErr;
+use crate::loader::err::TraceErr;
+use crate::loader::softfloat::lower_float;
+use crate::loader::trace::compilation_table_hash;
+use crate::loader::trace::read_trace;
+use crate::loader::trace::write_trace;
 use crate::profile::Profiler;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::HostEnvBuilder;
 use crate::runtime::wasmi_interpreter::Execution;
+use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
 use crate::runtime::CompiledImage;
 use crate::runtime::ExecutionResult;
 use crate::runtime::WasmInterpreter;
 use anyhow::anyhow;
 
-mod err;
+pub(crate) mod check;
+pub mod err;
+
+pub mod session;
+mod softfloat;
+pub mod trace;
 
 const ENTRY: &str = "zkmain";
 
@@ -47,7 +73,7 @@ pub struct ExecutionReturn {
 }
 
 pub struct ZkWasmLoader<E: MultiMillerLoop, Arg, EnvBuilder: HostEnvBuilder<Arg = Arg>> {
-    k: u32,
+    circuit_config: CircuitConfig,
     module: wasmi::Module,
     phantom_functions: Vec<String>,
     _mark: PhantomData<(Arg, EnvBuilder, E)>,
@@ -55,33 +81,27 @@ pub struct ZkWasmLoader<E: MultiMillerLoop, Arg, EnvBuilder: HostEnvBuilder<Arg
 
 impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E, T, EnvBuilder> {
     fn precheck(&self) -> Result<()> {
-        fn check_zkmain_exists(module: &wasmi::Module) -> Result<()> {
-            use parity_wasm::elements::Internal;
-
-            let export = module.module().export_section().unwrap();
-
-            if let Some(entry) = export
-                .entries()
-                .iter()
-                .find(|entry| entry.field() == "zkmain")
-            {
-                match entry.internal() {
-                    Internal::Function(_fid) => Ok(()),
-                    _ => Err(anyhow!(Error::PreCheck(PreCheckErr::ZkmainIsNotFunction))),
-                }
-            } else {
-                Err(anyhow!(Error::PreCheck(PreCheckErr::ZkmainNotExists)))
-            }
+        let module = self.module.module();
+
+        let errors = vec![
+            check_zkmain(module, ENTRY),
+            check_value_types(module),
+            check_instructions(module),
+            check_phantom_functions(module, &self.phantom_functions),
+            check_phantom_function_side_effects(
+                module,
+                &self.phantom_functions,
+                self.circuit_config.provable_trap(),
+            ),
+            check_memory_pages(module, &self.circuit_config),
+        ]
+        .concat();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!(Error::PreCheck(errors)))
         }
-
-        check_zkmain_exists(&self.module)?;
-        // TODO: check the signature of zkmain function.
-        // TODO: check the relation between maximal pages and K.
-        // TODO: check the instructions of phantom functions.
-        // TODO: check phantom functions exists.
-        // TODO: check if instructions are supported.
-
-        Ok(())
     }
 
     pub fn compile(
@@ -98,6 +118,7 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
             ENTRY,
             dryrun,
             &self.phantom_functions,
+            &self.circuit_config,
         )
     }
 
@@ -105,11 +126,13 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         &self,
         envconfig: EnvBuilder::HostConfig,
     ) -> Result<ZkWasmCircuit<E::Scalar>> {
-        let (env, wasm_runtime_io) = EnvBuilder::create_env_without_value(envconfig);
+        let (env, wasm_runtime_io) =
+            EnvBuilder::create_env_without_value(&self.circuit_config, envconfig);
 
         let compiled_module = self.compile(&env, true)?;
 
         let builder = ZkWasmCircuitBuilder {
+            circuit_config: self.circuit_config,
             tables: Tables {
                 compilation_tables: compiled_module.tables,
                 execution_tables: ExecutionTable::default(),
@@ -126,28 +149,54 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
     /// - image: wasm binary
     /// - phantom_functions: regular expressions of phantom function
     pub fn new(k: u32, image: Vec<u8>, phantom_functions: Vec<String>) -> Result<Self> {
-        set_zkwasm_k(k);
-
-        let mut module = wasmi::Module::from_buffer(&image)?;
-        if let Ok(parity_module) = module.module().clone().parse_names() {
-            module.module = parity_module;
-        } else {
-            warn!("Failed to parse name section of the wasm binary.");
-        }
+        let module = match parity_wasm::deserialize_buffer::<Module>(&image) {
+            Ok(module) => module,
+            Err(SerializationError::UnknownOpcode(opcode)) => {
+                return Err(anyhow!(Error::PreCheck(vec![
+                    PreCheckErr::UnsupportedOpcode { opcode }
+                ])))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let module = match module.parse_names() {
+            Ok(module) => module,
+            Err((_, module)) => {
+                warn!("Failed to parse name section of the wasm binary.");
+                module
+            }
+        };
+        let module = wasmi::Module::from_parity_module(lower_float(module)?)?;
 
         let loader = Self {
-            k,
+            circuit_config: CircuitConfig::new(k)?,
             module,
             phantom_functions,
             _mark: PhantomData,
         };
 
         loader.precheck()?;
-        loader.init_env()?;
 
         Ok(loader)
     }
 
+    pub fn circuit_config(&self) -> &CircuitConfig {
+        &self.circuit_config
+    }
+
+    /// Terminate the execution with a provable outcome instead of an error if the guest traps
+    /// with `unreachable`, a failed `require`, an out-of-bounds memory access whose effective
+    /// address doesn't overflow, a division by zero or a signed division overflow. The outcome
+    /// is published as the last instance, tagged so that it is never mistaken
+    /// for a guest output. The flag is part of the circuit, the verifying key created without it
+    /// rejects trapping executions. Traps are only provable when the execution is traced, i.e.
+    /// not in dry-run mode. The image is prechecked again since phantom functions must not
+    /// trap if traps are provable.
+    pub fn set_provable_trap(&mut self, provable_trap: bool) -> Result<()> {
+        self.circuit_config = self.circuit_config.with_provable_trap(provable_trap);
+
+        self.precheck()
+    }
+
     pub fn create_vkey(
         &self,
         params: &Params<E::G1Affine>,
@@ -163,7 +212,7 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         params: &Params<E::G1Affine>,
         envconfig: EnvBuilder::HostConfig,
     ) -> Result<Vec<E::G1Affine>> {
-        let (env, _) = EnvBuilder::create_env_without_value(envconfig);
+        let (env, _) = EnvBuilder::create_env_without_value(&self.circuit_config, envconfig);
         let compiled = self.compile(&env, true)?;
 
         let table_with_params = CompilationTableWithParams {
@@ -183,9 +232,48 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         dryrun: bool,
         write_to_file: bool,
     ) -> Result<ExecutionResult<RuntimeValue>> {
-        let (env, wasm_runtime_io) = EnvBuilder::create_env(arg, config);
+        let (env, wasm_runtime_io) = EnvBuilder::create_env(&self.circuit_config, arg, config);
         let compiled_module = self.compile(&env, dryrun)?;
-        let result = compiled_module.run(env, dryrun, wasm_runtime_io)?;
+        self.execute(env, wasm_runtime_io, compiled_module, dryrun, write_to_file)
+    }
+
+    /// Executes the image with tracing like `run`, the compilation tables of a previous
+    /// compilation of the image are reused instead of being rebuilt.
+    pub fn run_with_compilation_tables(
+        &self,
+        arg: T,
+        config: EnvBuilder::HostConfig,
+        compilation_tables: &CompilationTable,
+    ) -> Result<ExecutionResult<RuntimeValue>> {
+        let (env, wasm_runtime_io) = EnvBuilder::create_env(&self.circuit_config, arg, config);
+        let imports = ImportsBuilder::new().with_resolver("env", &env);
+        let compiled_module = WasmInterpreter::compile_with_tables(
+            &self.module,
+            &imports,
+            &env.function_description_table(),
+            ENTRY,
+            false,
+            &self.phantom_functions,
+            compilation_tables,
+        )?;
+
+        self.execute(env, wasm_runtime_io, compiled_module, false, false)
+    }
+
+    fn execute(
+        &self,
+        env: HostEnv,
+        wasm_runtime_io: WasmRuntimeIO,
+        compiled_module: CompiledImage<NotStartedModuleRef<'_>, Tracer>,
+        dryrun: bool,
+        write_to_file: bool,
+    ) -> Result<ExecutionResult<RuntimeValue>> {
+        let result = compiled_module.run(
+            env,
+            dryrun,
+            self.circuit_config.provable_trap(),
+            wasm_runtime_io,
+        )?;
         if !dryrun {
             result.tables.profile_tables();
 
@@ -197,19 +285,105 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         Ok(result)
     }
 
+    /// Write the execution trace in the binary trace format, the trace can be loaded by
+    /// `load_trace` to create the circuit without executing the image again.
+    pub fn write_trace<W: Write>(
+        &self,
+        execution_result: &ExecutionResult<RuntimeValue>,
+        writer: W,
+    ) -> Result<()> {
+        write_trace(self.circuit_config.k(), execution_result, writer)
+    }
+
+    /// Load an execution trace written by `write_trace`. The trace must be generated
+    /// from the same image with the same host environment and circuit size.
+    pub fn load_trace<R: Read>(
+        &self,
+        envconfig: EnvBuilder::HostConfig,
+        reader: R,
+    ) -> Result<ExecutionResult<RuntimeValue>> {
+        let (header, execution_result) = read_trace(reader)?;
+
+        if header.k != self.circuit_config.k() {
+            return Err(anyhow!(Error::Trace(TraceErr::KMismatch {
+                trace_k: header.k,
+                loader_k: self.circuit_config.k(),
+            })));
+        }
+
+        let (env, _) = EnvBuilder::create_env_without_value(&self.circuit_config, envconfig);
+        let compiled = self.compile(&env, true)?;
+
+        if compilation_table_hash(&compiled.tables) != header.compilation_table_hash {
+            return Err(anyhow!(Error::Trace(TraceErr::ImageMismatch)));
+        }
+
+        Ok(execution_result)
+    }
+
+    /// Estimate the rows required by each table and the rounds used by each host circuit to
+    /// find the minimal K before proving. The image is executed with tracing, since the sizes
+    /// of mtable, jtable and bit table depend on the events rather than the number of executed
+    /// instructions.
+    pub fn estimate(&self, arg: T, config: EnvBuilder::HostConfig) -> Result<RowEstimate>
+    where
+        EnvBuilder::HostConfig: Clone,
+    {
+        // The round budgets of host circuits are taken from the env of each candidate K.
+        let max_rounds = (MIN_K..=MAX_K)
+            .map(|k| {
+                let (env, _) =
+                    EnvBuilder::create_env_without_value(&CircuitConfig::new(k)?, config.clone());
+
+                Ok((k, env.external_env.get_statics()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let execution_result = self.run(arg, config, false, false)?;
+
+        let mut host_rounds = execution_result
+            .host_statics
+            .iter()
+            .map(|(plugin, statics)| HostRounds {
+                plugin: plugin.clone(),
+                used_rounds: statics.used_round,
+                max_rounds: max_rounds
+                    .iter()
+                    .filter_map(|(k, statics)| {
+                        statics.get(plugin).map(|statics| (*k, statics.max_round))
+                    })
+                    .collect(),
+            })
+            .collect::<Vec<_>>();
+        host_rounds.sort_by(|a, b| a.plugin.cmp(&b.plugin));
+
+        Ok(RowEstimate::new::<E::Scalar>(
+            &self.circuit_config,
+            &execution_result.tables,
+            execution_result.public_inputs_and_outputs.len()
+                + execution_result.trap.is_some() as usize,
+            host_rounds,
+        ))
+    }
+
     pub fn circuit_with_witness(
         &self,
         execution_result: ExecutionResult<RuntimeValue>,
     ) -> Result<(ZkWasmCircuit<E::Scalar>, Vec<E::Scalar>)> {
         //let execution_result = self.run(arg, config, false, true)?;
-        let instance: Vec<E::Scalar> = execution_result
+        let mut instance: Vec<E::Scalar> = execution_result
             .public_inputs_and_outputs
             .clone()
             .iter()
             .map(|v| (*v).into())
             .collect();
+        // The trap outcome follows the public inputs and outputs.
+        if let Some(outcome) = execution_result.trap {
+            instance.push(bn_to_field(&outcome.encode_instance()));
+        }
 
         let builder = ZkWasmCircuitBuilder {
+            circuit_config: self.circuit_config,
             tables: execution_result.tables,
             public_inputs_and_outputs: execution_result.public_inputs_and_outputs.clone(),
         };
@@ -222,7 +396,7 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         circuit: &ZkWasmCircuit<E::Scalar>,
         instances: &Vec<E::Scalar>,
     ) -> Result<()> {
-        let prover = MockProver::run(self.k, circuit, vec![instances.clone()])?;
+        let prover = MockProver::run(self.circuit_config.k(), circuit, vec![instances.clone()])?;
         assert_eq!(prover.verify(), Ok(()));
 
         Ok(())
@@ -246,12 +420,6 @@ impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmLoader<E,
         ))
     }
 
-    pub fn init_env(&self) -> Result<()> {
-        init_zkwasm_runtime(self.k);
-
-        Ok(())
-    }
-
     pub fn verify_proof(
         &self,
         params: &Params<E::G1Affine>,
@@ -342,7 +510,7 @@ mod tests {
                 }
             }
 
-            let params = prepare_param(self.k);
+            let params = prepare_param(self.circuit_config.k());
             let vkey = self.create_vkey(&params, ()).unwrap();
 
             let proof = self
@@ -0,0 +1,125 @@
+use anyhow::Result;
+use halo2_proofs::arithmetic::MultiMillerLoop;
+use halo2_proofs::plonk::create_proof;
+use halo2_proofs::plonk::keygen_pk;
+use halo2_proofs::plonk::keygen_vk;
+use halo2_proofs::plonk::ProvingKey;
+use halo2_proofs::plonk::VerifyingKey;
+use halo2_proofs::poly::commitment::Params;
+use halo2aggregator_s::transcript::poseidon::PoseidonWrite;
+use rand::rngs::OsRng;
+use specs::CompilationTable;
+
+use crate::checksum::CompilationTableWithParams;
+use crate::checksum::ImageCheckSum;
+use crate::runtime::host::HostEnvBuilder;
+
+use super::ZkWasmLoader;
+
+/// A proof of an execution and the instances it is verified with.
+pub struct SessionProof<E: MultiMillerLoop> {
+    pub instances: Vec<E::Scalar>,
+    pub proof: Vec<u8>,
+    pub outputs: Vec<u64>,
+}
+
+/// Proves executions of an image repeatedly.
+///
+/// The image is compiled and the proving key is generated once when the session is created,
+/// each `prove` only executes the image and creates the proof.
+pub struct ZkWasmSession<E: MultiMillerLoop, Arg, EnvBuilder: HostEnvBuilder<Arg = Arg>> {
+    loader: ZkWasmLoader<E, Arg, EnvBuilder>,
+    envconfig: EnvBuilder::HostConfig,
+    params: Params<E::G1Affine>,
+    compilation_tables: CompilationTable,
+    pkey: ProvingKey<E::G1Affine>,
+}
+
+impl<E: MultiMillerLoop, T, EnvBuilder: HostEnvBuilder<Arg = T>> ZkWasmSession<E, T, EnvBuilder>
+where
+    EnvBuilder::HostConfig: Clone,
+{
+    pub fn new(
+        loader: ZkWasmLoader<E, T, EnvBuilder>,
+        params: Params<E::G1Affine>,
+        envconfig: EnvBuilder::HostConfig,
+    ) -> Result<Self> {
+        let circuit = loader.circuit_without_witness(envconfig.clone())?;
+        let compilation_tables = circuit.tables.compilation_tables.clone();
+
+        let vkey = keygen_vk(&params, &circuit)?;
+        let pkey = keygen_pk(&params, vkey, &circuit)?;
+
+        Ok(ZkWasmSession {
+            loader,
+            envconfig,
+            params,
+            compilation_tables,
+            pkey,
+        })
+    }
+
+    pub fn loader(&self) -> &ZkWasmLoader<E, T, EnvBuilder> {
+        &self.loader
+    }
+
+    pub fn params(&self) -> &Params<E::G1Affine> {
+        &self.params
+    }
+
+    pub fn vkey(&self) -> &VerifyingKey<E::G1Affine> {
+        self.pkey.get_vk()
+    }
+
+    pub fn compilation_tables(&self) -> &CompilationTable {
+        &self.compilation_tables
+    }
+
+    pub fn checksum(&self) -> Vec<E::G1Affine> {
+        CompilationTableWithParams {
+            table: &self.compilation_tables,
+            params: &self.params,
+        }
+        .checksum()
+    }
+
+    /// Executes the image with `arg` and proves the execution with the cached compilation
+    /// tables and proving key.
+    pub fn prove(&self, arg: T) -> Result<SessionProof<E>> {
+        let execution_result = self.loader.run_with_compilation_tables(
+            arg,
+            self.envconfig.clone(),
+            &self.compilation_tables,
+        )?;
+        let outputs = execution_result.outputs.clone();
+
+        let (circuit, instances) = self.loader.circuit_with_witness(execution_result)?;
+
+        let mut transcript = PoseidonWrite::init(vec![]);
+        create_proof(
+            &self.params,
+            &self.pkey,
+            &[circuit],
+            &[&[&instances]],
+            OsRng,
+            &mut transcript,
+        )?;
+
+        Ok(SessionProof {
+            instances,
+            proof: transcript.finalize(),
+            outputs,
+        })
+    }
+
+    pub fn verify(&self, proof: &SessionProof<E>) -> Result<()> {
+        self.loader.verify_proof(
+            &self.params,
+            self.vkey().clone(),
+            proof.instances.clone(),
+            proof.proof.clone(),
+            #[cfg(feature = "uniform-circuit")]
+            self.envconfig.clone(),
+        )
+    }
+}
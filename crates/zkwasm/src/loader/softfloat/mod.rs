@@ -0,0 +1,313 @@
+//! Lower f32/f64 into integer instructions.
+//!
+//! The circuit only supports integer instructions, so a module which uses float is
+//! rewritten before compilation:
+//! - f32 and f64 values are represented by their bit patterns in i32 and i64;
+//! - float loads, stores and constants become the integer ones with the same bits,
+//!   reinterpret instructions are dropped;
+//! - any other float instruction becomes a call of the bundled soft-float library,
+//!   which is appended to the module.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use parity_wasm::elements::BlockType;
+use parity_wasm::elements::CodeSection;
+use parity_wasm::elements::Func;
+use parity_wasm::elements::FunctionSection;
+use parity_wasm::elements::GlobalEntry;
+use parity_wasm::elements::GlobalType;
+use parity_wasm::elements::InitExpr;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Local;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Section;
+use parity_wasm::elements::Type;
+use parity_wasm::elements::TypeSection;
+use parity_wasm::elements::ValueType;
+
+use super::check::imported_functions;
+use super::check::is_float_instruction;
+use super::check::is_float_type;
+
+const SOFTFLOAT_LIBRARY: &str = include_str!("softfloat.wat");
+
+fn lower_type(vtype: ValueType) -> ValueType {
+    match vtype {
+        ValueType::F32 => ValueType::I32,
+        ValueType::F64 => ValueType::I64,
+        _ => vtype,
+    }
+}
+
+fn lower_block_type(block_type: BlockType) -> BlockType {
+    match block_type {
+        BlockType::Value(vtype) => BlockType::Value(lower_type(vtype)),
+        _ => block_type,
+    }
+}
+
+/// The name of the library function which implements `instruction`.
+fn library_function(instruction: &Instruction) -> Option<&'static str> {
+    use Instruction::*;
+
+    let name = match instruction {
+        F32Eq => "f32_eq",
+        F32Ne => "f32_ne",
+        F32Lt => "f32_lt",
+        F32Gt => "f32_gt",
+        F32Le => "f32_le",
+        F32Ge => "f32_ge",
+        F64Eq => "f64_eq",
+        F64Ne => "f64_ne",
+        F64Lt => "f64_lt",
+        F64Gt => "f64_gt",
+        F64Le => "f64_le",
+        F64Ge => "f64_ge",
+        F32Abs => "f32_abs",
+        F32Neg => "f32_neg",
+        F32Ceil => "f32_ceil",
+        F32Floor => "f32_floor",
+        F32Trunc => "f32_trunc",
+        F32Nearest => "f32_nearest",
+        F32Sqrt => "f32_sqrt",
+        F32Add => "f32_add",
+        F32Sub => "f32_sub",
+        F32Mul => "f32_mul",
+        F32Div => "f32_div",
+        F32Min => "f32_min",
+        F32Max => "f32_max",
+        F32Copysign => "f32_copysign",
+        F64Abs => "f64_abs",
+        F64Neg => "f64_neg",
+        F64Ceil => "f64_ceil",
+        F64Floor => "f64_floor",
+        F64Trunc => "f64_trunc",
+        F64Nearest => "f64_nearest",
+        F64Sqrt => "f64_sqrt",
+        F64Add => "f64_add",
+        F64Sub => "f64_sub",
+        F64Mul => "f64_mul",
+        F64Div => "f64_div",
+        F64Min => "f64_min",
+        F64Max => "f64_max",
+        F64Copysign => "f64_copysign",
+        I32TruncSF32 => "i32_trunc_f32_s",
+        I32TruncUF32 => "i32_trunc_f32_u",
+        I32TruncSF64 => "i32_trunc_f64_s",
+        I32TruncUF64 => "i32_trunc_f64_u",
+        I64TruncSF32 => "i64_trunc_f32_s",
+        I64TruncUF32 => "i64_trunc_f32_u",
+        I64TruncSF64 => "i64_trunc_f64_s",
+        I64TruncUF64 => "i64_trunc_f64_u",
+        F32ConvertSI32 => "f32_convert_i32_s",
+        F32ConvertUI32 => "f32_convert_i32_u",
+        F32ConvertSI64 => "f32_convert_i64_s",
+        F32ConvertUI64 => "f32_convert_i64_u",
+        F32DemoteF64 => "f32_demote_f64",
+        F64ConvertSI32 => "f64_convert_i32_s",
+        F64ConvertUI32 => "f64_convert_i32_u",
+        F64ConvertSI64 => "f64_convert_i64_s",
+        F64ConvertUI64 => "f64_convert_i64_u",
+        F64PromoteF32 => "f64_promote_f32",
+        _ => return None,
+    };
+
+    Some(name)
+}
+
+/// Lower a single instruction, `None` if the instruction should be dropped.
+fn lower_instruction(
+    instruction: &Instruction,
+    library: &HashMap<String, u32>,
+) -> Option<Instruction> {
+    use Instruction::*;
+
+    let lowered = match instruction {
+        F32Load(align, offset) => I32Load(*align, *offset),
+        F64Load(align, offset) => I64Load(*align, *offset),
+        F32Store(align, offset) => I32Store(*align, *offset),
+        F64Store(align, offset) => I64Store(*align, *offset),
+        F32Const(bits) => I32Const(*bits as i32),
+        F64Const(bits) => I64Const(*bits as i64),
+        I32ReinterpretF32 | I64ReinterpretF64 | F32ReinterpretI32 | F64ReinterpretI64 => {
+            return None
+        }
+        Block(block_type) => Block(lower_block_type(*block_type)),
+        Loop(block_type) => Loop(lower_block_type(*block_type)),
+        If(block_type) => If(lower_block_type(*block_type)),
+        _ => match library_function(instruction) {
+            Some(name) => Call(*library.get(name).unwrap()),
+            None => instruction.clone(),
+        },
+    };
+
+    Some(lowered)
+}
+
+fn lower_instructions(
+    instructions: &[Instruction],
+    library: &HashMap<String, u32>,
+) -> Vec<Instruction> {
+    instructions
+        .iter()
+        .filter_map(|instruction| lower_instruction(instruction, library))
+        .collect()
+}
+
+fn uses_float(module: &Module) -> bool {
+    let types = module.type_section().map_or(false, |section| {
+        section.types().iter().any(|Type::Function(func_type)| {
+            func_type.params().iter().any(is_float_type)
+                || func_type.results().iter().any(is_float_type)
+        })
+    });
+
+    let globals = module.global_section().map_or(false, |section| {
+        section
+            .entries()
+            .iter()
+            .any(|global| is_float_type(&global.global_type().content_type()))
+    });
+
+    let code = module.code_section().map_or(false, |section| {
+        section.bodies().iter().any(|body| {
+            body.locals()
+                .iter()
+                .any(|local| is_float_type(&local.value_type()))
+                || body.code().elements().iter().any(is_float_instruction)
+        })
+    });
+
+    types || globals || code
+}
+
+fn load_library() -> Result<Module> {
+    let binary = wabt::Wat2Wasm::new()
+        .write_debug_names(true)
+        .convert(SOFTFLOAT_LIBRARY)?;
+    let library = parity_wasm::deserialize_buffer::<Module>(binary.as_ref())?;
+
+    Ok(library.parse_names().unwrap_or_else(|(_, library)| library))
+}
+
+/// Append all functions of `library` to `module`, returns the function index of each
+/// library export in `module`.
+fn merge_library(module: &mut Module, library: Module) -> Result<HashMap<String, u32>> {
+    if module.type_section().is_none() {
+        module.insert_section(Section::Type(TypeSection::default()))?;
+    }
+    if module.function_section().is_none() {
+        module.insert_section(Section::Function(FunctionSection::default()))?;
+    }
+    if module.code_section().is_none() {
+        module.insert_section(Section::Code(CodeSection::default()))?;
+    }
+
+    let type_offset = module.type_section().unwrap().types().len() as u32;
+    let function_offset =
+        imported_functions(module) + module.function_section().unwrap().entries().len() as u32;
+
+    module
+        .type_section_mut()
+        .unwrap()
+        .types_mut()
+        .extend(library.type_section().unwrap().types().iter().cloned());
+
+    module.function_section_mut().unwrap().entries_mut().extend(
+        library
+            .function_section()
+            .unwrap()
+            .entries()
+            .iter()
+            .map(|func| Func::new(func.type_ref() + type_offset)),
+    );
+
+    module.code_section_mut().unwrap().bodies_mut().extend(
+        library.code_section().unwrap().bodies().iter().map(|body| {
+            let mut body = body.clone();
+
+            for instruction in body.code_mut().elements_mut().iter_mut() {
+                if let Instruction::Call(fid) = instruction {
+                    *fid += function_offset;
+                }
+            }
+
+            body
+        }),
+    );
+
+    if let Some(function_names) = module
+        .names_section_mut()
+        .and_then(|section| section.functions_mut())
+    {
+        if let Some(library_names) = library
+            .names_section()
+            .and_then(|section| section.functions())
+        {
+            for (fid, name) in library_names.names().iter() {
+                function_names
+                    .names_mut()
+                    .insert(fid + function_offset, format!("softfloat::{}", name));
+            }
+        }
+    }
+
+    Ok(library
+        .export_section()
+        .unwrap()
+        .entries()
+        .iter()
+        .filter_map(|export| match export.internal() {
+            Internal::Function(fid) => Some((export.field().to_owned(), fid + function_offset)),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Rewrite all float types and instructions of `module` into integer ones, the module
+/// is returned unchanged if it does not use float.
+pub(super) fn lower_float(mut module: Module) -> Result<Module> {
+    if !uses_float(&module) {
+        return Ok(module);
+    }
+
+    let library = merge_library(&mut module, load_library()?)?;
+
+    if let Some(section) = module.type_section_mut() {
+        for Type::Function(func_type) in section.types_mut().iter_mut() {
+            for vtype in func_type.params_mut().iter_mut() {
+                *vtype = lower_type(*vtype);
+            }
+            for vtype in func_type.results_mut().iter_mut() {
+                *vtype = lower_type(*vtype);
+            }
+        }
+    }
+
+    if let Some(section) = module.global_section_mut() {
+        for global in section.entries_mut().iter_mut() {
+            *global = GlobalEntry::new(
+                GlobalType::new(
+                    lower_type(global.global_type().content_type()),
+                    global.global_type().is_mutable(),
+                ),
+                InitExpr::new(lower_instructions(global.init_expr().code(), &library)),
+            );
+        }
+    }
+
+    if let Some(section) = module.code_section_mut() {
+        for body in section.bodies_mut().iter_mut() {
+            for local in body.locals_mut().iter_mut() {
+                *local = Local::new(local.count(), lower_type(local.value_type()));
+            }
+
+            let instructions = lower_instructions(body.code().elements(), &library);
+            *body.code_mut().elements_mut() = instructions;
+        }
+    }
+
+    Ok(module)
+}
@@ -0,0 +1,809 @@
+;; Soft-float library for zkWasm.
+;;
+;; Every float instruction of a guest module is rewritten into a call of the exported
+;; function with the same name, e.g. `f64.add` is rewritten into `call $f64_add`. f32
+;; and f64 values are passed as their bit patterns in i32 and i64, so the library only
+;; uses integer instructions which are supported by the circuit.
+;;
+;; All operations round to nearest, ties to even. NaN results are always the canonical
+;; NaN. Conversions from float to integer trap on NaN and overflow.
+;;
+;; The algorithms follow Berkeley SoftFloat 3. f32 arithmetic is computed in f64 and
+;; rounded back, which is exact since f64 has more than 2 * 24 + 2 significand bits.
+(module
+  ;; Shift `a` right by `dist` bits, or-ing every bit shifted out into the lowest bit.
+  (func $shift_right_jam64 (param $a i64) (param $dist i64) (result i64)
+    (if (i64.eqz (local.get $dist))
+      (then
+        (return (local.get $a))))
+    (if (i64.lt_u (local.get $dist) (i64.const 63))
+      (then
+        (return (i64.or (i64.shr_u (local.get $a) (local.get $dist)) (i64.extend_i32_u (i64.ne (i64.shl (local.get $a) (i64.and (i64.sub (i64.const 0) (local.get $dist)) (i64.const 63))) (i64.const 0)))))))
+    (return (i64.extend_i32_u (i64.ne (local.get $a) (i64.const 0)))))
+
+  ;; Round and pack an f64 with the nearest-even mode. `sig` keeps its leading bit at
+  ;; bit 62 and the 10 lowest bits are the round bits, `exp` is the biased exponent minus
+  ;; one and may be out of range.
+  (func $round_pack_f64 (param $sign i64) (param $exp i64) (param $sig i64) (result i64)
+    (local $round_bits i64)
+    (local.set $round_bits (i64.and (local.get $sig) (i64.const 0x3ff)))
+    (if (i64.le_u (i64.const 0x7fd) (local.get $exp))
+      (then
+        (if (i64.lt_s (local.get $exp) (i64.const 0))
+          (then
+            (local.set $sig (call $shift_right_jam64 (local.get $sig) (i64.sub (i64.const 0) (local.get $exp))))
+            (local.set $exp (i64.const 0))
+            (local.set $round_bits (i64.and (local.get $sig) (i64.const 0x3ff))))
+          (else
+            (if (i32.or (i64.lt_s (i64.const 0x7fd) (local.get $exp)) (i64.le_u (i64.const 0x8000000000000000) (i64.add (local.get $sig) (i64.const 0x200))))
+              (then
+                (return (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))))))
+    (local.set $sig (i64.shr_u (i64.add (local.get $sig) (i64.const 0x200)) (i64.const 10)))
+    (if (i64.eq (local.get $round_bits) (i64.const 0x200))
+      (then
+        (local.set $sig (i64.and (local.get $sig) (i64.const 0xfffffffffffffffe)))))
+    (if (i64.eqz (local.get $sig))
+      (then
+        (local.set $exp (i64.const 0))))
+    (return (i64.add (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.shl (local.get $exp) (i64.const 52))) (local.get $sig))))
+
+  ;; Normalize `sig` so that its leading bit is at bit 62, then round and pack.
+  (func $norm_round_pack_f64 (param $sign i64) (param $exp i64) (param $sig i64) (result i64)
+    (local $dist i64)
+    (local.set $dist (i64.sub (i64.clz (local.get $sig)) (i64.const 1)))
+    (local.set $exp (i64.sub (local.get $exp) (local.get $dist)))
+    (if (i32.and (i64.le_u (i64.const 10) (local.get $dist)) (i64.lt_u (local.get $exp) (i64.const 0x7fd)))
+      (then
+        (return (i64.add (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.shl (select (local.get $exp) (i64.const 0) (i64.ne (local.get $sig) (i64.const 0))) (i64.const 52))) (i64.shl (local.get $sig) (i64.sub (local.get $dist) (i64.const 10)))))))
+    (return (call $round_pack_f64 (local.get $sign) (local.get $exp) (i64.shl (local.get $sig) (local.get $dist)))))
+
+  ;; Round and pack an f32 with the nearest-even mode. `sig` keeps its leading bit at
+  ;; bit 30 and the 7 lowest bits are the round bits.
+  (func $round_pack_f32 (param $sign i64) (param $exp i64) (param $sig i64) (result i32)
+    (local $round_bits i64)
+    (local.set $round_bits (i64.and (local.get $sig) (i64.const 0x7f)))
+    (if (i64.le_u (i64.const 0xfd) (local.get $exp))
+      (then
+        (if (i64.lt_s (local.get $exp) (i64.const 0))
+          (then
+            (local.set $sig (call $shift_right_jam64 (local.get $sig) (i64.sub (i64.const 0) (local.get $exp))))
+            (local.set $exp (i64.const 0))
+            (local.set $round_bits (i64.and (local.get $sig) (i64.const 0x7f))))
+          (else
+            (if (i32.or (i64.lt_s (i64.const 0xfd) (local.get $exp)) (i64.le_u (i64.const 0x80000000) (i64.add (local.get $sig) (i64.const 64))))
+              (then
+                (return (i32.wrap_i64 (i64.or (i64.shl (local.get $sign) (i64.const 31)) (i64.const 0x7f800000))))))))))
+    (local.set $sig (i64.shr_u (i64.add (local.get $sig) (i64.const 64)) (i64.const 7)))
+    (if (i64.eq (local.get $round_bits) (i64.const 64))
+      (then
+        (local.set $sig (i64.and (local.get $sig) (i64.const 0xfffffffffffffffe)))))
+    (if (i64.eqz (local.get $sig))
+      (then
+        (local.set $exp (i64.const 0))))
+    (return (i32.wrap_i64 (i64.add (i64.add (i64.shl (local.get $sign) (i64.const 31)) (i64.shl (local.get $exp) (i64.const 23))) (local.get $sig)))))
+
+  ;; Add the magnitudes of `a` and `b`, the result has the sign `sign`.
+  (func $f64_add_mags (param $a i64) (param $b i64) (param $sign i64) (result i64)
+    (local $exp_a i64)
+    (local $sig_a i64)
+    (local $exp_b i64)
+    (local $sig_b i64)
+    (local $exp_diff i64)
+    (local $exp_z i64)
+    (local $sig_z i64)
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (local.set $exp_b (i64.and (i64.shr_u (local.get $b) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_b (i64.and (local.get $b) (i64.const 0xfffffffffffff)))
+    (local.set $exp_diff (i64.sub (local.get $exp_a) (local.get $exp_b)))
+    (if (i64.eqz (local.get $exp_diff))
+      (then
+        (if (i64.eqz (local.get $exp_a))
+          (then
+            (return (i64.add (local.get $a) (local.get $sig_b)))))
+        (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+          (then
+            (if (i64.ne (i64.or (local.get $sig_a) (local.get $sig_b)) (i64.const 0))
+              (then
+                (return (i64.const 0x7ff8000000000000))))
+            (return (local.get $a))))
+        (local.set $exp_z (local.get $exp_a))
+        (local.set $sig_z (i64.shl (i64.add (i64.add (i64.const 0x20000000000000) (local.get $sig_a)) (local.get $sig_b)) (i64.const 9))))
+      (else
+        (local.set $sig_a (i64.shl (local.get $sig_a) (i64.const 9)))
+        (local.set $sig_b (i64.shl (local.get $sig_b) (i64.const 9)))
+        (if (i64.lt_s (local.get $exp_diff) (i64.const 0))
+          (then
+            (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+              (then
+                (if (i64.ne (local.get $sig_b) (i64.const 0))
+                  (then
+                    (return (i64.const 0x7ff8000000000000))))
+                (return (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))
+            (local.set $exp_z (local.get $exp_b))
+            (local.set $sig_a (select (i64.add (local.get $sig_a) (i64.const 0x2000000000000000)) (i64.shl (local.get $sig_a) (i64.const 1)) (i64.ne (local.get $exp_a) (i64.const 0))))
+            (local.set $sig_a (call $shift_right_jam64 (local.get $sig_a) (i64.sub (i64.const 0) (local.get $exp_diff)))))
+          (else
+            (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+              (then
+                (if (i64.ne (local.get $sig_a) (i64.const 0))
+                  (then
+                    (return (i64.const 0x7ff8000000000000))))
+                (return (local.get $a))))
+            (local.set $exp_z (local.get $exp_a))
+            (local.set $sig_b (select (i64.add (local.get $sig_b) (i64.const 0x2000000000000000)) (i64.shl (local.get $sig_b) (i64.const 1)) (i64.ne (local.get $exp_b) (i64.const 0))))
+            (local.set $sig_b (call $shift_right_jam64 (local.get $sig_b) (local.get $exp_diff)))))
+        (local.set $sig_z (i64.add (i64.add (i64.const 0x2000000000000000) (local.get $sig_a)) (local.get $sig_b)))
+        (if (i64.lt_u (local.get $sig_z) (i64.const 0x4000000000000000))
+          (then
+            (local.set $exp_z (i64.sub (local.get $exp_z) (i64.const 1)))
+            (local.set $sig_z (i64.shl (local.get $sig_z) (i64.const 1)))))))
+    (return (call $round_pack_f64 (local.get $sign) (local.get $exp_z) (local.get $sig_z))))
+
+  ;; Subtract the magnitude of `b` from the magnitude of `a`, `sign` is the sign of `a`.
+  (func $f64_sub_mags (param $a i64) (param $b i64) (param $sign i64) (result i64)
+    (local $exp_a i64)
+    (local $sig_a i64)
+    (local $exp_b i64)
+    (local $sig_b i64)
+    (local $exp_diff i64)
+    (local $exp_z i64)
+    (local $sig_z i64)
+    (local $dist i64)
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (local.set $exp_b (i64.and (i64.shr_u (local.get $b) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_b (i64.and (local.get $b) (i64.const 0xfffffffffffff)))
+    (local.set $exp_diff (i64.sub (local.get $exp_a) (local.get $exp_b)))
+    (if (i64.eqz (local.get $exp_diff))
+      (then
+        (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+          (then
+            (return (i64.const 0x7ff8000000000000))))
+        (local.set $sig_z (i64.sub (local.get $sig_a) (local.get $sig_b)))
+        (if (i64.eqz (local.get $sig_z))
+          (then
+            (return (i64.const 0))))
+        (if (i64.ne (local.get $exp_a) (i64.const 0))
+          (then
+            (local.set $exp_a (i64.sub (local.get $exp_a) (i64.const 1)))))
+        (if (i64.lt_s (local.get $sig_z) (i64.const 0))
+          (then
+            (local.set $sign (i64.xor (local.get $sign) (i64.const 1)))
+            (local.set $sig_z (i64.sub (i64.const 0) (local.get $sig_z)))))
+        (local.set $dist (i64.sub (i64.clz (local.get $sig_z)) (i64.const 11)))
+        (local.set $exp_z (i64.sub (local.get $exp_a) (local.get $dist)))
+        (if (i64.lt_s (local.get $exp_z) (i64.const 0))
+          (then
+            (local.set $dist (local.get $exp_a))
+            (local.set $exp_z (i64.const 0))))
+        (return (i64.add (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.shl (local.get $exp_z) (i64.const 52))) (i64.shl (local.get $sig_z) (local.get $dist))))))
+    (local.set $sig_a (i64.shl (local.get $sig_a) (i64.const 10)))
+    (local.set $sig_b (i64.shl (local.get $sig_b) (i64.const 10)))
+    (if (i64.lt_s (local.get $exp_diff) (i64.const 0))
+      (then
+        (local.set $sign (i64.xor (local.get $sign) (i64.const 1)))
+        (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+          (then
+            (if (i64.ne (local.get $sig_b) (i64.const 0))
+              (then
+                (return (i64.const 0x7ff8000000000000))))
+            (return (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))
+        (local.set $sig_a (i64.add (local.get $sig_a) (select (i64.const 0x4000000000000000) (local.get $sig_a) (i64.ne (local.get $exp_a) (i64.const 0)))))
+        (local.set $sig_a (call $shift_right_jam64 (local.get $sig_a) (i64.sub (i64.const 0) (local.get $exp_diff))))
+        (local.set $exp_z (local.get $exp_b))
+        (local.set $sig_z (i64.sub (i64.or (local.get $sig_b) (i64.const 0x4000000000000000)) (local.get $sig_a))))
+      (else
+        (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+          (then
+            (if (i64.ne (local.get $sig_a) (i64.const 0))
+              (then
+                (return (i64.const 0x7ff8000000000000))))
+            (return (local.get $a))))
+        (local.set $sig_b (i64.add (local.get $sig_b) (select (i64.const 0x4000000000000000) (local.get $sig_b) (i64.ne (local.get $exp_b) (i64.const 0)))))
+        (local.set $sig_b (call $shift_right_jam64 (local.get $sig_b) (local.get $exp_diff)))
+        (local.set $exp_z (local.get $exp_a))
+        (local.set $sig_z (i64.sub (i64.or (local.get $sig_a) (i64.const 0x4000000000000000)) (local.get $sig_b)))))
+    (return (call $norm_round_pack_f64 (local.get $sign) (i64.sub (local.get $exp_z) (i64.const 1)) (local.get $sig_z))))
+
+  (func $f64_add (export "f64_add") (param $a i64) (param $b i64) (result i64)
+    (if (i64.eq (i64.shr_u (local.get $a) (i64.const 63)) (i64.shr_u (local.get $b) (i64.const 63)))
+      (then
+        (return (call $f64_add_mags (local.get $a) (local.get $b) (i64.shr_u (local.get $a) (i64.const 63))))))
+    (return (call $f64_sub_mags (local.get $a) (local.get $b) (i64.shr_u (local.get $a) (i64.const 63)))))
+
+  (func $f64_sub (export "f64_sub") (param $a i64) (param $b i64) (result i64)
+    (if (i64.eq (i64.shr_u (local.get $a) (i64.const 63)) (i64.shr_u (local.get $b) (i64.const 63)))
+      (then
+        (return (call $f64_sub_mags (local.get $a) (local.get $b) (i64.shr_u (local.get $a) (i64.const 63))))))
+    (return (call $f64_add_mags (local.get $a) (local.get $b) (i64.shr_u (local.get $a) (i64.const 63)))))
+
+  ;; The high 64 bits of the 128 bits product of `a` and `b`, or-ed with 1 if the low
+  ;; 64 bits are not zero.
+  (func $mul64_hi (param $a i64) (param $b i64) (result i64)
+    (local $a_hi i64)
+    (local $a_lo i64)
+    (local $b_hi i64)
+    (local $b_lo i64)
+    (local $mid1 i64)
+    (local $mid i64)
+    (local $lo i64)
+    (local $hi i64)
+    (local.set $a_hi (i64.shr_u (local.get $a) (i64.const 32)))
+    (local.set $a_lo (i64.and (local.get $a) (i64.const 0xffffffff)))
+    (local.set $b_hi (i64.shr_u (local.get $b) (i64.const 32)))
+    (local.set $b_lo (i64.and (local.get $b) (i64.const 0xffffffff)))
+    (local.set $lo (i64.mul (local.get $a_lo) (local.get $b_lo)))
+    (local.set $mid1 (i64.mul (local.get $a_hi) (local.get $b_lo)))
+    (local.set $mid (i64.add (local.get $mid1) (i64.mul (local.get $a_lo) (local.get $b_hi))))
+    (local.set $hi (i64.mul (local.get $a_hi) (local.get $b_hi)))
+    (local.set $hi (i64.add (local.get $hi) (i64.or (i64.shl (i64.extend_i32_u (i64.lt_u (local.get $mid) (local.get $mid1))) (i64.const 32)) (i64.shr_u (local.get $mid) (i64.const 32)))))
+    (local.set $mid (i64.shl (local.get $mid) (i64.const 32)))
+    (local.set $lo (i64.add (local.get $lo) (local.get $mid)))
+    (local.set $hi (i64.add (local.get $hi) (i64.extend_i32_u (i64.lt_u (local.get $lo) (local.get $mid)))))
+    (return (i64.or (local.get $hi) (i64.extend_i32_u (i64.ne (local.get $lo) (i64.const 0))))))
+
+  (func $f64_mul (export "f64_mul") (param $a i64) (param $b i64) (result i64)
+    (local $sign i64)
+    (local $exp_a i64)
+    (local $sig_a i64)
+    (local $exp_b i64)
+    (local $sig_b i64)
+    (local $exp_z i64)
+    (local $sig_z i64)
+    (local $dist i64)
+    (local.set $sign (i64.xor (i64.shr_u (local.get $a) (i64.const 63)) (i64.shr_u (local.get $b) (i64.const 63))))
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (local.set $exp_b (i64.and (i64.shr_u (local.get $b) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_b (i64.and (local.get $b) (i64.const 0xfffffffffffff)))
+    (if (i32.or (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)) (i64.gt_u (i64.and (local.get $b) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)))
+      (then
+        (return (i64.const 0x7ff8000000000000))))
+    (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+      (then
+        (if (i64.eqz (i64.or (local.get $exp_b) (local.get $sig_b)))
+          (then
+            (return (i64.const 0x7ff8000000000000))))
+        (return (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))
+    (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+      (then
+        (if (i64.eqz (i64.or (local.get $exp_a) (local.get $sig_a)))
+          (then
+            (return (i64.const 0x7ff8000000000000))))
+        (return (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))
+    (if (i64.eqz (local.get $exp_a))
+      (then
+        (if (i64.eqz (local.get $sig_a))
+          (then
+            (return (i64.shl (local.get $sign) (i64.const 63)))))
+        (local.set $dist (i64.sub (i64.clz (local.get $sig_a)) (i64.const 11)))
+        (local.set $exp_a (i64.sub (i64.const 1) (local.get $dist)))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (local.get $dist)))))
+    (if (i64.eqz (local.get $exp_b))
+      (then
+        (if (i64.eqz (local.get $sig_b))
+          (then
+            (return (i64.shl (local.get $sign) (i64.const 63)))))
+        (local.set $dist (i64.sub (i64.clz (local.get $sig_b)) (i64.const 11)))
+        (local.set $exp_b (i64.sub (i64.const 1) (local.get $dist)))
+        (local.set $sig_b (i64.shl (local.get $sig_b) (local.get $dist)))))
+    (local.set $exp_z (i64.sub (i64.add (local.get $exp_a) (local.get $exp_b)) (i64.const 0x3ff)))
+    (local.set $sig_a (i64.shl (i64.or (local.get $sig_a) (i64.const 0x10000000000000)) (i64.const 10)))
+    (local.set $sig_b (i64.shl (i64.or (local.get $sig_b) (i64.const 0x10000000000000)) (i64.const 11)))
+    (local.set $sig_z (call $mul64_hi (local.get $sig_a) (local.get $sig_b)))
+    (if (i64.lt_u (local.get $sig_z) (i64.const 0x4000000000000000))
+      (then
+        (local.set $exp_z (i64.sub (local.get $exp_z) (i64.const 1)))
+        (local.set $sig_z (i64.shl (local.get $sig_z) (i64.const 1)))))
+    (return (call $round_pack_f64 (local.get $sign) (local.get $exp_z) (local.get $sig_z))))
+
+  (func $f64_div (export "f64_div") (param $a i64) (param $b i64) (result i64)
+    (local $sign i64)
+    (local $exp_a i64)
+    (local $sig_a i64)
+    (local $exp_b i64)
+    (local $sig_b i64)
+    (local $exp_z i64)
+    (local $sig_z i64)
+    (local $dist i64)
+    (local $i i64)
+    (local.set $sign (i64.xor (i64.shr_u (local.get $a) (i64.const 63)) (i64.shr_u (local.get $b) (i64.const 63))))
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (local.set $exp_b (i64.and (i64.shr_u (local.get $b) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_b (i64.and (local.get $b) (i64.const 0xfffffffffffff)))
+    (if (i32.or (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)) (i64.gt_u (i64.and (local.get $b) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)))
+      (then
+        (return (i64.const 0x7ff8000000000000))))
+    (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+      (then
+        (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+          (then
+            (return (i64.const 0x7ff8000000000000))))
+        (return (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))
+    (if (i64.eq (local.get $exp_b) (i64.const 0x7ff))
+      (then
+        (return (i64.shl (local.get $sign) (i64.const 63)))))
+    (if (i64.eqz (local.get $exp_b))
+      (then
+        (if (i64.eqz (local.get $sig_b))
+          (then
+            (if (i64.eqz (i64.or (local.get $exp_a) (local.get $sig_a)))
+              (then
+                (return (i64.const 0x7ff8000000000000))))
+            (return (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))
+        (local.set $dist (i64.sub (i64.clz (local.get $sig_b)) (i64.const 11)))
+        (local.set $exp_b (i64.sub (i64.const 1) (local.get $dist)))
+        (local.set $sig_b (i64.shl (local.get $sig_b) (local.get $dist)))))
+    (if (i64.eqz (local.get $exp_a))
+      (then
+        (if (i64.eqz (local.get $sig_a))
+          (then
+            (return (i64.shl (local.get $sign) (i64.const 63)))))
+        (local.set $dist (i64.sub (i64.clz (local.get $sig_a)) (i64.const 11)))
+        (local.set $exp_a (i64.sub (i64.const 1) (local.get $dist)))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (local.get $dist)))))
+    (local.set $exp_z (i64.add (i64.sub (local.get $exp_a) (local.get $exp_b)) (i64.const 0x3fe)))
+    (local.set $sig_a (i64.or (local.get $sig_a) (i64.const 0x10000000000000)))
+    (local.set $sig_b (i64.or (local.get $sig_b) (i64.const 0x10000000000000)))
+    (if (i64.lt_u (local.get $sig_a) (local.get $sig_b))
+      (then
+        (local.set $exp_z (i64.sub (local.get $exp_z) (i64.const 1)))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (i64.const 1)))))
+    (local.set $sig_z (i64.const 0))
+    (local.set $i (i64.const 0))
+    (block $done1
+      (loop $next1
+        (br_if $done1 (i32.eqz (i64.lt_u (local.get $i) (i64.const 63))))
+        (local.set $sig_z (i64.shl (local.get $sig_z) (i64.const 1)))
+        (if (i64.ge_u (local.get $sig_a) (local.get $sig_b))
+          (then
+            (local.set $sig_a (i64.sub (local.get $sig_a) (local.get $sig_b)))
+            (local.set $sig_z (i64.or (local.get $sig_z) (i64.const 1)))))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (i64.const 1)))
+        (local.set $i (i64.add (local.get $i) (i64.const 1)))
+        (br $next1)))
+    (local.set $sig_z (i64.or (local.get $sig_z) (i64.extend_i32_u (i64.ne (local.get $sig_a) (i64.const 0)))))
+    (return (call $round_pack_f64 (local.get $sign) (local.get $exp_z) (local.get $sig_z))))
+
+  (func $f64_sqrt (export "f64_sqrt") (param $a i64) (result i64)
+    (local $exp_a i64)
+    (local $sig_a i64)
+    (local $exp_z i64)
+    (local $dist i64)
+    (local $rem i64)
+    (local $root i64)
+    (local $trial i64)
+    (local $shift i64)
+    (local.set $exp_a (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $sig_a (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (if (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000))
+      (then
+        (return (i64.const 0x7ff8000000000000))))
+    (if (i64.ne (i64.shr_u (local.get $a) (i64.const 63)) (i64.const 0))
+      (then
+        (if (i64.eqz (i64.or (local.get $exp_a) (local.get $sig_a)))
+          (then
+            (return (local.get $a))))
+        (return (i64.const 0x7ff8000000000000))))
+    (if (i64.eq (local.get $exp_a) (i64.const 0x7ff))
+      (then
+        (return (local.get $a))))
+    (if (i64.eqz (local.get $exp_a))
+      (then
+        (if (i64.eqz (local.get $sig_a))
+          (then
+            (return (local.get $a))))
+        (local.set $dist (i64.sub (i64.clz (local.get $sig_a)) (i64.const 11)))
+        (local.set $exp_a (i64.sub (i64.const 1) (local.get $dist)))
+        (local.set $sig_a (i64.shl (local.get $sig_a) (local.get $dist)))))
+    (local.set $exp_z (i64.add (i64.shr_s (i64.sub (local.get $exp_a) (i64.const 0x3ff)) (i64.const 1)) (i64.const 0x3fe)))
+    (local.set $sig_a (i64.or (local.get $sig_a) (i64.const 0x10000000000000)))
+    (if (i64.eqz (i64.and (local.get $exp_a) (i64.const 1)))
+      (then
+        (local.set $sig_a (i64.shl (local.get $sig_a) (i64.const 1)))))
+    (local.set $rem (i64.const 0))
+    (local.set $root (i64.const 0))
+    (local.set $shift (i64.const 52))
+    (block $done1
+      (loop $next1
+        (br_if $done1 (i32.eqz (i64.ne (local.get $shift) (i64.const 0xffffffffffffffc6))))
+        (local.set $rem (i64.shl (local.get $rem) (i64.const 2)))
+        (if (i64.ge_s (local.get $shift) (i64.const 0))
+          (then
+            (local.set $rem (i64.or (local.get $rem) (i64.and (i64.shr_u (local.get $sig_a) (local.get $shift)) (i64.const 3))))))
+        (local.set $trial (i64.or (i64.shl (local.get $root) (i64.const 2)) (i64.const 1)))
+        (local.set $root (i64.shl (local.get $root) (i64.const 1)))
+        (if (i64.ge_u (local.get $rem) (local.get $trial))
+          (then
+            (local.set $rem (i64.sub (local.get $rem) (local.get $trial)))
+            (local.set $root (i64.or (local.get $root) (i64.const 1)))))
+        (local.set $shift (i64.sub (local.get $shift) (i64.const 2)))
+        (br $next1)))
+    (return (call $round_pack_f64 (i64.const 0) (local.get $exp_z) (i64.or (i64.shl (local.get $root) (i64.const 8)) (i64.extend_i32_u (i64.ne (local.get $rem) (i64.const 0)))))))
+
+  (func $f64_abs (export "f64_abs") (param $a i64) (result i64)
+    (return (i64.and (local.get $a) (i64.const 0x7fffffffffffffff))))
+
+  (func $f64_neg (export "f64_neg") (param $a i64) (result i64)
+    (return (i64.xor (local.get $a) (i64.const 0x8000000000000000))))
+
+  (func $f64_copysign (export "f64_copysign") (param $a i64) (param $b i64) (result i64)
+    (return (i64.or (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.and (local.get $b) (i64.const 0x8000000000000000)))))
+
+  (func $f64_eq (export "f64_eq") (param $a i64) (param $b i64) (result i32)
+    (if (i32.or (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)) (i64.gt_u (i64.and (local.get $b) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)))
+      (then
+        (return (i32.const 0))))
+    (return (i32.or (i64.eq (local.get $a) (local.get $b)) (i64.eqz (i64.and (i64.or (local.get $a) (local.get $b)) (i64.const 0x7fffffffffffffff))))))
+
+  (func $f64_ne (export "f64_ne") (param $a i64) (param $b i64) (result i32)
+    (return (i32.eqz (call $f64_eq (local.get $a) (local.get $b)))))
+
+  (func $f64_lt (export "f64_lt") (param $a i64) (param $b i64) (result i32)
+    (if (i32.or (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)) (i64.gt_u (i64.and (local.get $b) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)))
+      (then
+        (return (i32.const 0))))
+    (if (i64.ne (i64.shr_u (local.get $a) (i64.const 63)) (i64.shr_u (local.get $b) (i64.const 63)))
+      (then
+        (return (i32.and (i32.wrap_i64 (i64.shr_u (local.get $a) (i64.const 63))) (i64.ne (i64.and (i64.or (local.get $a) (local.get $b)) (i64.const 0x7fffffffffffffff)) (i64.const 0))))))
+    (return (i32.and (i64.ne (local.get $a) (local.get $b)) (i32.xor (i32.wrap_i64 (i64.shr_u (local.get $a) (i64.const 63))) (i64.lt_u (local.get $a) (local.get $b))))))
+
+  (func $f64_le (export "f64_le") (param $a i64) (param $b i64) (result i32)
+    (if (i32.or (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)) (i64.gt_u (i64.and (local.get $b) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)))
+      (then
+        (return (i32.const 0))))
+    (if (i64.ne (i64.shr_u (local.get $a) (i64.const 63)) (i64.shr_u (local.get $b) (i64.const 63)))
+      (then
+        (return (i32.or (i32.wrap_i64 (i64.shr_u (local.get $a) (i64.const 63))) (i64.eqz (i64.and (i64.or (local.get $a) (local.get $b)) (i64.const 0x7fffffffffffffff)))))))
+    (return (i32.or (i64.eq (local.get $a) (local.get $b)) (i32.xor (i32.wrap_i64 (i64.shr_u (local.get $a) (i64.const 63))) (i64.lt_u (local.get $a) (local.get $b))))))
+
+  (func $f64_gt (export "f64_gt") (param $a i64) (param $b i64) (result i32)
+    (return (call $f64_lt (local.get $b) (local.get $a))))
+
+  (func $f64_ge (export "f64_ge") (param $a i64) (param $b i64) (result i32)
+    (return (call $f64_le (local.get $b) (local.get $a))))
+
+  (func $f64_min (export "f64_min") (param $a i64) (param $b i64) (result i64)
+    (if (i32.or (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)) (i64.gt_u (i64.and (local.get $b) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)))
+      (then
+        (return (i64.const 0x7ff8000000000000))))
+    (if (call $f64_lt (local.get $a) (local.get $b))
+      (then
+        (return (local.get $a))))
+    (if (call $f64_lt (local.get $b) (local.get $a))
+      (then
+        (return (local.get $b))))
+    (return (i64.or (local.get $a) (local.get $b))))
+
+  (func $f64_max (export "f64_max") (param $a i64) (param $b i64) (result i64)
+    (if (i32.or (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)) (i64.gt_u (i64.and (local.get $b) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000)))
+      (then
+        (return (i64.const 0x7ff8000000000000))))
+    (if (call $f64_lt (local.get $a) (local.get $b))
+      (then
+        (return (local.get $b))))
+    (if (call $f64_lt (local.get $b) (local.get $a))
+      (then
+        (return (local.get $a))))
+    (return (i64.and (local.get $a) (local.get $b))))
+
+  ;; Round to an integral value, `mode` is 0 for nearest-even, 1 for floor, 2 for ceil
+  ;; and 3 for trunc.
+  (func $f64_round_to_int (param $a i64) (param $mode i64) (result i64)
+    (local $exp i64)
+    (local $z i64)
+    (local $last_bit i64)
+    (local $round_bits i64)
+    (local.set $exp (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (if (i64.le_u (local.get $exp) (i64.const 0x3fe))
+      (then
+        (if (i64.eqz (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)))
+          (then
+            (return (local.get $a))))
+        (local.set $z (i64.and (local.get $a) (i64.const 0x8000000000000000)))
+        (if (i64.eqz (local.get $mode))
+          (then
+            (if (i32.and (i64.eq (local.get $exp) (i64.const 0x3fe)) (i64.ne (i64.and (local.get $a) (i64.const 0xfffffffffffff)) (i64.const 0)))
+              (then
+                (return (i64.or (local.get $z) (i64.const 0x3ff0000000000000)))))))
+        (if (i32.and (i64.eq (local.get $mode) (i64.const 1)) (i64.ne (local.get $z) (i64.const 0)))
+          (then
+            (return (i64.const 0xbff0000000000000))))
+        (if (i32.and (i64.eq (local.get $mode) (i64.const 2)) (i64.eqz (local.get $z)))
+          (then
+            (return (i64.const 0x3ff0000000000000))))
+        (return (local.get $z))))
+    (if (i64.le_u (i64.const 0x433) (local.get $exp))
+      (then
+        (if (i64.gt_u (i64.and (local.get $a) (i64.const 0x7fffffffffffffff)) (i64.const 0x7ff0000000000000))
+          (then
+            (return (i64.const 0x7ff8000000000000))))
+        (return (local.get $a))))
+    (local.set $z (local.get $a))
+    (local.set $last_bit (i64.shl (i64.const 1) (i64.sub (i64.const 0x433) (local.get $exp))))
+    (local.set $round_bits (i64.sub (local.get $last_bit) (i64.const 1)))
+    (if (i64.eqz (local.get $mode))
+      (then
+        (local.set $z (i64.add (local.get $z) (i64.shr_u (local.get $last_bit) (i64.const 1))))
+        (if (i64.eqz (i64.and (local.get $z) (local.get $round_bits)))
+          (then
+            (local.set $z (i64.and (local.get $z) (i64.xor (local.get $last_bit) (i64.const 0xffffffffffffffff)))))))
+      (else
+        (if (i64.eq (local.get $mode) (select (i64.const 1) (i64.const 2) (i64.ne (i64.shr_u (local.get $z) (i64.const 63)) (i64.const 0))))
+          (then
+            (local.set $z (i64.add (local.get $z) (local.get $round_bits)))))))
+    (return (i64.and (local.get $z) (i64.xor (local.get $round_bits) (i64.const 0xffffffffffffffff)))))
+
+  (func $f64_nearest (export "f64_nearest") (param $a i64) (result i64)
+    (return (call $f64_round_to_int (local.get $a) (i64.const 0))))
+
+  (func $f64_floor (export "f64_floor") (param $a i64) (result i64)
+    (return (call $f64_round_to_int (local.get $a) (i64.const 1))))
+
+  (func $f64_ceil (export "f64_ceil") (param $a i64) (result i64)
+    (return (call $f64_round_to_int (local.get $a) (i64.const 2))))
+
+  (func $f64_trunc (export "f64_trunc") (param $a i64) (result i64)
+    (return (call $f64_round_to_int (local.get $a) (i64.const 3))))
+
+  (func $f64_promote_f32 (export "f64_promote_f32") (param $a i32) (result i64)
+    (local $sign i64)
+    (local $exp i64)
+    (local $frac i64)
+    (local $dist i64)
+    (local.set $sign (i64.extend_i32_u (i32.shr_u (local.get $a) (i32.const 31))))
+    (local.set $exp (i64.and (i64.shr_u (i64.extend_i32_u (local.get $a)) (i64.const 23)) (i64.const 0xff)))
+    (local.set $frac (i64.and (i64.extend_i32_u (local.get $a)) (i64.const 0x7fffff)))
+    (if (i64.eq (local.get $exp) (i64.const 0xff))
+      (then
+        (if (i64.ne (local.get $frac) (i64.const 0))
+          (then
+            (return (i64.const 0x7ff8000000000000))))
+        (return (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.const 0x7ff0000000000000)))))
+    (if (i64.eqz (local.get $exp))
+      (then
+        (if (i64.eqz (local.get $frac))
+          (then
+            (return (i64.shl (local.get $sign) (i64.const 63)))))
+        (local.set $dist (i64.sub (i64.clz (local.get $frac)) (i64.const 40)))
+        (local.set $exp (i64.sub (i64.sub (i64.const 1) (local.get $dist)) (i64.const 1)))
+        (local.set $frac (i64.shl (local.get $frac) (local.get $dist)))))
+    (return (i64.add (i64.add (i64.shl (local.get $sign) (i64.const 63)) (i64.shl (i64.add (local.get $exp) (i64.const 0x380)) (i64.const 52))) (i64.shl (local.get $frac) (i64.const 29)))))
+
+  (func $f32_demote_f64 (export "f32_demote_f64") (param $a i64) (result i32)
+    (local $sign i64)
+    (local $exp i64)
+    (local $frac i64)
+    (local.set $sign (i64.shr_u (local.get $a) (i64.const 63)))
+    (local.set $exp (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)))
+    (local.set $frac (i64.and (local.get $a) (i64.const 0xfffffffffffff)))
+    (if (i64.eq (local.get $exp) (i64.const 0x7ff))
+      (then
+        (if (i64.ne (local.get $frac) (i64.const 0))
+          (then
+            (return (i32.const 0x7fc00000))))
+        (return (i32.wrap_i64 (i64.or (i64.shl (local.get $sign) (i64.const 31)) (i64.const 0x7f800000))))))
+    (local.set $frac (call $shift_right_jam64 (local.get $frac) (i64.const 22)))
+    (if (i64.eqz (i64.or (local.get $exp) (local.get $frac)))
+      (then
+        (return (i32.wrap_i64 (i64.shl (local.get $sign) (i64.const 31))))))
+    (return (call $round_pack_f32 (local.get $sign) (i64.sub (local.get $exp) (i64.const 0x381)) (i64.or (local.get $frac) (i64.const 0x40000000)))))
+
+  ;; Convert the integer `mag` with the sign `sign` to the nearest f64.
+  (func $f64_from_u64 (param $sign i64) (param $mag i64) (result i64)
+    (if (i64.eqz (local.get $mag))
+      (then
+        (return (i64.const 0))))
+    (if (i64.lt_s (local.get $mag) (i64.const 0))
+      (then
+        (return (call $round_pack_f64 (local.get $sign) (i64.const 0x43d) (i64.or (i64.shr_u (local.get $mag) (i64.const 1)) (i64.and (local.get $mag) (i64.const 1)))))))
+    (return (call $norm_round_pack_f64 (local.get $sign) (i64.const 0x43c) (local.get $mag))))
+
+  ;; Convert the integer `mag` with the sign `sign` to the nearest f32.
+  (func $f32_from_u64 (param $sign i64) (param $mag i64) (result i32)
+    (local $dist i64)
+    (if (i64.eqz (local.get $mag))
+      (then
+        (return (i32.const 0))))
+    (local.set $dist (i64.sub (i64.clz (local.get $mag)) (i64.const 40)))
+    (if (i64.ge_s (local.get $dist) (i64.const 0))
+      (then
+        (return (i32.wrap_i64 (i64.add (i64.add (i64.shl (local.get $sign) (i64.const 31)) (i64.shl (i64.sub (i64.const 0x95) (local.get $dist)) (i64.const 23))) (i64.shl (local.get $mag) (local.get $dist)))))))
+    (local.set $dist (i64.add (local.get $dist) (i64.const 7)))
+    (if (i64.lt_s (local.get $dist) (i64.const 0))
+      (then
+        (local.set $mag (call $shift_right_jam64 (local.get $mag) (i64.sub (i64.const 0) (local.get $dist)))))
+      (else
+        (local.set $mag (i64.shl (local.get $mag) (local.get $dist)))))
+    (return (call $round_pack_f32 (local.get $sign) (i64.sub (i64.const 0x9c) (local.get $dist)) (local.get $mag))))
+
+  (func $f32_convert_i32_s (export "f32_convert_i32_s") (param $a i32) (result i32)
+    (local $v i64)
+    (local.set $v (i64.extend_i32_s (local.get $a)))
+    (if (i64.lt_s (local.get $v) (i64.const 0))
+      (then
+        (return (call $f32_from_u64 (i64.const 1) (i64.sub (i64.const 0) (local.get $v))))))
+    (return (call $f32_from_u64 (i64.const 0) (local.get $v))))
+
+  (func $f32_convert_i32_u (export "f32_convert_i32_u") (param $a i32) (result i32)
+    (local $v i64)
+    (local.set $v (i64.extend_i32_u (local.get $a)))
+    (return (call $f32_from_u64 (i64.const 0) (local.get $v))))
+
+  (func $f32_convert_i64_s (export "f32_convert_i64_s") (param $a i64) (result i32)
+    (local $v i64)
+    (local.set $v (local.get $a))
+    (if (i64.lt_s (local.get $v) (i64.const 0))
+      (then
+        (return (call $f32_from_u64 (i64.const 1) (i64.sub (i64.const 0) (local.get $v))))))
+    (return (call $f32_from_u64 (i64.const 0) (local.get $v))))
+
+  (func $f32_convert_i64_u (export "f32_convert_i64_u") (param $a i64) (result i32)
+    (local $v i64)
+    (local.set $v (local.get $a))
+    (return (call $f32_from_u64 (i64.const 0) (local.get $v))))
+
+  (func $f64_convert_i32_s (export "f64_convert_i32_s") (param $a i32) (result i64)
+    (local $v i64)
+    (local.set $v (i64.extend_i32_s (local.get $a)))
+    (if (i64.lt_s (local.get $v) (i64.const 0))
+      (then
+        (return (call $f64_from_u64 (i64.const 1) (i64.sub (i64.const 0) (local.get $v))))))
+    (return (call $f64_from_u64 (i64.const 0) (local.get $v))))
+
+  (func $f64_convert_i32_u (export "f64_convert_i32_u") (param $a i32) (result i64)
+    (local $v i64)
+    (local.set $v (i64.extend_i32_u (local.get $a)))
+    (return (call $f64_from_u64 (i64.const 0) (local.get $v))))
+
+  (func $f64_convert_i64_s (export "f64_convert_i64_s") (param $a i64) (result i64)
+    (local $v i64)
+    (local.set $v (local.get $a))
+    (if (i64.lt_s (local.get $v) (i64.const 0))
+      (then
+        (return (call $f64_from_u64 (i64.const 1) (i64.sub (i64.const 0) (local.get $v))))))
+    (return (call $f64_from_u64 (i64.const 0) (local.get $v))))
+
+  (func $f64_convert_i64_u (export "f64_convert_i64_u") (param $a i64) (result i64)
+    (local $v i64)
+    (local.set $v (local.get $a))
+    (return (call $f64_from_u64 (i64.const 0) (local.get $v))))
+
+  ;; Truncate `a` to an integer and return its magnitude, trap if `a` is NaN or infinite,
+  ;; or its magnitude is not less than 2^64.
+  (func $f64_trunc_mag (param $a i64) (result i64)
+    (local $exp i64)
+    (local $sig i64)
+    (local.set $exp (i64.sub (i64.and (i64.shr_u (local.get $a) (i64.const 52)) (i64.const 0x7ff)) (i64.const 0x3ff)))
+    (if (i64.lt_s (local.get $exp) (i64.const 0))
+      (then
+        (return (i64.const 0))))
+    (if (i64.ge_s (local.get $exp) (i64.const 64))
+      (then
+        (unreachable)))
+    (local.set $sig (i64.or (i64.and (local.get $a) (i64.const 0xfffffffffffff)) (i64.const 0x10000000000000)))
+    (if (i64.ge_s (local.get $exp) (i64.const 52))
+      (then
+        (return (i64.shl (local.get $sig) (i64.sub (local.get $exp) (i64.const 52))))))
+    (return (i64.shr_u (local.get $sig) (i64.sub (i64.const 52) (local.get $exp)))))
+
+  (func $i32_trunc_f64_s (export "i32_trunc_f64_s") (param $a i64) (result i32)
+    (local $mag i64)
+    (local.set $mag (call $f64_trunc_mag (local.get $a)))
+    (if (i64.ne (i64.shr_u (local.get $a) (i64.const 63)) (i64.const 0))
+      (then
+        (if (i64.gt_u (local.get $mag) (i64.const 0x80000000))
+          (then
+            (unreachable)))
+        (return (i32.wrap_i64 (i64.sub (i64.const 0) (local.get $mag))))))
+    (if (i64.gt_u (local.get $mag) (i64.const 0x7fffffff))
+      (then
+        (unreachable)))
+    (return (i32.wrap_i64 (local.get $mag))))
+
+  (func $i32_trunc_f32_s (export "i32_trunc_f32_s") (param $a i32) (result i32)
+    (return (call $i32_trunc_f64_s (call $f64_promote_f32 (local.get $a)))))
+
+  (func $i32_trunc_f64_u (export "i32_trunc_f64_u") (param $a i64) (result i32)
+    (local $mag i64)
+    (local.set $mag (call $f64_trunc_mag (local.get $a)))
+    (if (i32.and (i64.ne (i64.shr_u (local.get $a) (i64.const 63)) (i64.const 0)) (i64.ne (local.get $mag) (i64.const 0)))
+      (then
+        (unreachable)))
+    (if (i64.gt_u (local.get $mag) (i64.const 0xffffffff))
+      (then
+        (unreachable)))
+    (return (i32.wrap_i64 (local.get $mag))))
+
+  (func $i32_trunc_f32_u (export "i32_trunc_f32_u") (param $a i32) (result i32)
+    (return (call $i32_trunc_f64_u (call $f64_promote_f32 (local.get $a)))))
+
+  (func $i64_trunc_f64_s (export "i64_trunc_f64_s") (param $a i64) (result i64)
+    (local $mag i64)
+    (local.set $mag (call $f64_trunc_mag (local.get $a)))
+    (if (i64.ne (i64.shr_u (local.get $a) (i64.const 63)) (i64.const 0))
+      (then
+        (if (i64.gt_u (local.get $mag) (i64.const 0x8000000000000000))
+          (then
+            (unreachable)))
+        (return (i64.sub (i64.const 0) (local.get $mag)))))
+    (if (i64.gt_u (local.get $mag) (i64.const 0x7fffffffffffffff))
+      (then
+        (unreachable)))
+    (return (local.get $mag)))
+
+  (func $i64_trunc_f32_s (export "i64_trunc_f32_s") (param $a i32) (result i64)
+    (return (call $i64_trunc_f64_s (call $f64_promote_f32 (local.get $a)))))
+
+  (func $i64_trunc_f64_u (export "i64_trunc_f64_u") (param $a i64) (result i64)
+    (local $mag i64)
+    (local.set $mag (call $f64_trunc_mag (local.get $a)))
+    (if (i32.and (i64.ne (i64.shr_u (local.get $a) (i64.const 63)) (i64.const 0)) (i64.ne (local.get $mag) (i64.const 0)))
+      (then
+        (unreachable)))
+    (return (local.get $mag)))
+
+  (func $i64_trunc_f32_u (export "i64_trunc_f32_u") (param $a i32) (result i64)
+    (return (call $i64_trunc_f64_u (call $f64_promote_f32 (local.get $a)))))
+
+  (func $f32_add (export "f32_add") (param $a i32) (param $b i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_add (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))))
+
+  (func $f32_sub (export "f32_sub") (param $a i32) (param $b i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_sub (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))))
+
+  (func $f32_mul (export "f32_mul") (param $a i32) (param $b i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_mul (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))))
+
+  (func $f32_div (export "f32_div") (param $a i32) (param $b i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_div (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))))
+
+  (func $f32_min (export "f32_min") (param $a i32) (param $b i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_min (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))))
+
+  (func $f32_max (export "f32_max") (param $a i32) (param $b i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_max (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b))))))
+
+  (func $f32_sqrt (export "f32_sqrt") (param $a i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_sqrt (call $f64_promote_f32 (local.get $a))))))
+
+  (func $f32_nearest (export "f32_nearest") (param $a i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_nearest (call $f64_promote_f32 (local.get $a))))))
+
+  (func $f32_floor (export "f32_floor") (param $a i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_floor (call $f64_promote_f32 (local.get $a))))))
+
+  (func $f32_ceil (export "f32_ceil") (param $a i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_ceil (call $f64_promote_f32 (local.get $a))))))
+
+  (func $f32_trunc (export "f32_trunc") (param $a i32) (result i32)
+    (return (call $f32_demote_f64 (call $f64_trunc (call $f64_promote_f32 (local.get $a))))))
+
+  (func $f32_eq (export "f32_eq") (param $a i32) (param $b i32) (result i32)
+    (return (call $f64_eq (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func $f32_ne (export "f32_ne") (param $a i32) (param $b i32) (result i32)
+    (return (call $f64_ne (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func $f32_lt (export "f32_lt") (param $a i32) (param $b i32) (result i32)
+    (return (call $f64_lt (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func $f32_gt (export "f32_gt") (param $a i32) (param $b i32) (result i32)
+    (return (call $f64_gt (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func $f32_le (export "f32_le") (param $a i32) (param $b i32) (result i32)
+    (return (call $f64_le (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func $f32_ge (export "f32_ge") (param $a i32) (param $b i32) (result i32)
+    (return (call $f64_ge (call $f64_promote_f32 (local.get $a)) (call $f64_promote_f32 (local.get $b)))))
+
+  (func $f32_abs (export "f32_abs") (param $a i32) (result i32)
+    (return (i32.and (local.get $a) (i32.const 0x7fffffff))))
+
+  (func $f32_neg (export "f32_neg") (param $a i32) (result i32)
+    (return (i32.xor (local.get $a) (i32.const 0x80000000))))
+
+  (func $f32_copysign (export "f32_copysign") (param $a i32) (param $b i32) (result i32)
+    (return (i32.or (i32.and (local.get $a) (i32.const 0x7fffffff)) (i32.and (local.get $b) (i32.const 0x80000000))))))
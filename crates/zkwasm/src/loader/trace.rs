@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::io::Write;
+
+use anyhow::anyhow;
+use anyhow::Result;
+use serde::Deserialize;
+use serde::Serialize;
+use sha2::Digest;
+use sha2::Sha256;
+use specs::etable::EventTable;
+use specs::jtable::JumpTable;
+use specs::mtable::MTable;
+use specs::CompilationTable;
+use specs::ExecutionTable;
+use specs::Tables;
+use wasmi::RuntimeValue;
+
+use super::err::Error;
+use super::err::TraceErr;
+use crate::runtime::host::ForeignStatics;
+use crate::runtime::memory_event_of_step;
+use crate::runtime::ExecutionResult;
+
+const TRACE_MAGIC: [u8; 8] = *b"ZKWTRACE";
+/// Version of the binary trace format, bump it on any change of the layout.
+pub const TRACE_VERSION: u32 = 1;
+
+/// Hash of the compilation tables, used to check whether a trace is generated from the
+/// same image, host environment and circuit size.
+pub fn compilation_table_hash(compilation_tables: &CompilationTable) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+
+    hasher.update(bincode::serialize(compilation_tables).unwrap());
+
+    hasher.finalize().into()
+}
+
+// The mtable is not stored since it can be derived from the etable.
+#[derive(Serialize, Deserialize)]
+struct TraceBody {
+    k: u32,
+    compilation_table_hash: [u8; 32],
+    compilation_tables: CompilationTable,
+    etable: EventTable,
+    jtable: JumpTable,
+    public_inputs_and_outputs: Vec<u64>,
+    outputs: Vec<u64>,
+    host_statics: HashMap<String, ForeignStatics>,
+    guest_statics: usize,
+}
+
+/// Header of a binary trace, which is checked before the tables are restored.
+pub struct TraceHeader {
+    pub version: u32,
+    pub k: u32,
+    pub compilation_table_hash: [u8; 32],
+}
+
+/// Write the execution trace in the binary trace format:
+/// magic (8 bytes) | version (u32, little endian) | bincode encoded body.
+pub fn write_trace<W: Write>(
+    k: u32,
+    execution_result: &ExecutionResult<RuntimeValue>,
+    mut writer: W,
+) -> Result<()> {
+    let compilation_tables = &execution_result.tables.compilation_tables;
+
+    let body = TraceBody {
+        k,
+        compilation_table_hash: compilation_table_hash(compilation_tables),
+        compilation_tables: compilation_tables.clone(),
+        etable: execution_result.tables.execution_tables.etable.clone(),
+        jtable: execution_result.tables.execution_tables.jtable.clone(),
+        public_inputs_and_outputs: execution_result.public_inputs_and_outputs.clone(),
+        outputs: execution_result.outputs.clone(),
+        host_statics: execution_result.host_statics.clone(),
+        guest_statics: execution_result.guest_statics,
+    };
+
+    writer.write_all(&TRACE_MAGIC)?;
+    writer.write_all(&TRACE_VERSION.to_le_bytes())?;
+    bincode::serialize_into(&mut writer, &body)?;
+
+    Ok(())
+}
+
+/// Read an execution trace written by `write_trace`, the `result` of the returned
+/// execution result is always `None`.
+pub fn read_trace<R: Read>(mut reader: R) -> Result<(TraceHeader, ExecutionResult<RuntimeValue>)> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if magic != TRACE_MAGIC {
+        return Err(anyhow!(Error::Trace(TraceErr::InvalidMagic)));
+    }
+
+    let mut version = [0u8; 4];
+    reader.read_exact(&mut version)?;
+    let version = u32::from_le_bytes(version);
+    if version != TRACE_VERSION {
+        return Err(anyhow!(Error::Trace(TraceErr::UnsupportedVersion {
+            version,
+            expected: TRACE_VERSION,
+        })));
+    }
+
+    let body: TraceBody = bincode::deserialize_from(&mut reader)?;
+
+    if compilation_table_hash(&body.compilation_tables) != body.compilation_table_hash {
+        return Err(anyhow!(Error::Trace(TraceErr::CorruptedCompilationTable)));
+    }
+
+    let mentries = body
+        .etable
+        .entries()
+        .iter()
+        .map(|eentry| memory_event_of_step(eentry, &mut 1))
+        .collect::<Vec<Vec<_>>>()
+        .concat();
+    let mtable = MTable::new(mentries, &body.compilation_tables.imtable);
+    let trap = body
+        .etable
+        .entries()
+        .last()
+        .and_then(|eentry| eentry.trap_outcome());
+
+    Ok((
+        TraceHeader {
+            version,
+            k: body.k,
+            compilation_table_hash: body.compilation_table_hash,
+        },
+        ExecutionResult {
+            tables: Tables {
+                compilation_tables: body.compilation_tables,
+                execution_tables: ExecutionTable {
+                    etable: body.etable,
+                    mtable,
+                    jtable: body.jtable,
+                },
+            },
+            result: None,
+            public_inputs_and_outputs: body.public_inputs_and_outputs,
+            host_statics: body.host_statics,
+            guest_statics: body.guest_statics,
+            outputs: body.outputs,
+            trap,
+        },
+    ))
+}
@@ -0,0 +1,165 @@
+use serde::Serialize;
+use specs::etable::EventTableEntry;
+use specs::jtable::JumpTableEntry;
+use specs::step::StepInfo;
+use specs::Tables;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::runtime::memory_event_of_step;
+
+/// Rows attributed to a single wasm function, excluding the rows of its callees.
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct FunctionProfile {
+    pub fid: u32,
+    pub function_name: String,
+    pub calls: usize,
+    pub etable_entries: usize,
+    pub mtable_entries: usize,
+    pub host_calls: usize,
+}
+
+#[derive(Default, Serialize, Debug, Clone)]
+pub struct ProfileReport {
+    pub etable_entries: usize,
+    pub mtable_entries: usize,
+    pub host_calls: usize,
+    /// Sorted by etable entries in descending order.
+    pub functions: Vec<FunctionProfile>,
+    /// Etable entries of each call stack, the frames are separated by ';'.
+    pub stacks: BTreeMap<String, usize>,
+}
+
+impl ProfileReport {
+    /// Write the call stacks in the folded format, which is the input of `flamegraph.pl`.
+    pub fn write_folded<W: Write>(&self, mut writer: W) -> Result<()> {
+        for (stack, etable_entries) in &self.stacks {
+            writeln!(writer, "{} {}", stack, etable_entries)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn write_json<W: Write>(&self, writer: W) -> Result<()> {
+        serde_json::to_writer_pretty(writer, self)?;
+
+        Ok(())
+    }
+}
+
+pub trait FunctionStatistic {
+    fn profile_functions(&self) -> ProfileReport;
+}
+
+impl FunctionStatistic for Tables {
+    fn profile_functions(&self) -> ProfileReport {
+        let mut function_names = HashMap::<u32, String>::new();
+        for entry in self.compilation_tables.itable.iter() {
+            function_names.entry(entry.fid).or_insert_with(|| {
+                if entry.function_name.is_empty() {
+                    format!("func[{}]", entry.fid)
+                } else {
+                    entry.function_name.clone()
+                }
+            });
+        }
+        let name_of = |fid: u32| {
+            function_names
+                .get(&fid)
+                .cloned()
+                .unwrap_or_else(|| format!("func[{}]", fid))
+        };
+
+        let frames = self
+            .execution_tables
+            .jtable
+            .entries()
+            .iter()
+            .map(|frame| (frame.eid, frame))
+            .collect::<HashMap<u32, &JumpTableEntry>>();
+
+        // Call stack of each frame, indexed by the eid of the call creating the frame.
+        let mut stacks_of_frame = HashMap::<u32, String>::new();
+        let mut stack_of = |event: &EventTableEntry| -> String {
+            if event.last_jump_eid == 0 {
+                return name_of(event.fid);
+            }
+
+            stacks_of_frame
+                .entry(event.last_jump_eid)
+                .or_insert_with(|| {
+                    let mut names = vec![name_of(event.fid)];
+                    let mut frame_eid = event.last_jump_eid;
+
+                    while frame_eid != 0 {
+                        let frame = frames[&frame_eid];
+
+                        names.push(name_of(frame.fid));
+                        frame_eid = frame.last_jump_eid;
+                    }
+
+                    names.reverse();
+                    names.join(";")
+                })
+                .clone()
+        };
+
+        let mut report = ProfileReport::default();
+        let mut functions = BTreeMap::<u32, FunctionProfile>::new();
+
+        for event in self.execution_tables.etable.entries() {
+            let mentries = memory_event_of_step(event, &mut 1).len();
+            let is_host_call = matches!(
+                event.step_info,
+                StepInfo::CallHost { .. } | StepInfo::ExternalHostCall { .. }
+            );
+
+            let function = functions
+                .entry(event.fid)
+                .or_insert_with(|| FunctionProfile {
+                    fid: event.fid,
+                    function_name: name_of(event.fid),
+                    ..Default::default()
+                });
+            function.etable_entries += 1;
+            function.mtable_entries += mentries;
+            if is_host_call {
+                function.host_calls += 1;
+            }
+
+            let callee = match &event.step_info {
+                StepInfo::Call { index } => Some(*index),
+                StepInfo::CallIndirect { func_index, .. } => Some(*func_index),
+                _ => None,
+            };
+            if let Some(callee) = callee {
+                functions
+                    .entry(callee)
+                    .or_insert_with(|| FunctionProfile {
+                        fid: callee,
+                        function_name: name_of(callee),
+                        ..Default::default()
+                    })
+                    .calls += 1;
+            }
+
+            *report.stacks.entry(stack_of(event)).or_insert(0) += 1;
+
+            report.etable_entries += 1;
+            report.mtable_entries += mentries;
+            if is_host_call {
+                report.host_calls += 1;
+            }
+        }
+
+        report.functions = functions.into_values().collect();
+        report
+            .functions
+            .sort_by(|a, b| b.etable_entries.cmp(&a.etable_entries));
+
+        report
+    }
+}
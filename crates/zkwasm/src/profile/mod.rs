@@ -1,15 +1,26 @@
+use function_statistic::FunctionStatistic;
 use instruction_statistic::InstructionStatistic;
 use specs::Tables;
 
+pub use function_statistic::FunctionProfile;
+pub use function_statistic::ProfileReport;
+
+mod function_statistic;
 mod helper;
 mod instruction_statistic;
 
 pub trait Profiler {
     fn profile_tables(&self);
+    /// Attribute the etable and mtable entries to each wasm function.
+    fn profile_functions(&self) -> ProfileReport;
 }
 
 impl Profiler for Tables {
     fn profile_tables(&self) {
         self.profile_instruction();
     }
+
+    fn profile_functions(&self) -> ProfileReport {
+        FunctionStatistic::profile_functions(self)
+    }
 }
@@ -0,0 +1,115 @@
+use specs::etable::EventTable;
+use specs::etable::EventTableEntry;
+use specs::itable::InstructionTable;
+use specs::jtable::JumpTable;
+use specs::jtable::JumpTableEntry;
+use specs::step::StepInfo;
+
+use crate::loader::err::Backtrace;
+use crate::loader::err::GuestFrame;
+use crate::loader::err::GuestLocation;
+
+fn frame_of(jtable: &JumpTable, eid: u32) -> Option<&JumpTableEntry> {
+    jtable.entries().iter().find(|frame| frame.eid == eid)
+}
+
+/// Location of the instruction following `event`, i.e. the instruction being executed
+/// when the guest traps.
+pub(super) fn location_of_next_step(
+    event: &EventTableEntry,
+    jtable: &JumpTable,
+) -> Option<GuestLocation> {
+    let iid = match &event.step_info {
+        StepInfo::Br { dst_pc, .. } | StepInfo::BrTable { dst_pc, .. } => *dst_pc,
+        StepInfo::BrIfEqz {
+            condition, dst_pc, ..
+        } if *condition == 0 => *dst_pc,
+        StepInfo::BrIfNez {
+            condition, dst_pc, ..
+        } if *condition != 0 => *dst_pc,
+        StepInfo::Call { index } => {
+            return Some(GuestLocation {
+                fid: *index,
+                iid: 0,
+            })
+        }
+        StepInfo::CallIndirect { func_index, .. } => {
+            return Some(GuestLocation {
+                fid: *func_index,
+                iid: 0,
+            })
+        }
+        StepInfo::Return { .. } => {
+            let frame = frame_of(jtable, event.last_jump_eid)?;
+
+            return Some(GuestLocation {
+                fid: frame.fid,
+                iid: frame.iid + 1,
+            });
+        }
+        _ => event.iid + 1,
+    };
+
+    Some(GuestLocation {
+        fid: event.fid,
+        iid,
+    })
+}
+
+/// The eid of the call which creates the frame of the step following `event`, 0 for the entry
+/// frame.
+pub(super) fn frame_id_of_next_step(event: &EventTableEntry, jtable: &JumpTable) -> Option<u32> {
+    match &event.step_info {
+        StepInfo::Call { .. } | StepInfo::CallIndirect { .. } => Some(event.eid),
+        StepInfo::Return { .. } => Some(frame_of(jtable, event.last_jump_eid)?.last_jump_eid),
+        _ => Some(event.last_jump_eid),
+    }
+}
+
+fn symbolize(location: GuestLocation, itable: &InstructionTable) -> GuestFrame {
+    let function_name = itable
+        .iter()
+        .find(|entry| entry.fid == location.fid)
+        .map(|entry| entry.function_name.clone());
+    let instruction = itable
+        .iter()
+        .find(|entry| entry.fid == location.fid && entry.iid == location.iid)
+        .map(|entry| format!("{:?}", entry.opcode));
+
+    GuestFrame {
+        location,
+        function_name,
+        instruction,
+    }
+}
+
+/// Rebuild the guest call stack after the last traced event, `None` if no event is traced.
+pub(crate) fn backtrace_of(
+    etable: &EventTable,
+    jtable: &JumpTable,
+    itable: &InstructionTable,
+) -> Option<Backtrace> {
+    let event = etable.entries().last()?;
+
+    let mut locations = vec![location_of_next_step(event, jtable)?];
+
+    let mut frame_eid = frame_id_of_next_step(event, jtable)?;
+
+    while frame_eid != 0 {
+        let frame = frame_of(jtable, frame_eid)?;
+
+        locations.push(GuestLocation {
+            fid: frame.fid,
+            iid: frame.iid,
+        });
+
+        frame_eid = frame.last_jump_eid;
+    }
+
+    Some(Backtrace(
+        locations
+            .into_iter()
+            .map(|location| symbolize(location, itable))
+            .collect(),
+    ))
+}
@@ -1,10 +1,13 @@
+use specs::manifest::EncodedInputs;
 use std::sync::Arc;
 use std::sync::Mutex;
 
+use crate::circuits::config::CircuitConfig;
 use crate::foreign::context::runtime::register_context_foreign;
 use crate::foreign::log_helper::register_log_foreign;
 use crate::foreign::require_helper::register_require_foreign;
 use crate::foreign::wasm_input_helper::runtime::register_wasm_input_foreign;
+use crate::foreign::wasm_input_helper::source::PrivateInputSource;
 use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
 
 use super::host_env::HostEnv;
@@ -14,13 +17,24 @@ pub struct ExecutionArg {
     /// Public inputs for `wasm_input(1)`
     pub public_inputs: Vec<u64>,
     /// Private inputs for `wasm_input(0)`
-    pub private_inputs: Vec<u64>,
+    pub private_inputs: Box<dyn PrivateInputSource>,
     /// Context inputs for `wasm_read_context()`
     pub context_inputs: Vec<u64>,
     /// Context outputs for `wasm_write_context()`
     pub context_outputs: Arc<Mutex<Vec<u64>>>,
 }
 
+impl From<EncodedInputs> for ExecutionArg {
+    fn from(inputs: EncodedInputs) -> Self {
+        ExecutionArg {
+            public_inputs: inputs.public_inputs,
+            private_inputs: inputs.private_inputs.into(),
+            context_inputs: inputs.context_inputs,
+            context_outputs: Arc::new(Mutex::new(vec![])),
+        }
+    }
+}
+
 impl super::ContextOutput for ExecutionArg {
     fn get_context_outputs(&self) -> Arc<Mutex<Vec<u64>>> {
         self.context_outputs.clone()
@@ -33,7 +47,10 @@ impl HostEnvBuilder for DefaultHostEnvBuilder {
     type Arg = ExecutionArg;
     type HostConfig = ();
 
-    fn create_env_without_value(_config: Self::HostConfig) -> (HostEnv, WasmRuntimeIO) {
+    fn create_env_without_value(
+        _circuit_config: &CircuitConfig,
+        _config: Self::HostConfig,
+    ) -> (HostEnv, WasmRuntimeIO) {
         let mut env = HostEnv::new();
         let wasm_runtime_io = register_wasm_input_foreign(&mut env, vec![], vec![]);
         register_require_foreign(&mut env);
@@ -44,7 +61,11 @@ impl HostEnvBuilder for DefaultHostEnvBuilder {
         (env, wasm_runtime_io)
     }
 
-    fn create_env(arg: Self::Arg, _config: Self::HostConfig) -> (HostEnv, WasmRuntimeIO) {
+    fn create_env(
+        _circuit_config: &CircuitConfig,
+        arg: Self::Arg,
+        _config: Self::HostConfig,
+    ) -> (HostEnv, WasmRuntimeIO) {
         let mut env = HostEnv::new();
         let wasm_runtime_io =
             register_wasm_input_foreign(&mut env, arg.public_inputs, arg.private_inputs);
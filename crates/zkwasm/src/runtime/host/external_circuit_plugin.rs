@@ -2,12 +2,10 @@ use specs::external_host_call_table::ExternalHostCallSignature;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use wasmi::tracer::Observer;
 use wasmi::FuncInstance;
 use wasmi::ModuleImportResolver;
-use wasmi::RuntimeArgs;
-use wasmi::RuntimeValue;
 
+use super::ForeignCallback;
 use super::ForeignContext;
 use super::ForeignPlugin;
 use super::ForeignStatics;
@@ -17,7 +15,7 @@ pub(super) struct ForeignOp {
     pub op_index: usize,
     pub sig: ExternalHostCallSignature,
     pub plugin: Rc<ForeignPlugin>,
-    pub cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    pub cb: Rc<ForeignCallback>,
 }
 
 pub struct ExternalCircuitEnv {
@@ -52,7 +50,7 @@ impl ExternalCircuitEnv {
         op_index: usize,
         sig: ExternalHostCallSignature,
         plugin: Rc<ForeignPlugin>,
-        cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+        cb: Rc<ForeignCallback>,
     ) {
         assert!(!*self.finalized.borrow());
 
@@ -13,10 +13,15 @@ use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 use wasmi::Trap;
 
+use crate::circuits::config::MAX_K;
+use crate::circuits::config::MIN_K;
 use crate::runtime::host::HostFunctionExecutionEnv;
 
 use super::external_circuit_plugin::ExternalCircuitEnv;
 use super::internal_circuit_plugin::InternalCircuitEnv;
+use super::ForeignContext;
+use super::ForeignError;
+use super::ForeignTrap;
 use super::HostFunction;
 
 pub struct HostEnv {
@@ -72,6 +77,7 @@ impl HostEnv {
                             sig: op.sig.into(),
                         },
                         execution_env: HostFunctionExecutionEnv {
+                            plugin: op.plugin.name.clone(),
                             ctx: op.plugin.ctx.clone(),
                             cb: op.cb.clone(),
                         },
@@ -85,6 +91,8 @@ impl HostEnv {
         for (name, op) in &mut self.internal_env.functions {
             op.index = Some(internal_op_allocator_offset);
 
+            let plugin = self.internal_env.plugins.get(&op.plugin).unwrap();
+
             lookup.insert(
                 internal_op_allocator_offset,
                 HostFunction {
@@ -94,13 +102,8 @@ impl HostEnv {
                         plugin: op.plugin,
                     },
                     execution_env: HostFunctionExecutionEnv {
-                        ctx: self
-                            .internal_env
-                            .plugins
-                            .get(&op.plugin)
-                            .unwrap()
-                            .ctx
-                            .clone(),
+                        plugin: plugin.name.clone(),
+                        ctx: plugin.ctx.clone(),
                         cb: op.cb.clone(),
                     },
                 },
@@ -149,6 +152,34 @@ impl ModuleImportResolver for HostEnv {
     }
 }
 
+/// Rejects the execution as soon as the plugin exhausts the rounds of its circuit, instead of
+/// failing when the external host circuit is proved. `started_rounds` are the rounds which the
+/// pending call starts in addition to the used rounds.
+fn check_rounds(
+    plugin: &str,
+    ctx: &dyn ForeignContext,
+    started_rounds: usize,
+) -> Option<ForeignError> {
+    let statics = ctx.get_statics()?;
+    let used_round = statics.used_round + started_rounds;
+
+    if used_round <= statics.max_round {
+        return None;
+    }
+
+    let suggested_k = (MIN_K..=MAX_K).find(|k| {
+        ctx.max_rounds(*k)
+            .map_or(false, |max_round| used_round <= max_round)
+    });
+
+    Some(ForeignError::RoundsExceeded {
+        plugin: plugin.to_string(),
+        used_round,
+        max_round: statics.max_round,
+        suggested_k,
+    })
+}
+
 pub struct ExecEnv {
     pub host_env: HostEnv,
     pub tracer: Rc<RefCell<Tracer>>,
@@ -170,11 +201,30 @@ impl Externals for ExecEnv {
         {
             Some(HostFunction {
                 desc,
-                execution_env: HostFunctionExecutionEnv { ctx, cb },
+                execution_env: HostFunctionExecutionEnv { plugin, ctx, cb },
             }) => {
                 let mut ctx = (*ctx).borrow_mut();
                 let ctx = ctx.as_mut();
 
+                let trap = |error| {
+                    Trap::host(ForeignTrap {
+                        function: desc.name().to_string(),
+                        error,
+                    })
+                };
+
+                let op = match desc {
+                    HostFunctionDesc::Internal {
+                        op_index_in_plugin, ..
+                    } => *op_index_in_plugin,
+                    HostFunctionDesc::External { op, .. } => *op,
+                };
+
+                // The call is rejected before it touches the context or any side effect.
+                if let Some(error) = check_rounds(plugin, ctx, ctx.rounds_of_call(op, &args)) {
+                    return Err(trap(error));
+                }
+
                 let start = Instant::now();
                 let r = cb(&self.tracer.borrow().observer, ctx, args);
                 let duration = start.elapsed();
@@ -185,7 +235,12 @@ impl Externals for ExecEnv {
                     .and_modify(|d| *d += duration.as_millis())
                     .or_insert(duration.as_millis());
 
-                Ok(r)
+                // Plugins which don't declare the rounds of their calls are checked after the call.
+                r.and_then(|r| match check_rounds(plugin, ctx, 0) {
+                    Some(error) => Err(error),
+                    None => Ok(r),
+                })
+                .map_err(trap)
             }
             None => unreachable!(),
         }
@@ -3,12 +3,10 @@ use specs::host_function::Signature;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
-use wasmi::tracer::Observer;
 use wasmi::FuncInstance;
 use wasmi::ModuleImportResolver;
-use wasmi::RuntimeArgs;
-use wasmi::RuntimeValue;
 
+use super::ForeignCallback;
 use super::ForeignContext;
 use super::ForeignPlugin;
 
@@ -17,7 +15,7 @@ pub(super) struct ForeignOp {
     pub index_within_plugin: usize,
     pub sig: Signature,
     pub plugin: HostPlugin,
-    pub cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    pub cb: Rc<ForeignCallback>,
 }
 
 pub struct InternalCircuitEnv {
@@ -56,7 +54,7 @@ impl InternalCircuitEnv {
         sig: Signature,
         plugin: HostPlugin,
         index_within_plugin: usize,
-        cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+        cb: Rc<ForeignCallback>,
     ) {
         assert!(!*self.finalized.borrow());
 
@@ -1,14 +1,19 @@
 use self::host_env::HostEnv;
 use super::wasmi_interpreter::WasmRuntimeIO;
+use crate::circuits::config::CircuitConfig;
 use downcast_rs::impl_downcast;
 use downcast_rs::Downcast;
+use serde::Deserialize;
+use serde::Serialize;
 use specs::external_host_call_table::ExternalHostCallSignature;
 use specs::host_function::HostFunctionDesc;
 use std::cell::RefCell;
+use std::fmt::Display;
 use std::rc::Rc;
 use std::sync::Arc;
 use std::sync::Mutex;
 use wasmi::tracer::Observer;
+use wasmi::HostError;
 use wasmi::RuntimeArgs;
 use wasmi::RuntimeValue;
 use wasmi::Signature;
@@ -46,12 +51,62 @@ impl MatchForeignOpSignature for ExternalHostCallSignature {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct ForeignStatics {
     pub used_round: usize,
     pub max_round: usize,
 }
 
+/// Error returned by a foreign function, which traps the execution of the guest.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ForeignError {
+    /// `wasm_input` is invoked after all public or private inputs are consumed.
+    InputExhausted { is_public: bool },
+    /// The source of private inputs fails to read the next input.
+    InputSourceFailed(String),
+    /// The condition of `require` is false.
+    RequireFailed,
+    /// `wasm_read_context` is invoked after all context inputs are consumed.
+    ContextUnderflow,
+    /// A witness is popped from an empty witness buffer.
+    WitnessUnderflow,
+    /// The arguments of the foreign function are invalid.
+    InvalidArgument(String),
+    /// The plugin uses more rounds than its circuit supports with the current K,
+    /// `suggested_k` is the minimal K which supports the used rounds.
+    RoundsExceeded {
+        plugin: String,
+        used_round: usize,
+        max_round: usize,
+        suggested_k: Option<u32>,
+    },
+}
+
+impl Display for ForeignError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// The host error carried by the trap of a failed foreign function.
+#[derive(Debug)]
+pub(crate) struct ForeignTrap {
+    pub(crate) function: String,
+    pub(crate) error: ForeignError,
+}
+
+impl Display for ForeignTrap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "foreign function {} traps: {}",
+            self.function, self.error
+        )
+    }
+}
+
+impl HostError for ForeignTrap {}
+
 /// Context of the plugin.
 ///
 /// # Examples
@@ -69,9 +124,27 @@ pub trait ForeignContext: Downcast {
     fn get_statics(&self) -> Option<ForeignStatics> {
         None
     }
+
+    /// The maximal rounds supported by the circuit of the plugin with size `k`.
+    fn max_rounds(&self, _k: u32) -> Option<usize> {
+        None
+    }
+
+    /// The rounds started by calling the op `op` of the plugin with `args`, the budget of the
+    /// plugin is checked against them before the call.
+    fn rounds_of_call(&self, _op: usize, _args: &RuntimeArgs) -> usize {
+        0
+    }
 }
 impl_downcast!(ForeignContext);
 
+/// Callback of a foreign function, an `Err` traps the execution of the guest.
+pub type ForeignCallback = dyn Fn(
+    &Observer,
+    &mut dyn ForeignContext,
+    RuntimeArgs,
+) -> Result<Option<RuntimeValue>, ForeignError>;
+
 pub struct ForeignPlugin {
     pub name: String,
     ctx: Rc<RefCell<Box<dyn ForeignContext>>>,
@@ -79,8 +152,9 @@ pub struct ForeignPlugin {
 
 #[derive(Clone)]
 struct HostFunctionExecutionEnv {
+    plugin: String,
     ctx: Rc<RefCell<Box<dyn ForeignContext>>>,
-    cb: Rc<dyn Fn(&Observer, &mut dyn ForeignContext, RuntimeArgs) -> Option<RuntimeValue>>,
+    cb: Rc<ForeignCallback>,
 }
 
 #[derive(Clone)]
@@ -95,7 +169,14 @@ pub trait HostEnvBuilder {
     type Arg;
     type HostConfig: Default;
     /// Create an empty env without value, this is used by compiling, computing hash
-    fn create_env_without_value(config: Self::HostConfig) -> (HostEnv, WasmRuntimeIO);
+    fn create_env_without_value(
+        circuit_config: &CircuitConfig,
+        config: Self::HostConfig,
+    ) -> (HostEnv, WasmRuntimeIO);
     /// Create an env with execution parameters, this is used by dry-run, run
-    fn create_env(env: Self::Arg, config: Self::HostConfig) -> (HostEnv, WasmRuntimeIO);
+    fn create_env(
+        circuit_config: &CircuitConfig,
+        env: Self::Arg,
+        config: Self::HostConfig,
+    ) -> (HostEnv, WasmRuntimeIO);
 }
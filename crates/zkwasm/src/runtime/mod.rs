@@ -9,13 +9,16 @@ use specs::mtable::LocationType;
 use specs::mtable::MemoryTableEntry;
 use specs::mtable::VarType;
 use specs::step::StepInfo;
+use specs::trap::TrapOutcome;
 use specs::CompilationTable;
 use specs::Tables;
 
 use self::host::ForeignStatics;
 use self::wasmi_interpreter::WasmiRuntime;
 
+mod backtrace;
 pub mod host;
+mod trap;
 pub mod wasmi_interpreter;
 
 pub struct CompiledImage<I, T> {
@@ -33,6 +36,8 @@ pub struct ExecutionResult<R> {
     pub host_statics: HashMap<String, ForeignStatics>,
     pub guest_statics: usize, // total instructions used in guest circuits
     pub outputs: Vec<u64>,
+    /// The outcome if the execution terminates with a provable trap.
+    pub trap: Option<TrapOutcome>,
 }
 
 // TODO: use feature
@@ -358,6 +363,7 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             ops
         }
         StepInfo::Drop { .. } => vec![],
+        StepInfo::Unreachable => vec![],
         StepInfo::Select {
             val1,
             val2,
@@ -678,6 +684,44 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             vec![stack_read, global_set]
         }
 
+        // A trapping step only reads its operands.
+        StepInfo::Load { raw_address, .. } if event.trap_code().is_some() => {
+            mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*raw_address as u64],
+                &[],
+            )
+        }
+        StepInfo::Store {
+            vtype,
+            raw_address,
+            value,
+            ..
+        } if event.trap_code().is_some() => vec![
+            mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                *vtype,
+                *vtype,
+                &[*value],
+                &[],
+            ),
+            mem_op_from_stack_only_step(
+                sp_before_execution + 1,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*raw_address as u64],
+                &[],
+            ),
+        ]
+        .concat(),
         StepInfo::Load {
             vtype,
             load_size,
@@ -887,6 +931,17 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             &[],
             &[*value as u32 as u64],
         ),
+        StepInfo::I32BinOp { left, right, .. } if event.trap_code().is_some() => {
+            mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I32,
+                VarType::I32,
+                &[*right as u32 as u64, *left as u32 as u64],
+                &[],
+            )
+        }
         StepInfo::I32BinOp {
             left, right, value, ..
         }
@@ -916,6 +971,17 @@ pub fn memory_event_of_step(event: &EventTableEntry, emid: &mut u32) -> Vec<Memo
             &[*value as u32 as u64],
         ),
 
+        StepInfo::I64BinOp { left, right, .. } if event.trap_code().is_some() => {
+            mem_op_from_stack_only_step(
+                sp_before_execution,
+                eid,
+                emid,
+                VarType::I64,
+                VarType::I64,
+                &[*right as u64, *left as u64],
+                &[],
+            )
+        }
         StepInfo::I64BinOp {
             left, right, value, ..
         }
@@ -0,0 +1,249 @@
+use log::warn;
+use specs::etable::EventTable;
+use specs::etable::EventTableEntry;
+use specs::external_host_call_table::ExternalHostCallSignature;
+use specs::host_function::HostPlugin;
+use specs::host_function::Signature;
+use specs::itable::BinOp;
+use specs::itable::Opcode;
+use specs::jtable::JumpTable;
+use specs::mtable::LocationType;
+use specs::mtable::VarType;
+use specs::step::StepInfo;
+use specs::trap::TrapOutcome;
+use specs::types::ValueType;
+use specs::CompilationTable;
+use wasmi::DEFAULT_VALUE_STACK_LIMIT;
+
+use super::backtrace::frame_id_of_next_step;
+use super::backtrace::location_of_next_step;
+use super::memory_event_of_step;
+
+/// Stack pointer after the execution of `event`.
+fn sp_of_next_step(event: &EventTableEntry) -> u32 {
+    let sp = event.sp;
+
+    match &event.step_info {
+        StepInfo::Br { drop, .. } | StepInfo::Return { drop, .. } => sp + drop,
+        StepInfo::BrIfEqz {
+            condition, drop, ..
+        } => sp + 1 + if *condition == 0 { *drop } else { 0 },
+        StepInfo::BrIfNez {
+            condition, drop, ..
+        } => sp + 1 + if *condition != 0 { *drop } else { 0 },
+        StepInfo::BrTable { drop, .. } => sp + 1 + drop,
+
+        StepInfo::Drop => sp + 1,
+        StepInfo::Select { .. } => sp + 2,
+        StepInfo::CallIndirect { .. } => sp + 1,
+        StepInfo::CallHost { args, ret_val, .. } => {
+            sp + args.len() as u32 - ret_val.is_some() as u32
+        }
+        StepInfo::ExternalHostCall { sig, .. } => match sig {
+            ExternalHostCallSignature::Argument => sp + 1,
+            ExternalHostCallSignature::Return => sp - 1,
+        },
+
+        StepInfo::GetLocal { .. } | StepInfo::GetGlobal { .. } => sp - 1,
+        StepInfo::SetLocal { .. } | StepInfo::SetGlobal { .. } => sp + 1,
+
+        StepInfo::Store { .. } => sp + 2,
+        StepInfo::MemorySize => sp - 1,
+
+        StepInfo::I32Const { .. } | StepInfo::I64Const { .. } => sp - 1,
+        StepInfo::I32BinOp { .. }
+        | StepInfo::I32BinShiftOp { .. }
+        | StepInfo::I32BinBitOp { .. }
+        | StepInfo::I64BinOp { .. }
+        | StepInfo::I64BinShiftOp { .. }
+        | StepInfo::I64BinBitOp { .. }
+        | StepInfo::I32Comp { .. }
+        | StepInfo::I64Comp { .. } => sp + 1,
+
+        _ => sp,
+    }
+}
+
+/// Value of the stack slot at `offset`, which is the value of its last access.
+fn stack_value(etable: &EventTable, offset: u32) -> Option<u64> {
+    etable.entries().iter().rev().find_map(|event| {
+        memory_event_of_step(event, &mut 1)
+            .into_iter()
+            .rev()
+            .find(|entry| entry.ltype == LocationType::Stack && entry.offset == offset)
+            .map(|entry| entry.value)
+    })
+}
+
+/// Effective address of a memory access, `None` if it overflows `u32`. Such an access is out of
+/// bounds of any memory, but the trap is not provable since the step only keeps a `u32`
+/// effective address.
+fn effective_address(raw_address: u32, offset: u32) -> Option<u32> {
+    match raw_address.checked_add(offset) {
+        Some(effective_address) => Some(effective_address),
+        None => {
+            warn!(
+                "The effective address of the memory access overflows: {} + {}, the trap is not provable.",
+                raw_address, offset
+            );
+
+            None
+        }
+    }
+}
+
+/// The tracer doesn't record the step raising a trap, rebuild it from the state after the last
+/// traced event. `None` if the trap is not provable.
+fn trap_step(
+    etable: &EventTable,
+    jtable: &JumpTable,
+    tables: &CompilationTable,
+) -> Option<EventTableEntry> {
+    let (eid, fid, iid, sp, last_jump_eid, allocated_memory_pages) = match etable.entries().last() {
+        Some(event) => {
+            let location = location_of_next_step(event, jtable)?;
+            let allocated_memory_pages = match &event.step_info {
+                StepInfo::MemoryGrow { grow_size, result } if *result != -1 => {
+                    event.allocated_memory_pages + *grow_size as u32
+                }
+                _ => event.allocated_memory_pages,
+            };
+
+            (
+                event.eid + 1,
+                location.fid,
+                location.iid,
+                sp_of_next_step(event),
+                frame_id_of_next_step(event, jtable)?,
+                allocated_memory_pages,
+            )
+        }
+        None => (
+            1,
+            tables.fid_of_entry,
+            0,
+            DEFAULT_VALUE_STACK_LIMIT as u32 - 1,
+            0,
+            tables.configure_table.init_memory_pages,
+        ),
+    };
+
+    let stack = |depth: u32| stack_value(etable, sp + depth);
+
+    let step_info = match &tables.itable.get(fid, iid).as_ref()?.opcode {
+        Opcode::Unreachable => StepInfo::Unreachable,
+        Opcode::Bin {
+            class:
+                class @ (BinOp::UnsignedDiv | BinOp::UnsignedRem | BinOp::SignedDiv | BinOp::SignedRem),
+            vtype,
+        } => {
+            let right = stack(1)?;
+            let left = stack(2)?;
+
+            match vtype {
+                VarType::I32 => StepInfo::I32BinOp {
+                    class: *class,
+                    left: left as i32,
+                    right: right as i32,
+                    value: 0,
+                },
+                VarType::I64 => StepInfo::I64BinOp {
+                    class: *class,
+                    left: left as i64,
+                    right: right as i64,
+                    value: 0,
+                },
+            }
+        }
+        Opcode::Load {
+            offset,
+            vtype,
+            size,
+        } => {
+            let raw_address = stack(1)? as u32;
+
+            StepInfo::Load {
+                vtype: *vtype,
+                load_size: *size,
+                offset: *offset,
+                raw_address,
+                effective_address: effective_address(raw_address, *offset)?,
+                value: 0,
+                block_value1: 0,
+                block_value2: 0,
+            }
+        }
+        Opcode::Store {
+            offset,
+            vtype,
+            size,
+        } => {
+            let value = stack(1)?;
+            let raw_address = stack(2)? as u32;
+
+            StepInfo::Store {
+                vtype: *vtype,
+                store_size: *size,
+                offset: *offset,
+                raw_address,
+                effective_address: effective_address(raw_address, *offset)?,
+                pre_block_value1: 0,
+                updated_block_value1: 0,
+                pre_block_value2: 0,
+                updated_block_value2: 0,
+                value,
+            }
+        }
+        Opcode::InternalHostCall {
+            plugin: HostPlugin::Require,
+            function_index,
+            function_name,
+            op_index_in_plugin,
+        } => StepInfo::CallHost {
+            plugin: HostPlugin::Require,
+            host_function_idx: *function_index,
+            function_name: function_name.clone(),
+            signature: Signature {
+                params: vec![ValueType::I32],
+                return_type: None,
+            },
+            args: vec![stack(1)?],
+            ret_val: None,
+            op_index_in_plugin: *op_index_in_plugin,
+        },
+        _ => return None,
+    };
+
+    Some(EventTableEntry {
+        eid,
+        fid,
+        iid,
+        sp,
+        allocated_memory_pages,
+        last_jump_eid,
+        step_info,
+    })
+    .filter(|step| step.trap_code().is_some())
+}
+
+/// Terminate the event table with the step raising the trap, `None` if the trap is not
+/// provable.
+pub(crate) fn terminate_with_trap(
+    etable: &mut EventTable,
+    jtable: &JumpTable,
+    tables: &CompilationTable,
+) -> Option<TrapOutcome> {
+    if let Some(outcome) = etable
+        .entries()
+        .last()
+        .and_then(|event| event.trap_outcome())
+    {
+        return Some(outcome);
+    }
+
+    let step = trap_step(etable, jtable, tables)?;
+    let outcome = step.trap_outcome();
+    etable.entries_mut().push(step);
+
+    outcome
+}
@@ -2,10 +2,14 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 
-use crate::circuits::config::zkwasm_k;
+use crate::circuits::config::CircuitConfig;
+use crate::loader::err::Error;
+use crate::loader::err::RuntimeErr;
 use crate::runtime::memory_event_of_step;
+use anyhow::anyhow;
 use anyhow::Result;
 use specs::host_function::HostFunctionDesc;
+use specs::itable::InstructionTable;
 use specs::jtable::StaticFrameEntry;
 use specs::mtable::MTable;
 use specs::CompilationTable;
@@ -15,8 +19,11 @@ use wasmi::ImportResolver;
 use wasmi::ModuleInstance;
 use wasmi::RuntimeValue;
 
+use super::backtrace::backtrace_of;
 use super::host::host_env::ExecEnv;
 use super::host::host_env::HostEnv;
+use super::host::ForeignTrap;
+use super::trap::terminate_with_trap;
 use super::CompiledImage;
 use super::ExecutionResult;
 
@@ -34,11 +41,42 @@ impl WasmRuntimeIO {
     }
 }
 
+fn runtime_error(
+    error: wasmi::Error,
+    tracer: &wasmi::tracer::Tracer,
+    itable: &InstructionTable,
+) -> anyhow::Error {
+    let backtrace = backtrace_of(&tracer.etable, &tracer.jtable, itable);
+
+    if let Some(ForeignTrap {
+        function,
+        error: foreign_error,
+    }) = error
+        .as_host_error()
+        .and_then(|host_error| host_error.downcast_ref::<ForeignTrap>())
+    {
+        return anyhow!(Error::Runtime(RuntimeErr::ForeignTrap {
+            function: function.clone(),
+            error: foreign_error.clone(),
+            backtrace,
+        }));
+    }
+
+    match error {
+        wasmi::Error::Trap(trap) => anyhow!(Error::Runtime(RuntimeErr::Trap {
+            trap: trap.to_string(),
+            backtrace,
+        })),
+        _ => error.into(),
+    }
+}
+
 pub trait Execution<R> {
     fn run(
         self,
         externals: HostEnv,
         dryrun: bool,
+        provable_trap: bool,
         wasm_io: WasmRuntimeIO,
     ) -> Result<ExecutionResult<R>>;
 }
@@ -50,19 +88,44 @@ impl Execution<RuntimeValue>
         self,
         externals: HostEnv,
         dryrun: bool,
+        provable_trap: bool,
         wasm_io: WasmRuntimeIO,
     ) -> Result<ExecutionResult<RuntimeValue>> {
         let mut exec_env = ExecEnv {
             host_env: externals,
             tracer: self.tracer.clone(),
         };
-        let instance = self
+        // Events are not traced in dry-run mode, hence a trap is only provable with tracing.
+        let terminate = |error: wasmi::Error| {
+            let mut tracer = self.tracer.borrow_mut();
+            let tracer = &mut *tracer;
+
+            let outcome = if provable_trap && !dryrun {
+                terminate_with_trap(&mut tracer.etable, &tracer.jtable, &self.tables)
+            } else {
+                None
+            };
+
+            outcome.ok_or_else(|| runtime_error(error, tracer, &self.tables.itable))
+        };
+
+        let (result, trap) = match self
             .instance
             .run_start_tracer(&mut exec_env, self.tracer.clone())
-            .unwrap();
-
-        let result =
-            instance.invoke_export_trace(&self.entry, &[], &mut exec_env, self.tracer.clone())?;
+        {
+            Ok(instance) => {
+                match instance.invoke_export_trace(
+                    &self.entry,
+                    &[],
+                    &mut exec_env,
+                    self.tracer.clone(),
+                ) {
+                    Ok(result) => (result, None),
+                    Err(error) => (None, Some(terminate(error)?)),
+                }
+            }
+            Err(trap) => (None, Some(terminate(wasmi::Error::from(trap))?)),
+        };
 
         let execution_tables = if !dryrun {
             let tracer = self.tracer.borrow();
@@ -98,6 +161,7 @@ impl Execution<RuntimeValue>
             guest_statics: self.tracer.borrow().observer.counter,
             public_inputs_and_outputs: wasm_io.public_inputs_and_outputs.borrow().clone(),
             outputs: wasm_io.outputs.borrow().clone(),
+            trap,
         })
     }
 }
@@ -109,14 +173,16 @@ impl WasmiRuntime {
         WasmiRuntime
     }
 
-    pub fn compile<'a, I: ImportResolver>(
+    fn instantiate<'a, I: ImportResolver>(
         module: &'a wasmi::Module,
         imports: &I,
         host_plugin_lookup: &HashMap<usize, HostFunctionDesc>,
-        entry: &str,
         dry_run: bool,
         phantom_functions: &Vec<String>,
-    ) -> Result<CompiledImage<wasmi::NotStartedModuleRef<'a>, wasmi::tracer::Tracer>> {
+    ) -> (
+        wasmi::NotStartedModuleRef<'a>,
+        Rc<RefCell<wasmi::tracer::Tracer>>,
+    ) {
         let tracer =
             wasmi::tracer::Tracer::new(host_plugin_lookup.clone(), phantom_functions, dry_run);
         let tracer = Rc::new(RefCell::new(tracer));
@@ -124,6 +190,53 @@ impl WasmiRuntime {
         let instance = ModuleInstance::new(&module, imports, Some(tracer.clone()))
             .expect("failed to instantiate wasm module");
 
+        (instance, tracer)
+    }
+
+    /// Instantiates the module with the compilation tables of a previous `compile` of the
+    /// same module and circuit config, the tables are reused instead of being rebuilt.
+    pub fn compile_with_tables<'a, I: ImportResolver>(
+        module: &'a wasmi::Module,
+        imports: &I,
+        host_plugin_lookup: &HashMap<usize, HostFunctionDesc>,
+        entry: &str,
+        dry_run: bool,
+        phantom_functions: &Vec<String>,
+        tables: &CompilationTable,
+    ) -> Result<CompiledImage<wasmi::NotStartedModuleRef<'a>, wasmi::tracer::Tracer>> {
+        let (instance, tracer) = Self::instantiate(
+            module,
+            imports,
+            host_plugin_lookup,
+            dry_run,
+            phantom_functions,
+        );
+
+        Ok(CompiledImage {
+            entry: entry.to_owned(),
+            tables: tables.clone(),
+            instance,
+            tracer,
+        })
+    }
+
+    pub fn compile<'a, I: ImportResolver>(
+        module: &'a wasmi::Module,
+        imports: &I,
+        host_plugin_lookup: &HashMap<usize, HostFunctionDesc>,
+        entry: &str,
+        dry_run: bool,
+        phantom_functions: &Vec<String>,
+        circuit_config: &CircuitConfig,
+    ) -> Result<CompiledImage<wasmi::NotStartedModuleRef<'a>, wasmi::tracer::Tracer>> {
+        let (instance, tracer) = Self::instantiate(
+            module,
+            imports,
+            host_plugin_lookup,
+            dry_run,
+            phantom_functions,
+        );
+
         let fid_of_entry = {
             let idx_of_entry = instance.lookup_function_by_name(tracer.clone(), entry);
 
@@ -163,7 +276,7 @@ impl WasmiRuntime {
         };
 
         let itable = tracer.borrow().itable.clone().into();
-        let imtable = tracer.borrow().imtable.finalized(zkwasm_k());
+        let imtable = tracer.borrow().imtable.finalized(circuit_config.k());
         let elem_table = tracer.borrow().elem_table.clone();
         let configure_table = tracer.borrow().configure_table.clone();
         let static_jtable = tracer.borrow().static_jtable_entries.clone();
@@ -1,4 +1,4 @@
-use crate::circuits::config::zkwasm_k;
+use crate::circuits::config::CircuitConfig;
 use crate::circuits::utils::table_entry::MemoryWritingTable;
 use crate::circuits::ZkWasmCircuit;
 use crate::profile::Profiler;
@@ -21,8 +21,17 @@ use wasmi::RuntimeValue;
 mod test_wasm_instructions;
 
 mod spec;
+mod test_estimate;
+mod test_input_manifest;
+mod test_precheck;
+mod test_profile;
 mod test_rlp;
+mod test_runtime_error;
+mod test_session;
+mod test_softfloat;
 mod test_start;
+mod test_trace;
+mod test_trap;
 #[cfg(feature = "uniform-circuit")]
 mod test_uniform_verifier;
 
@@ -44,19 +53,19 @@ fn test_circuit_mock<F: FieldExt>(
         v
     };
 
+    let circuit_config = CircuitConfig::default();
+
     execution_result.tables.write_json(None);
-    let memory_writing_table: MemoryWritingTable = execution_result
-        .tables
-        .execution_tables
-        .mtable
-        .clone()
-        .into();
+    let memory_writing_table = MemoryWritingTable::new(
+        execution_result.tables.execution_tables.mtable.clone(),
+        circuit_config.maximal_eid(),
+    );
     memory_writing_table.write_json(None);
 
     execution_result.tables.profile_tables();
 
-    let circuit = ZkWasmCircuit::new(execution_result.tables);
-    let prover = MockProver::run(zkwasm_k(), &circuit, vec![instance])?;
+    let circuit = ZkWasmCircuit::new(circuit_config, execution_result.tables);
+    let prover = MockProver::run(circuit_config.k(), &circuit, vec![instance])?;
     assert_eq!(prover.verify(), Ok(()));
 
     Ok(())
@@ -80,10 +89,11 @@ fn compile_then_execute_wasm(
         function_name,
         false,
         &vec![],
+        &CircuitConfig::default(),
     )
     .unwrap();
 
-    let execution_result = compiled_module.run(env, false, wasm_runtime_io)?;
+    let execution_result = compiled_module.run(env, false, false, wasm_runtime_io)?;
 
     Ok(execution_result)
 }
@@ -1,94 +1,609 @@
-/*
-    use wast::Error;
-
-    #[cfg(test)]
-    fn run_spec_test(_file_name: &str) -> Result<(), Error> {
-        todo!();
-
-        /*
-        let path = format!("src/test/spec/{}.wast", file_name);
-        let file = fs::read_to_string(&path).unwrap();
-
-        let mut lexer = Lexer::new(&file);
-        lexer.allow_confusing_unicode(true);
-        let parse_buffer = ParseBuffer::new_with_lexer(lexer)?;
-
-        let wast: Wast = wast::parser::parse(&parse_buffer)?;
-        let imports = ImportsBuilder::default();
-        let mut externals = NopExternals;
-
-        let compiler = WasmInterpreter::new();
-        let mut compile_outcome = None;
-
-        for directive in wast.directives {
-            match directive {
-                wast::WastDirective::Wat(wat) => match wat {
-                    wast::QuoteWat::Wat(wat) => match wat {
-                        wast::Wat::Module(module) => {
-                            let compiled = compiler
-                                .compile_from_wast(module, &imports, &HashMap::default())
-                                .unwrap();
-                            compile_outcome = Some(compiled);
-                        }
-                        wast::Wat::Component(_) => todo!(),
-                    },
-                    wast::QuoteWat::QuoteModule(_, _) => todo!(),
-                    wast::QuoteWat::QuoteComponent(_, _) => todo!(),
-                },
-                wast::WastDirective::AssertMalformed { .. } => todo!(),
-                wast::WastDirective::AssertInvalid { .. } => todo!(),
-                wast::WastDirective::Register { .. } => todo!(),
-                wast::WastDirective::Invoke(..) => todo!(),
-                wast::WastDirective::AssertTrap { .. } => todo!(),
-                wast::WastDirective::AssertReturn {
-                    span: _span,
-                    exec,
-                    results: _results,
-                } => {
-                    let from_wastarg = |arg: &WastArg| match arg {
-                        WastArg::Core(core) => match core {
-                            wast::core::WastArgCore::I32(v) => Value::I32(*v),
-                            wast::core::WastArgCore::I64(v) => Value::I64(*v),
-                            wast::core::WastArgCore::F32(_) => todo!(),
-                            wast::core::WastArgCore::F64(_) => todo!(),
-                            wast::core::WastArgCore::V128(_) => todo!(),
-                            wast::core::WastArgCore::RefNull(_) => todo!(),
-                            wast::core::WastArgCore::RefExtern(_) => todo!(),
-                        },
-                        WastArg::Component(_) => todo!(),
-                    };
-
-                    let _actual_results = match exec {
-                        wast::WastExecute::Invoke(invoke) => compiler.run(
-                            &mut externals,
-                            &compile_outcome.unwrap(),
-                            invoke.name,
-                            invoke.args.iter().map(|arg| from_wastarg(arg)).collect(),
-                        ),
-                        wast::WastExecute::Wat(_) => todo!(),
-                        wast::WastExecute::Get { .. } => todo!(),
-                    }
-                    .unwrap();
+//! Runner of the `.wast` scripts of the WebAssembly spec test suite.
+//!
+//! Each invocation is executed by calling an entry appended to the module, since the entry
+//! of zkWasm doesn't take arguments. Modules of integer instructions are also proved by the
+//! mock prover if required. Every directive is reported as passed, failed or unsupported,
+//! unsupported directives are skipped rather than counted as passed.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::panic::catch_unwind;
+use std::panic::AssertUnwindSafe;
+
+use anyhow::Result;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::pairing::bn256::Fr;
+use parity_wasm::elements::ExportEntry;
+use parity_wasm::elements::Func;
+use parity_wasm::elements::FuncBody;
+use parity_wasm::elements::FunctionType;
+use parity_wasm::elements::Instruction;
+use parity_wasm::elements::Instructions;
+use parity_wasm::elements::Internal;
+use parity_wasm::elements::Module;
+use parity_wasm::elements::Type;
+use wabt::script::Action;
+use wabt::script::CommandKind;
+use wabt::script::ScriptParser;
+use wabt::script::Value;
+use wabt::Features;
+use wasmi::ImportsBuilder;
+use wasmi::RuntimeValue;
+
+use crate::circuits::config::CircuitConfig;
+use crate::circuits::ZkWasmCircuit;
+use crate::loader::check::check_instructions;
+use crate::loader::check::check_value_types;
+use crate::loader::check::imported_functions;
+use crate::loader::err::Error;
+use crate::loader::err::RuntimeErr;
+use crate::runtime::host::host_env::HostEnv;
+use crate::runtime::wasmi_interpreter::Execution;
+use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+use crate::runtime::ExecutionResult;
+use crate::runtime::WasmInterpreter;
+
+const ENTRY: &str = "zkwasm_spec_entry";
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Outcome {
+    Pass,
+    Fail(String),
+    Unsupported(String),
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Pass => write!(f, "pass"),
+            Outcome::Fail(reason) => write!(f, "fail: {}", reason),
+            Outcome::Unsupported(reason) => write!(f, "unsupported: {}", reason),
+        }
+    }
+}
+
+pub(crate) struct DirectiveReport {
+    pub(crate) line: u64,
+    pub(crate) directive: &'static str,
+    pub(crate) interpreter: Outcome,
+    /// `None` if the mock prover is disabled or the directive is not executed successfully.
+    pub(crate) mock_prover: Option<Outcome>,
+}
+
+pub(crate) struct SpecReport {
+    pub(crate) name: String,
+    pub(crate) directives: Vec<DirectiveReport>,
+}
+
+impl SpecReport {
+    pub(crate) fn failures(&self) -> Vec<&DirectiveReport> {
+        self.directives
+            .iter()
+            .filter(|report| {
+                matches!(report.interpreter, Outcome::Fail(_))
+                    || matches!(report.mock_prover, Some(Outcome::Fail(_)))
+            })
+            .collect()
+    }
+
+    pub(crate) fn count(&self, outcome: fn(&Outcome) -> bool) -> usize {
+        self.directives
+            .iter()
+            .filter(|report| outcome(&report.interpreter))
+            .count()
+    }
+}
+
+impl Display for SpecReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for report in &self.directives {
+            if report.interpreter != Outcome::Pass {
+                writeln!(
+                    f,
+                    "{}:{} {} {}",
+                    self.name, report.line, report.directive, report.interpreter
+                )?;
+            }
+
+            if let Some(mock_prover) = &report.mock_prover {
+                if *mock_prover != Outcome::Pass {
+                    writeln!(
+                        f,
+                        "{}:{} {} mock prover {}",
+                        self.name, report.line, report.directive, mock_prover
+                    )?;
+                }
+            }
+        }
+
+        write!(
+            f,
+            "{}: {} passed, {} failed, {} unsupported",
+            self.name,
+            self.count(|outcome| *outcome == Outcome::Pass),
+            self.count(|outcome| matches!(outcome, Outcome::Fail(_))),
+            self.count(|outcome| matches!(outcome, Outcome::Unsupported(_))),
+        )
+    }
+}
+
+/// A module of the script, `unsupported` is the reason why its invocations are not executed.
+#[derive(Clone)]
+struct SpecModule {
+    module: Module,
+    unsupported: Option<String>,
+    integer_only: bool,
+}
+
+impl SpecModule {
+    fn new(wasm: Vec<u8>) -> Result<Self, String> {
+        // Validate the module before executing it, parity_wasm doesn't validate.
+        wasmi::Module::from_buffer(&wasm).map_err(|e| e.to_string())?;
+        let module = parity_wasm::deserialize_buffer::<Module>(&wasm).map_err(|e| e.to_string())?;
+
+        let unsupported = if module
+            .import_section()
+            .map_or(false, |section| !section.entries().is_empty())
+        {
+            Some("imports of the spectest module".to_owned())
+        } else {
+            None
+        };
+
+        let integer_only =
+            check_value_types(&module).is_empty() && check_instructions(&module).is_empty();
+
+        Ok(SpecModule {
+            module,
+            unsupported,
+            integer_only,
+        })
+    }
+
+    /// Appends `ENTRY` calling the export `field` with `args`.
+    fn with_entry(&self, field: &str, args: &[Value]) -> Result<Module, Outcome> {
+        let mut module = self.module.clone();
+
+        let fid = module
+            .export_section()
+            .and_then(|section| {
+                section
+                    .entries()
+                    .iter()
+                    .find(|export| export.field() == field)
+            })
+            .and_then(|export| match export.internal() {
+                Internal::Function(fid) => Some(*fid),
+                _ => None,
+            })
+            .ok_or_else(|| Outcome::Fail(format!("function {} is not exported", field)))?;
+
+        let type_ref = module.function_section().unwrap().entries()
+            [(fid - imported_functions(&module)) as usize]
+            .type_ref();
+        let Type::Function(func_type) =
+            module.type_section().unwrap().types()[type_ref as usize].clone();
+
+        let mut instructions = args
+            .iter()
+            .map(|arg| match arg {
+                Value::I32(v) => Ok(Instruction::I32Const(*v)),
+                Value::I64(v) => Ok(Instruction::I64Const(*v)),
+                _ => Err(Outcome::Unsupported(format!("argument {:?}", arg))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        instructions.push(Instruction::Call(fid));
+        instructions.push(Instruction::End);
+
+        let entry_type = module.type_section().unwrap().types().len() as u32;
+        let entry =
+            imported_functions(&module) + module.function_section().unwrap().entries().len() as u32;
+
+        module
+            .type_section_mut()
+            .unwrap()
+            .types_mut()
+            .push(Type::Function(FunctionType::new(
+                vec![],
+                func_type.results().to_vec(),
+            )));
+        module
+            .function_section_mut()
+            .unwrap()
+            .entries_mut()
+            .push(Func::new(entry_type));
+        module
+            .code_section_mut()
+            .unwrap()
+            .bodies_mut()
+            .push(FuncBody::new(vec![], Instructions::new(instructions)));
+        module
+            .export_section_mut()
+            .unwrap()
+            .entries_mut()
+            .push(ExportEntry::new(
+                ENTRY.to_owned(),
+                Internal::Function(entry),
+            ));
+
+        Ok(module)
+    }
+}
+
+fn execute(module: Module) -> Result<ExecutionResult<RuntimeValue>> {
+    let module = wasmi::Module::from_parity_module(module)?;
+
+    let mut env = HostEnv::new();
+    env.finalize();
+    let imports = ImportsBuilder::new().with_resolver("env", &env);
+
+    let compiled_module = WasmInterpreter::compile(
+        &module,
+        &imports,
+        &env.function_description_table(),
+        ENTRY,
+        false,
+        &vec![],
+        &CircuitConfig::default(),
+    )?;
+
+    compiled_module.run(env, false, false, WasmRuntimeIO::empty())
+}
+
+fn mock_prove(execution_result: ExecutionResult<RuntimeValue>) -> Outcome {
+    let instances = execution_result
+        .public_inputs_and_outputs
+        .iter()
+        .map(|v| Fr::from(*v))
+        .collect();
+
+    let circuit_config = CircuitConfig::default();
+    let circuit = ZkWasmCircuit::<Fr>::new(circuit_config, execution_result.tables);
+
+    match MockProver::run(circuit_config.k(), &circuit, vec![instances]) {
+        Ok(prover) => match prover.verify() {
+            Ok(()) => Outcome::Pass,
+            Err(failures) => {
+                Outcome::Fail(format!("{} constraints are not satisfied", failures.len()))
+            }
+        },
+        Err(e) => Outcome::Fail(format!("{:?}", e)),
+    }
+}
+
+fn to_runtime_value(value: &Value) -> Option<RuntimeValue> {
+    match value {
+        Value::I32(v) => Some(RuntimeValue::I32(*v)),
+        Value::I64(v) => Some(RuntimeValue::I64(*v)),
+        _ => None,
+    }
+}
+
+/// Kinds of the wasmi trap raised for the trap message of the spec.
+fn trap_kinds(message: &str) -> Option<&'static [&'static str]> {
+    let kinds: &'static [&'static str] = match message {
+        "unreachable" => &["Unreachable"],
+        "out of bounds memory access" => &["MemoryAccessOutOfBounds"],
+        "integer divide by zero" => &["DivisionByZero"],
+        "integer overflow" | "invalid conversion to integer" => &["InvalidConversionToInt"],
+        "call stack exhausted" => &["StackOverflow"],
+        "indirect call type mismatch" => &["UnexpectedSignature"],
+        _ if message.starts_with("undefined element")
+            || message.starts_with("uninitialized element") =>
+        {
+            &["TableAccessOutOfBounds", "ElemUninitialized"]
+        }
+        _ => return None,
+    };
+
+    Some(kinds)
+}
+
+fn catch_panic<T>(f: impl FnOnce() -> Result<T, Outcome>) -> Result<T, Outcome> {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|panic| {
+        let message = panic
+            .downcast_ref::<String>()
+            .cloned()
+            .or_else(|| panic.downcast_ref::<&str>().map(|s| s.to_string()))
+            .unwrap_or_default();
+
+        Err(Outcome::Fail(format!("panicked: {}", message)))
+    })
+}
+
+struct SpecRunner {
+    mock: bool,
+    modules: HashMap<String, SpecModule>,
+    current: Option<SpecModule>,
+}
+
+impl SpecRunner {
+    fn module(&self, name: &Option<String>) -> Result<&SpecModule, Outcome> {
+        let module = match name {
+            Some(name) => self.modules.get(name),
+            None => self.current.as_ref(),
+        };
+
+        let module = module.ok_or_else(|| Outcome::Fail("no module is defined".to_owned()))?;
+        match &module.unsupported {
+            Some(reason) => Err(Outcome::Unsupported(reason.clone())),
+            None => Ok(module),
+        }
+    }
+
+    /// Executes the action, the mock prover runs if the action returns and the module only
+    /// contains integer instructions.
+    fn invoke(
+        &self,
+        action: &Action,
+    ) -> Result<(Result<ExecutionResult<RuntimeValue>>, bool), Outcome> {
+        match action {
+            Action::Invoke {
+                module,
+                field,
+                args,
+            } => {
+                let module = self.module(module)?;
+                if !module.integer_only {
+                    return Err(Outcome::Unsupported("float instructions".to_owned()));
+                }
+
+                let entry_module = module.with_entry(field, args)?;
+                let result = catch_panic(|| Ok(execute(entry_module)))?;
+
+                Ok((result, self.mock))
+            }
+            Action::Get { .. } => Err(Outcome::Unsupported("get of globals".to_owned())),
+        }
+    }
+
+    fn assert_return(&self, action: &Action, expected: &Vec<Value>) -> (Outcome, Option<Outcome>) {
+        let expected = match expected
+            .iter()
+            .map(to_runtime_value)
+            .collect::<Option<Vec<_>>>()
+        {
+            Some(expected) => expected,
+            None => {
+                return (
+                    Outcome::Unsupported(format!("expected {:?}", expected)),
+                    None,
+                )
+            }
+        };
 
-                    todo!()
+        let (result, mock) = match self.invoke(action) {
+            Ok(result) => result,
+            Err(outcome) => return (outcome, None),
+        };
+
+        match result {
+            Ok(execution_result) => {
+                let actual = execution_result.result.into_iter().collect::<Vec<_>>();
+                if actual != expected {
+                    return (
+                        Outcome::Fail(format!("expected {:?}, got {:?}", expected, actual)),
+                        None,
+                    );
                 }
-                wast::WastDirective::AssertExhaustion { .. } => todo!(),
-                wast::WastDirective::AssertUnlinkable { .. } => todo!(),
-                wast::WastDirective::AssertException { .. } => todo!(),
+
+                let mock_prover = if mock {
+                    Some(
+                        catch_panic(|| Ok(mock_prove(execution_result)))
+                            .unwrap_or_else(|outcome| outcome),
+                    )
+                } else {
+                    None
+                };
+
+                (Outcome::Pass, mock_prover)
             }
+            Err(e) => (Outcome::Fail(format!("unexpected error {}", e)), None),
         }
-        Ok(())
-        */
     }
 
-    /*
-    mod tests {
-        use super::run_spec_test;
+    /// The action must trap with the kind of `message`.
+    fn assert_trap(&self, action: &Action, message: &str) -> Outcome {
+        let kinds = match trap_kinds(message) {
+            Some(kinds) => kinds,
+            None => return Outcome::Unsupported(format!("trap {:?}", message)),
+        };
+
+        let error = match self.invoke(action) {
+            Ok((Ok(_), _)) => return Outcome::Fail(format!("expected trap {:?}", message)),
+            Ok((Err(error), _)) => error,
+            Err(outcome) => return outcome,
+        };
+
+        match error.downcast_ref::<Error>() {
+            Some(Error::Runtime(RuntimeErr::Trap { trap, .. }))
+                if kinds.iter().any(|kind| trap.contains(kind)) =>
+            {
+                Outcome::Pass
+            }
+            _ => Outcome::Fail(format!("expected trap {:?}, got {}", message, error)),
+        }
+    }
+
+    fn run(&mut self, kind: CommandKind) -> (&'static str, Outcome, Option<Outcome>) {
+        match kind {
+            CommandKind::Module { module, name } => match SpecModule::new(module.into_vec()) {
+                Ok(module) => {
+                    if let Some(name) = name {
+                        self.modules.insert(name, module.clone());
+                    }
+                    self.current = Some(module);
+
+                    ("module", Outcome::Pass, None)
+                }
+                Err(e) => {
+                    self.current = None;
+
+                    ("module", Outcome::Fail(e), None)
+                }
+            },
+            CommandKind::AssertReturn { action, expected } => {
+                let (outcome, mock_prover) = self.assert_return(&action, &expected);
+
+                ("assert_return", outcome, mock_prover)
+            }
+            CommandKind::AssertTrap { action, message } => {
+                ("assert_trap", self.assert_trap(&action, &message), None)
+            }
+            CommandKind::AssertExhaustion { action, message } => (
+                "assert_exhaustion",
+                self.assert_trap(&action, &message),
+                None,
+            ),
+            CommandKind::AssertInvalid { module, .. } => {
+                let outcome = match wasmi::Module::from_buffer(module.into_vec()) {
+                    Ok(_) => Outcome::Fail("the module is valid".to_owned()),
+                    Err(_) => Outcome::Pass,
+                };
+
+                ("assert_invalid", outcome, None)
+            }
+            CommandKind::AssertMalformed { module, .. } => {
+                let outcome = match wasmi::Module::from_buffer(module.into_vec()) {
+                    Ok(_) => Outcome::Fail("the module is well-formed".to_owned()),
+                    Err(_) => Outcome::Pass,
+                };
+
+                ("assert_malformed", outcome, None)
+            }
+            CommandKind::PerformAction(action) => {
+                let outcome = match self.invoke(&action) {
+                    Ok((Ok(_), _)) => Outcome::Pass,
+                    Ok((Err(e), _)) => Outcome::Fail(format!("unexpected error {}", e)),
+                    Err(outcome) => outcome,
+                };
 
-        #[test]
-        fn test_spec_i32() {
-            run_spec_test("i32").unwrap()
+                ("invoke", outcome, None)
+            }
+            CommandKind::AssertReturnCanonicalNan { .. } => (
+                "assert_return_canonical_nan",
+                Outcome::Unsupported("float results".to_owned()),
+                None,
+            ),
+            CommandKind::AssertReturnArithmeticNan { .. } => (
+                "assert_return_arithmetic_nan",
+                Outcome::Unsupported("float results".to_owned()),
+                None,
+            ),
+            CommandKind::AssertUninstantiable { .. } => (
+                "assert_uninstantiable",
+                Outcome::Unsupported("instantiation failures".to_owned()),
+                None,
+            ),
+            CommandKind::AssertUnlinkable { .. } => (
+                "assert_unlinkable",
+                Outcome::Unsupported("linking".to_owned()),
+                None,
+            ),
+            CommandKind::Register { .. } => {
+                ("register", Outcome::Unsupported("linking".to_owned()), None)
+            }
         }
     }
-    */
-*/
+}
+
+/// Runs all directives of the script `source`, the mock prover is skipped unless `mock`.
+pub(crate) fn run_spec(name: &str, source: &str, mock: bool) -> Result<SpecReport> {
+    let mut features = Features::new();
+    features.enable_sign_extension();
+
+    let mut parser = ScriptParser::<f32, f64>::from_source_and_name_with_features(
+        source.as_bytes(),
+        &format!("{}.wast", name),
+        features,
+    )?;
+
+    let mut runner = SpecRunner {
+        mock,
+        modules: HashMap::new(),
+        current: None,
+    };
+    let mut directives = vec![];
+
+    while let Some(command) = parser.next()? {
+        let (directive, interpreter, mock_prover) = runner.run(command.kind);
+
+        directives.push(DirectiveReport {
+            line: command.line,
+            directive,
+            interpreter,
+            mock_prover,
+        });
+    }
+
+    Ok(SpecReport {
+        name: name.to_owned(),
+        directives,
+    })
+}
+
+mod tests {
+    use super::run_spec;
+    use super::Outcome;
+
+    #[test]
+    fn test_spec_i32() {
+        let source = std::fs::read_to_string("src/test/spec/i32.wast").unwrap();
+        let report = run_spec("i32", &source, true).unwrap();
+
+        assert!(report.failures().is_empty(), "{}", report);
+        assert!(report.count(|outcome| *outcome == Outcome::Pass) > 0);
+    }
+
+    #[test]
+    fn test_spec_mock_prover() {
+        let source = r#"
+            (module
+              (func (export "add") (param i32 i32) (result i32) (i32.add (local.get 0) (local.get 1)))
+              (func (export "div_s") (param i64 i64) (result i64) (i64.div_s (local.get 0) (local.get 1)))
+            )
+
+            (assert_return (invoke "add" (i32.const 1) (i32.const 2)) (i32.const 3))
+            (assert_return (invoke "div_s" (i64.const -7) (i64.const 2)) (i64.const -3))
+            (assert_trap (invoke "div_s" (i64.const 1) (i64.const 0)) "integer divide by zero")
+            (assert_invalid (module (func (result i32) (i64.const 0))) "type mismatch")
+        "#;
+
+        let report = run_spec("mock", source, true).unwrap();
+
+        assert!(report.failures().is_empty(), "{}", report);
+        assert_eq!(
+            report
+                .directives
+                .iter()
+                .filter(|report| report.mock_prover == Some(Outcome::Pass))
+                .count(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_spec_trap_message() {
+        let source = r#"
+            (module
+              (func (export "div_s") (param i32 i32) (result i32) (i32.div_s (local.get 0) (local.get 1)))
+            )
+
+            (assert_trap (invoke "div_s" (i32.const 1) (i32.const 0)) "integer divide by zero")
+            (assert_trap (invoke "div_s" (i32.const 1) (i32.const 0)) "integer overflow")
+            (assert_trap (invoke "div_s" (i32.const 1) (i32.const 0)) "unknown trap")
+        "#;
+
+        let report = run_spec("trap", source, false).unwrap();
+
+        let outcomes = report
+            .directives
+            .iter()
+            .map(|report| report.interpreter.clone())
+            .collect::<Vec<_>>();
+
+        assert_eq!(outcomes[1], Outcome::Pass);
+        assert!(matches!(outcomes[2], Outcome::Fail(_)));
+        assert!(matches!(outcomes[3], Outcome::Unsupported(_)));
+    }
+}
@@ -0,0 +1,106 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::circuits::bit_table::STEP_SIZE;
+    use crate::circuits::config::CircuitConfig;
+    use crate::circuits::config::MAX_K;
+    use crate::circuits::config::MIN_K;
+    use crate::circuits::estimate::HostRounds;
+    use crate::circuits::estimate::TableKind;
+    use crate::loader::err::ConfigErr;
+    use crate::loader::err::Error;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    #[test]
+    fn test_estimate_rows() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (func $zkmain
+              (local i64)
+              (local.set 0 (call $wasm_input (i32.const 1)))
+              (drop (i64.and (local.get 0) (i64.const 3)))
+              (drop (i64.popcnt (local.get 0)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])
+                .unwrap();
+
+        let arg = ExecutionArg {
+            public_inputs: vec![7],
+            private_inputs: vec![].into(),
+            context_inputs: vec![],
+            context_outputs: Arc::new(Mutex::new(vec![])),
+        };
+
+        let estimate = loader.estimate(arg, ()).unwrap();
+
+        let rows_of = |table: TableKind| {
+            estimate
+                .tables
+                .iter()
+                .find(|rows| rows.table == table)
+                .unwrap()
+                .required_rows
+        };
+
+        assert!(rows_of(TableKind::EventTable) > 0);
+        assert_eq!(rows_of(TableKind::WasmInputHelperTable), 2);
+        assert_eq!(rows_of(TableKind::BitTable), 2 * STEP_SIZE);
+        assert!(estimate.overflowed_tables(MIN_K).is_empty());
+        assert_eq!(estimate.minimal_k(), Some(MIN_K));
+
+        // The rounds of host circuits are part of the search of K.
+        let mut estimate = estimate;
+        estimate.host_rounds.push(HostRounds {
+            plugin: "foreign_round".to_string(),
+            used_rounds: 2,
+            max_rounds: (MIN_K..=MAX_K)
+                .map(|k| (k, (k - MIN_K + 1) as usize))
+                .collect(),
+        });
+
+        assert_eq!(estimate.overflowed_host_rounds(MIN_K).len(), 1);
+        assert_eq!(estimate.minimal_k(), Some(MIN_K + 1));
+
+        estimate.host_rounds[0].used_rounds = 100;
+        assert_eq!(estimate.minimal_k(), None);
+    }
+
+    #[test]
+    fn test_k_out_of_range() {
+        assert!(CircuitConfig::new(MIN_K).is_ok());
+        assert!(CircuitConfig::new(MAX_K).is_ok());
+
+        let wasm = wabt::wat2wasm(r#"(module (func $zkmain) (export "zkmain" (func $zkmain)))"#)
+            .expect("failed to parse wat");
+
+        for k in [MIN_K - 1, MAX_K + 1] {
+            let error = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+                k,
+                wasm.clone(),
+                vec![],
+            )
+            .err()
+            .unwrap();
+
+            assert!(matches!(
+                error.downcast::<Error>().unwrap(),
+                Error::Config(ConfigErr::KOutOfRange { k: error_k, .. }) if error_k == k
+            ));
+        }
+    }
+}
@@ -0,0 +1,43 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Bn256;
+    use specs::manifest::InputManifest;
+
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    #[test]
+    fn test_run_with_manifest() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "require" (func $require (param i32)))
+
+            (func $zkmain
+              (call $require (i64.eq (call $wasm_input (i32.const 1)) (i64.const 42)))
+              (call $require (i64.eq (call $wasm_input (i32.const 0)) (i64.const 255)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])
+                .unwrap();
+
+        let inputs = InputManifest::from_json(
+            r#"{
+                "public": [{ "type": "u32", "value": 42 }],
+                "private": [{ "type": "u8", "value": 255 }]
+            }"#,
+        )
+        .unwrap()
+        .encode()
+        .unwrap();
+
+        loader.run(inputs.into(), (), true, false).unwrap();
+    }
+}
@@ -0,0 +1,343 @@
+mod tests {
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::loader::err::Error;
+    use crate::loader::err::PreCheckErr;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    fn precheck(textual_repr: &str, phantom_functions: Vec<String>) -> Vec<PreCheckErr> {
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(&textual_repr)
+            .expect("failed to parse wat")
+            .as_ref()
+            .to_vec();
+
+        precheck_wasm(wasm, phantom_functions)
+    }
+
+    fn precheck_wasm(wasm: Vec<u8>, phantom_functions: Vec<String>) -> Vec<PreCheckErr> {
+        match ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            18,
+            wasm,
+            phantom_functions,
+        ) {
+            Ok(_) => vec![],
+            Err(e) => match e.downcast::<Error>().unwrap() {
+                Error::PreCheck(errors) => errors,
+                _ => unreachable!(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_precheck_float() {
+        let textual_repr = r#"
+        (module
+            (func $zkmain
+              f32.const 1.0
+              drop
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        // Float instructions are lowered into the soft-float library before prechecking.
+        let errors = precheck(textual_repr, vec![]);
+
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_precheck_memory_pages() {
+        // The circuit of K = 18 addresses 16 pages of heap memory.
+        let textual_repr = r#"
+        (module
+            (memory $0 17)
+
+            (func $zkmain)
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let errors = precheck(textual_repr, vec![]);
+
+        assert!(matches!(
+            errors.as_slice(),
+            [PreCheckErr::InitMemoryPagesExceedLimit {
+                init_memory_pages: 17,
+                maximal_memory_pages_for_k: 16,
+            }]
+        ));
+
+        let textual_repr = r#"
+        (module
+            (memory $0 1 17)
+
+            (func $zkmain)
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let errors = precheck(textual_repr, vec![]);
+
+        assert!(matches!(
+            errors.as_slice(),
+            [PreCheckErr::MaximalMemoryPagesExceedLimit {
+                maximal_memory_pages: 17,
+                maximal_memory_pages_for_k: 16,
+            }]
+        ));
+
+        let textual_repr = r#"
+        (module
+            (memory $0 1 16)
+
+            (func $zkmain)
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        assert!(precheck(textual_repr, vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_precheck_simd() {
+        let textual_repr = r#"
+        (module
+            (func $zkmain
+              (drop (v128.const i32x4 0 1 2 3))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let mut features = wabt::Features::new();
+        features.enable_simd();
+        let wasm =
+            wabt::wat2wasm_with_features(textual_repr, features).expect("failed to parse wat");
+
+        let errors = precheck_wasm(wasm, vec![]);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            PreCheckErr::UnsupportedOpcode { opcode: 0xfd }
+        ));
+    }
+
+    #[test]
+    fn test_precheck_zkmain_signature() {
+        let textual_repr = r#"
+        (module
+            (func $zkmain (param i32)
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let errors = precheck(textual_repr, vec![]);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0], PreCheckErr::ZkmainTypeNotMatch));
+    }
+
+    #[test]
+    fn test_precheck_phantom_not_exists() {
+        let textual_repr = r#"
+        (module
+            (func $zkmain)
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let errors = precheck(textual_repr, vec!["foo".to_owned()]);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            errors[0],
+            PreCheckErr::PhantomFunctionNotExists { .. }
+        ));
+    }
+
+    #[test]
+    fn test_precheck_phantom_pure() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+
+            (func $square (param i32) (result i32)
+              (i32.mul (local.get 0) (local.get 0))
+            )
+
+            (func $search (param i32) (result i32)
+              (call $square (i32.load (local.get 0)))
+            )
+
+            (func $zkmain
+              (drop (call $search (i32.const 0)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let errors = precheck(textual_repr, vec!["search".to_owned()]);
+
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_precheck_phantom_side_effect() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (memory $0 1)
+            (global $g (mut i32) (i32.const 0))
+
+            (func $update
+              (global.set $g (i32.const 1))
+            )
+
+            (func $phantom_store
+              (i32.store (i32.const 0) (i32.const 1))
+            )
+
+            (func $phantom_call
+              (call $update)
+            )
+
+            (func $phantom_host
+              (drop (call $wasm_input (i32.const 1)))
+            )
+
+            (func $zkmain
+              (call $phantom_store)
+              (call $phantom_call)
+              (call $phantom_host)
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let errors = precheck(textual_repr, vec!["phantom_.*".to_owned()]);
+
+        assert_eq!(errors.len(), 3);
+
+        let offending = errors
+            .iter()
+            .map(|error| match error {
+                PreCheckErr::PhantomFunctionHasSideEffect {
+                    phantom_function,
+                    function_name,
+                    ..
+                } => (phantom_function.as_str(), function_name.as_deref()),
+                e => panic!("unexpected error {:?}", e),
+            })
+            .collect::<Vec<_>>();
+
+        assert!(offending.contains(&("phantom_store", Some("phantom_store"))));
+        assert!(offending.contains(&("phantom_call", Some("update"))));
+        assert!(offending.contains(&("phantom_host", Some("phantom_host"))));
+    }
+
+    #[test]
+    fn test_precheck_phantom_indirect_host_call() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (type $input (func (param i32) (result i64)))
+            (table 1 funcref)
+            (elem (i32.const 0) $wasm_input)
+
+            (func $phantom_indirect
+              (drop (call_indirect (type $input) (i32.const 1) (i32.const 0)))
+            )
+
+            (func $zkmain
+              (call $phantom_indirect)
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let errors = precheck(textual_repr, vec!["phantom_.*".to_owned()]);
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            PreCheckErr::PhantomFunctionHasSideEffect { phantom_function, .. }
+                if phantom_function == "phantom_indirect"
+        ));
+    }
+
+    #[test]
+    fn test_precheck_phantom_trap() {
+        let textual_repr = r#"
+        (module
+            (func $fail
+              unreachable
+            )
+
+            (func $phantom_check (param i32)
+              (if (local.get 0) (then (call $fail)))
+            )
+
+            (func $zkmain
+              (call $phantom_check (i32.const 0))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(&textual_repr)
+            .expect("failed to parse wat")
+            .as_ref()
+            .to_vec();
+
+        let mut loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            18,
+            wasm,
+            vec!["phantom_.*".to_owned()],
+        )
+        .unwrap();
+
+        // A phantom function may trap if traps are not provable.
+        let errors = match loader
+            .set_provable_trap(true)
+            .unwrap_err()
+            .downcast::<Error>()
+            .unwrap()
+        {
+            Error::PreCheck(errors) => errors,
+            _ => unreachable!(),
+        };
+
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(
+            &errors[0],
+            PreCheckErr::PhantomFunctionTraps {
+                phantom_function,
+                function_name: Some(function_name),
+                ..
+            } if phantom_function == "phantom_check" && function_name == "fail"
+        ));
+
+        loader.set_provable_trap(false).unwrap();
+    }
+}
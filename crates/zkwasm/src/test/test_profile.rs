@@ -0,0 +1,68 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::loader::ZkWasmLoader;
+    use crate::profile::Profiler;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    #[test]
+    fn test_profile_functions() {
+        let textual_repr = r#"
+        (module
+            (func $add (param i32 i32) (result i32)
+              (i32.add (local.get 0) (local.get 1))
+            )
+
+            (func $zkmain
+              (drop (call $add (i32.const 1) (i32.const 2)))
+              (drop (call $add (i32.const 3) (i32.const 4)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])
+                .unwrap();
+
+        let arg = ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![].into(),
+            context_inputs: vec![],
+            context_outputs: Arc::new(Mutex::new(vec![])),
+        };
+
+        let result = loader.run(arg, (), false, false).unwrap();
+        let report = result.tables.profile_functions();
+
+        assert_eq!(
+            report.etable_entries,
+            result.tables.execution_tables.etable.entries().len()
+        );
+        assert_eq!(report.etable_entries, report.stacks.values().sum::<usize>());
+        assert_eq!(
+            report.etable_entries,
+            report
+                .functions
+                .iter()
+                .map(|function| function.etable_entries)
+                .sum::<usize>()
+        );
+
+        assert_eq!(report.functions.len(), 2);
+        assert_eq!(report.stacks.len(), 2);
+        assert!(report.functions.iter().any(|function| function.calls == 2));
+        assert!(report.stacks.keys().any(|stack| stack.contains(';')));
+
+        let mut folded = vec![];
+        report.write_folded(&mut folded).unwrap();
+        assert_eq!(String::from_utf8(folded).unwrap().lines().count(), 2);
+    }
+}
@@ -159,7 +159,7 @@ fn build_circuit() -> Result<(
         .run(
             ExecutionArg {
                 public_inputs,
-                private_inputs,
+                private_inputs: private_inputs.into(),
                 context_inputs: vec![],
                 context_outputs: Arc::new(Mutex::new(vec![])),
             },
@@ -0,0 +1,157 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::loader::err::Error;
+    use crate::loader::err::RuntimeErr;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+    use crate::runtime::host::ForeignError;
+
+    fn run(textual_repr: &str, dryrun: bool) -> RuntimeErr {
+        let wasm = wabt::Wat2Wasm::new()
+            .write_debug_names(true)
+            .convert(&textual_repr)
+            .expect("failed to parse wat");
+
+        let loader = ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(
+            18,
+            wasm.as_ref().to_vec(),
+            vec![],
+        )
+        .unwrap();
+
+        let arg = ExecutionArg {
+            public_inputs: vec![1],
+            private_inputs: vec![].into(),
+            context_inputs: vec![],
+            context_outputs: Arc::new(Mutex::new(vec![])),
+        };
+
+        match loader.run(arg, (), dryrun, false) {
+            Err(e) => match e.downcast::<Error>().unwrap() {
+                Error::Runtime(e) => e,
+                e => panic!("unexpected error {:?}", e),
+            },
+            Ok(_) => panic!("execution should trap"),
+        }
+    }
+
+    #[test]
+    fn test_require_failed() {
+        let textual_repr = r#"
+        (module
+            (import "env" "require" (func $require (param i32)))
+
+            (func $check (param i32)
+              (call $require (local.get 0))
+            )
+
+            (func $zkmain
+              (call $check (i32.const 1))
+              (call $check (i32.const 0))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        match run(textual_repr, false) {
+            RuntimeErr::ForeignTrap {
+                function,
+                error,
+                backtrace,
+            } => {
+                assert_eq!(function, "require");
+                assert_eq!(error, ForeignError::RequireFailed);
+
+                let backtrace = backtrace.unwrap();
+                assert_eq!(backtrace.0.len(), 2);
+                assert_ne!(backtrace.0[0].location.fid, backtrace.0[1].location.fid);
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_input_exhausted() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (func $zkmain
+              (drop (call $wasm_input (i32.const 1)))
+              (drop (call $wasm_input (i32.const 1)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        match run(textual_repr, true) {
+            RuntimeErr::ForeignTrap {
+                function,
+                error,
+                backtrace,
+            } => {
+                assert_eq!(function, "wasm_input");
+                assert_eq!(error, ForeignError::InputExhausted { is_public: true });
+                assert!(backtrace.is_none());
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_context_underflow() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_read_context" (func $wasm_read_context (result i64)))
+
+            (func $zkmain
+              (drop (call $wasm_read_context))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        match run(textual_repr, true) {
+            RuntimeErr::ForeignTrap { error, .. } => {
+                assert_eq!(error, ForeignError::ContextUnderflow)
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+
+    #[test]
+    fn test_trap_backtrace() {
+        let textual_repr = r#"
+        (module
+            (func $div (param i32 i32) (result i32)
+              (i32.div_u (local.get 0) (local.get 1))
+            )
+
+            (func $zkmain
+              (drop (call $div (i32.const 1) (i32.const 1)))
+              (drop (call $div (i32.const 1) (i32.const 0)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        match run(textual_repr, false) {
+            RuntimeErr::Trap { backtrace, .. } => {
+                let backtrace = backtrace.unwrap();
+
+                assert_eq!(backtrace.0.len(), 2);
+                assert!(backtrace.0[0].instruction.as_ref().unwrap().contains("Div"));
+            }
+            e => panic!("unexpected error {:?}", e),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use halo2_proofs::pairing::bn256::Bn256;
+    use halo2_proofs::pairing::bn256::Fr;
+    use halo2_proofs::pairing::bn256::G1Affine;
+    use halo2_proofs::poly::commitment::Params;
+
+    use crate::circuits::config::MIN_K;
+    use crate::loader::session::ZkWasmSession;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    #[test]
+    fn test_session_prove_repeatedly() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+
+            (func $zkmain
+              (call $wasm_output (i64.mul (call $wasm_input (i32.const 1)) (i64.const 2)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(MIN_K, wasm, vec![])
+                .unwrap();
+        let params = Params::<G1Affine>::unsafe_setup::<Bn256>(MIN_K);
+        let session = ZkWasmSession::new(loader, params, ()).unwrap();
+
+        for input in [3, 5] {
+            let proof = session
+                .prove(ExecutionArg {
+                    public_inputs: vec![input],
+                    private_inputs: vec![].into(),
+                    context_inputs: vec![],
+                    context_outputs: Arc::new(Mutex::new(vec![])),
+                })
+                .unwrap();
+
+            assert_eq!(proof.outputs, vec![input * 2]);
+            assert_eq!(proof.instances, vec![Fr::from(input), Fr::from(input * 2)]);
+            session.verify(&proof).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_run_with_compilation_tables() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+            (import "env" "wasm_output" (func $wasm_output (param i64)))
+
+            (func $zkmain
+              (call $wasm_output (i64.add (call $wasm_input (i32.const 1)) (i64.const 1)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(MIN_K, wasm, vec![])
+                .unwrap();
+        let arg = || ExecutionArg {
+            public_inputs: vec![7],
+            private_inputs: vec![].into(),
+            context_inputs: vec![],
+            context_outputs: Arc::new(Mutex::new(vec![])),
+        };
+
+        let compiled = loader.run(arg(), (), false, false).unwrap();
+        let cached = loader
+            .run_with_compilation_tables(arg(), (), &compiled.tables.compilation_tables)
+            .unwrap();
+
+        assert_eq!(cached.public_inputs_and_outputs, vec![7, 8]);
+        assert_eq!(
+            cached.public_inputs_and_outputs,
+            compiled.public_inputs_and_outputs
+        );
+        assert_eq!(
+            cached.tables.execution_tables.etable.entries().len(),
+            compiled.tables.execution_tables.etable.entries().len()
+        );
+    }
+}
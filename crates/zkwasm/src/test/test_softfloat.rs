@@ -0,0 +1,533 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use anyhow::Result;
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    const F32_CANONICAL_NAN: u32 = 0x7fc00000;
+    const F64_CANONICAL_NAN: u64 = 0x7ff8000000000000;
+
+    /// Zeros, ones, ties, the smallest and largest normal and subnormal numbers, infinities and
+    /// quiet and signaling NaNs.
+    const F32_VALUES: [u32; 24] = [
+        0x00000000, 0x80000000, 0x3f800000, 0xbf800000, 0x3dcccccd, 0x3fc00000, 0x40200000,
+        0xc0200000, 0x00800000, 0x00000001, 0x007fffff, 0x80000001, 0x7f7fffff, 0xff7fffff,
+        0x7f800000, 0xff800000, 0x7fc00000, 0xffc00001, 0x7f800001, 0x4b800000, 0x3effffff,
+        0x3f000000, 0xbf000000, 0x40400000,
+    ];
+    const F64_VALUES: [u64; 24] = [
+        0x0000000000000000,
+        0x8000000000000000,
+        0x3ff0000000000000,
+        0xbff0000000000000,
+        0x3fb999999999999a,
+        0x3ff8000000000000,
+        0x4004000000000000,
+        0xc004000000000000,
+        0x0010000000000000,
+        0x0000000000000001,
+        0x000fffffffffffff,
+        0x8000000000000001,
+        0x7fefffffffffffff,
+        0xffefffffffffffff,
+        0x7ff0000000000000,
+        0xfff0000000000000,
+        0x7ff8000000000000,
+        0xfff8000000000001,
+        0x7ff0000000000001,
+        0x4340000000000000,
+        0x3fdfffffffffffff,
+        0x3fe0000000000000,
+        0xbfe0000000000000,
+        0x4008000000000000,
+    ];
+
+    fn execution_arg() -> ExecutionArg {
+        ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![].into(),
+            context_inputs: vec![],
+            context_outputs: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    fn run_mock(textual_repr: &str) {
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])
+                .unwrap();
+
+        let result = loader.run(execution_arg(), (), false, true).unwrap();
+
+        let (circuit, instances) = loader.circuit_with_witness(result).unwrap();
+
+        loader.mock_test(&circuit, &instances).unwrap()
+    }
+
+    /// Executes the checks in dry-run mode, the execution traps if any check fails.
+    fn run_checks(checks: &[String]) -> Result<()> {
+        let textual_repr = format!(
+            r#"
+        (module
+            (func $check32 (param i32 i32)
+              (if (i32.ne (local.get 0) (local.get 1))
+                (then unreachable))
+            )
+
+            (func $check64 (param i64 i64)
+              (if (i64.ne (local.get 0) (local.get 1))
+                (then unreachable))
+            )
+
+            (func $zkmain
+              {}
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#,
+            checks.join("\n")
+        );
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])
+                .unwrap();
+
+        loader.run(execution_arg(), (), true, false).map(|_| ())
+    }
+
+    fn f32_const(bits: u32) -> String {
+        format!("(f32.reinterpret_i32 (i32.const {}))", bits as i32)
+    }
+
+    fn f64_const(bits: u64) -> String {
+        format!("(f64.reinterpret_i64 (i64.const {}))", bits as i64)
+    }
+
+    /// Check the f32 `expr` against `expected`, whose NaN is expected to be canonical.
+    fn check_f32(expr: String, expected: f32) -> String {
+        let expected = if expected.is_nan() {
+            F32_CANONICAL_NAN
+        } else {
+            expected.to_bits()
+        };
+
+        format!(
+            "(call $check32 (i32.reinterpret_f32 {}) (i32.const {}))",
+            expr, expected as i32
+        )
+    }
+
+    /// Check the f64 `expr` against `expected`, whose NaN is expected to be canonical.
+    fn check_f64(expr: String, expected: f64) -> String {
+        let expected = if expected.is_nan() {
+            F64_CANONICAL_NAN
+        } else {
+            expected.to_bits()
+        };
+
+        format!(
+            "(call $check64 (i64.reinterpret_f64 {}) (i64.const {}))",
+            expr, expected as i64
+        )
+    }
+
+    /// `min` of wasm, which propagates NaN and orders -0 below +0 unlike `f64::min`.
+    fn wasm_min(a: f64, b: f64) -> f64 {
+        if a.is_nan() || b.is_nan() {
+            f64::NAN
+        } else if a == 0.0 && b == 0.0 {
+            if a.is_sign_negative() {
+                a
+            } else {
+                b
+            }
+        } else {
+            a.min(b)
+        }
+    }
+
+    /// `max` of wasm, which propagates NaN and orders -0 below +0 unlike `f64::max`.
+    fn wasm_max(a: f64, b: f64) -> f64 {
+        if a.is_nan() || b.is_nan() {
+            f64::NAN
+        } else if a == 0.0 && b == 0.0 {
+            if a.is_sign_positive() {
+                a
+            } else {
+                b
+            }
+        } else {
+            a.max(b)
+        }
+    }
+
+    /// `nearest` of wasm, which rounds ties to even unlike `f64::round`.
+    fn wasm_nearest(a: f64) -> f64 {
+        let rounded = a.round();
+
+        if (rounded - a).abs() == 0.5 {
+            (a / 2.0).round() * 2.0
+        } else {
+            rounded
+        }
+    }
+
+    /// A deterministic xorshift sequence of bit patterns.
+    fn random_bits(count: usize) -> Vec<u64> {
+        let mut state = 0x2545f4914f6cdd1du64;
+
+        (0..count)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_softfloat_f64_mock() {
+        let textual_repr = r#"
+        (module
+            (global $x (mut f64) (f64.const 0.1))
+
+            (func $check (param i64 i64)
+              (if (i64.ne (local.get 0) (local.get 1))
+                (then unreachable))
+            )
+
+            (func $add (param f64 f64) (result f64)
+              (f64.add (local.get 0) (local.get 1))
+            )
+
+            (func $zkmain
+              (local $y f64)
+              (local.set $y (f64.const 0.2))
+
+              (call $check
+                (i64.reinterpret_f64 (call $add (global.get $x) (local.get $y)))
+                (i64.const 0x3fd3333333333334))
+              (call $check
+                (i64.reinterpret_f64 (f64.div (f64.const 1) (f64.const 3)))
+                (i64.const 0x3fd5555555555555))
+              (call $check
+                (i64.reinterpret_f64 (f64.sqrt (f64.const 2)))
+                (i64.const 0x3ff6a09e667f3bcd))
+              (call $check
+                (i64.reinterpret_f64 (f64.min (f64.const -0) (f64.const 0)))
+                (i64.const 0x8000000000000000))
+              (call $check
+                (i64.reinterpret_f64 (f64.nearest (f64.const 2.5)))
+                (i64.const 0x4000000000000000))
+              (call $check
+                (i64.reinterpret_f64 (f64.convert_i64_u (i64.const -1)))
+                (i64.const 0x43f0000000000000))
+              (call $check
+                (i64.extend_i32_s (i32.trunc_f64_s (f64.const -3.7)))
+                (i64.const -3))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        run_mock(textual_repr);
+    }
+
+    #[test]
+    fn test_softfloat_f32_mock() {
+        let textual_repr = r#"
+        (module
+            (memory 1)
+
+            (func $check (param i32 i32)
+              (if (i32.ne (local.get 0) (local.get 1))
+                (then unreachable))
+            )
+
+            (func $zkmain
+              (f32.store (i32.const 0) (f32.const 1.1))
+
+              (call $check
+                (i32.reinterpret_f32 (f32.mul (f32.load (i32.const 0)) (f32.const 1.1)))
+                (i32.const 0x3f9ae148))
+              (call $check
+                (i32.reinterpret_f32 (f32.demote_f64 (f64.const 0.1)))
+                (i32.const 0x3dcccccd))
+              (call $check
+                (f32.lt (f32.const 1) (f32.const 2))
+                (i32.const 1))
+              (call $check
+                (i32.reinterpret_f32
+                  (block (result f32) (f32.convert_i32_s (i32.const -7))))
+                (i32.const 0xc0e00000))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        run_mock(textual_repr);
+    }
+
+    #[test]
+    fn test_softfloat_f32_arithmetic() {
+        let mut checks = vec![];
+
+        for a in F32_VALUES {
+            let (x, y) = (f32_const(a), f32::from_bits(a));
+
+            checks.push(check_f32(format!("(f32.sqrt {})", x), y.sqrt()));
+
+            for b in F32_VALUES {
+                let (x2, y2) = (f32_const(b), f32::from_bits(b));
+
+                checks.push(check_f32(format!("(f32.add {} {})", x, x2), y + y2));
+                checks.push(check_f32(format!("(f32.sub {} {})", x, x2), y - y2));
+                checks.push(check_f32(format!("(f32.mul {} {})", x, x2), y * y2));
+                checks.push(check_f32(format!("(f32.div {} {})", x, x2), y / y2));
+            }
+        }
+
+        run_checks(&checks).unwrap();
+    }
+
+    #[test]
+    fn test_softfloat_f64_arithmetic() {
+        let mut checks = vec![];
+
+        for a in F64_VALUES {
+            let (x, y) = (f64_const(a), f64::from_bits(a));
+
+            checks.push(check_f64(format!("(f64.sqrt {})", x), y.sqrt()));
+
+            for b in F64_VALUES {
+                let (x2, y2) = (f64_const(b), f64::from_bits(b));
+
+                checks.push(check_f64(format!("(f64.add {} {})", x, x2), y + y2));
+                checks.push(check_f64(format!("(f64.sub {} {})", x, x2), y - y2));
+                checks.push(check_f64(format!("(f64.mul {} {})", x, x2), y * y2));
+                checks.push(check_f64(format!("(f64.div {} {})", x, x2), y / y2));
+            }
+        }
+
+        run_checks(&checks).unwrap();
+    }
+
+    #[test]
+    fn test_softfloat_min_max() {
+        let mut checks = vec![];
+
+        for a in F32_VALUES {
+            for b in F32_VALUES {
+                let (x, x2) = (f32_const(a), f32_const(b));
+                let (y, y2) = (f32::from_bits(a) as f64, f32::from_bits(b) as f64);
+
+                checks.push(check_f32(
+                    format!("(f32.min {} {})", x, x2),
+                    wasm_min(y, y2) as f32,
+                ));
+                checks.push(check_f32(
+                    format!("(f32.max {} {})", x, x2),
+                    wasm_max(y, y2) as f32,
+                ));
+            }
+        }
+
+        for a in F64_VALUES {
+            for b in F64_VALUES {
+                let (x, x2) = (f64_const(a), f64_const(b));
+                let (y, y2) = (f64::from_bits(a), f64::from_bits(b));
+
+                checks.push(check_f64(
+                    format!("(f64.min {} {})", x, x2),
+                    wasm_min(y, y2),
+                ));
+                checks.push(check_f64(
+                    format!("(f64.max {} {})", x, x2),
+                    wasm_max(y, y2),
+                ));
+            }
+        }
+
+        run_checks(&checks).unwrap();
+    }
+
+    #[test]
+    fn test_softfloat_rounding() {
+        let mut checks = vec![];
+
+        // Ties in both directions and the largest values with a fraction.
+        let f32_values = F32_VALUES
+            .into_iter()
+            .chain([0x40600000, 0xc0600000, 0x4b7fffff, 0xcb7fffff]);
+        let f64_values = F64_VALUES.into_iter().chain([
+            0x400c000000000000,
+            0xc00c000000000000,
+            0x432fffffffffffff,
+            0xc32fffffffffffff,
+        ]);
+
+        for a in f32_values {
+            let (x, y) = (f32_const(a), f32::from_bits(a));
+
+            checks.push(check_f32(
+                format!("(f32.nearest {})", x),
+                wasm_nearest(y as f64) as f32,
+            ));
+            checks.push(check_f32(format!("(f32.floor {})", x), y.floor()));
+            checks.push(check_f32(format!("(f32.ceil {})", x), y.ceil()));
+            checks.push(check_f32(format!("(f32.trunc {})", x), y.trunc()));
+        }
+
+        for a in f64_values {
+            let (x, y) = (f64_const(a), f64::from_bits(a));
+
+            checks.push(check_f64(format!("(f64.nearest {})", x), wasm_nearest(y)));
+            checks.push(check_f64(format!("(f64.floor {})", x), y.floor()));
+            checks.push(check_f64(format!("(f64.ceil {})", x), y.ceil()));
+            checks.push(check_f64(format!("(f64.trunc {})", x), y.trunc()));
+        }
+
+        run_checks(&checks).unwrap();
+    }
+
+    #[test]
+    fn test_softfloat_f32_double_rounding() {
+        // f32 operations are computed in f64 and rounded back, the double rounding gives the
+        // correctly rounded f32 result since f64 keeps more than 2 * 24 + 2 significand bits.
+        let bits = random_bits(2000);
+        let values = bits
+            .iter()
+            .map(|bits| f32::from_bits(*bits as u32))
+            .filter(|value| value.is_finite())
+            .collect::<Vec<_>>();
+
+        for (a, b) in values.iter().zip(values.iter().skip(1)) {
+            assert_eq!(
+                ((*a as f64) / (*b as f64)) as f32,
+                a / b,
+                "{:e} / {:e}",
+                a,
+                b
+            );
+            assert_eq!(
+                ((*a as f64) * (*b as f64)) as f32,
+                a * b,
+                "{:e} * {:e}",
+                a,
+                b
+            );
+            assert_eq!(
+                ((*a as f64) + (*b as f64)) as f32,
+                a + b,
+                "{:e} + {:e}",
+                a,
+                b
+            );
+        }
+
+        for a in values.iter().filter(|value| **value >= 0.0) {
+            assert_eq!((*a as f64).sqrt() as f32, a.sqrt(), "sqrt {:e}", a);
+        }
+
+        let mut checks = vec![];
+
+        for (a, b) in values.iter().zip(values.iter().skip(1)).take(200) {
+            let (x, x2) = (f32_const(a.to_bits()), f32_const(b.to_bits()));
+
+            checks.push(check_f32(format!("(f32.div {} {})", x, x2), a / b));
+            checks.push(check_f32(format!("(f32.sqrt {})", x), a.sqrt()));
+        }
+
+        run_checks(&checks).unwrap();
+    }
+
+    #[test]
+    fn test_softfloat_trunc_overflow_trap() {
+        // The instruction and the range of the truncated value, the upper bound is exclusive.
+        let truncations = [
+            ("i32.trunc_f32_s", -2147483648.0, 2147483648.0),
+            ("i32.trunc_f64_s", -2147483648.0, 2147483648.0),
+            ("i32.trunc_f32_u", 0.0, 4294967296.0),
+            ("i32.trunc_f64_u", 0.0, 4294967296.0),
+            (
+                "i64.trunc_f32_s",
+                -9223372036854775808.0,
+                9223372036854775808.0,
+            ),
+            (
+                "i64.trunc_f64_s",
+                -9223372036854775808.0,
+                9223372036854775808.0,
+            ),
+            ("i64.trunc_f32_u", 0.0, 18446744073709551616.0),
+            ("i64.trunc_f64_u", 0.0, 18446744073709551616.0),
+        ];
+        let values = [
+            2147483647.9,
+            2147483648.0,
+            -2147483648.9,
+            -2147483649.0,
+            -0.9,
+            -1.0,
+            -0.0,
+            4294967295.9,
+            4294967296.0,
+            9223372036854774784.0,
+            9223372036854775808.0,
+            -9223372036854775808.0,
+            -9223372036854777856.0,
+            18446744073709549568.0,
+            18446744073709551616.0,
+            f64::from_bits(1),
+            f64::NAN,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ];
+
+        for (instruction, min, max) in truncations {
+            for value in values {
+                let (operand, value) = if instruction.contains("f32") {
+                    (f32_const((value as f32).to_bits()), value as f32 as f64)
+                } else {
+                    (f64_const(value.to_bits()), value)
+                };
+
+                let truncated = value.trunc();
+                let expected = (truncated >= min && truncated < max).then(|| truncated as i128);
+
+                let expr = format!("({} {})", instruction, operand);
+                let check = if instruction.starts_with("i32") {
+                    format!(
+                        "(call $check32 {} (i32.const {}))",
+                        expr,
+                        expected.unwrap_or(0) as i32
+                    )
+                } else {
+                    format!(
+                        "(call $check64 {} (i64.const {}))",
+                        expr,
+                        expected.unwrap_or(0) as i64
+                    )
+                };
+
+                let result = run_checks(&[check]);
+
+                match expected {
+                    Some(_) => result.unwrap(),
+                    None => assert!(result.is_err(), "{} {:e} must trap", instruction, value),
+                }
+            }
+        }
+    }
+}
@@ -41,7 +41,7 @@ mod tests {
 
         let arg = ExecutionArg {
             public_inputs: vec![],
-            private_inputs: vec![],
+            private_inputs: vec![].into(),
             context_inputs: vec![],
             context_outputs: Arc::new(Mutex::new(vec![])),
         };
@@ -0,0 +1,96 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use halo2_proofs::pairing::bn256::Bn256;
+
+    use crate::loader::err::Error;
+    use crate::loader::err::TraceErr;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    fn loader_of(textual_repr: &str) -> ZkWasmLoader<Bn256, ExecutionArg, DefaultHostEnvBuilder> {
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![]).unwrap()
+    }
+
+    fn trace_of(loader: &ZkWasmLoader<Bn256, ExecutionArg, DefaultHostEnvBuilder>) -> Vec<u8> {
+        let arg = ExecutionArg {
+            public_inputs: vec![],
+            private_inputs: vec![].into(),
+            context_inputs: vec![],
+            context_outputs: Arc::new(Mutex::new(vec![])),
+        };
+
+        let result = loader.run(arg, (), false, false).unwrap();
+
+        let mut trace = vec![];
+        loader.write_trace(&result, &mut trace).unwrap();
+        trace
+    }
+
+    const TEXTUAL_REPR: &str = r#"
+        (module
+            (memory $0 1)
+
+            (func $zkmain
+              (local $i i32)
+              (block
+                (loop
+                  (i32.store (i32.const 8) (local.get $i))
+                  (local.set $i (i32.add (local.get $i) (i32.const 1)))
+                  (br_if 1 (i32.ge_u (local.get $i) (i32.const 8)))
+                  (br 0)
+                )
+              )
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+    #[test]
+    fn test_trace_reprove_mock() {
+        let loader = loader_of(TEXTUAL_REPR);
+        let trace = trace_of(&loader);
+
+        let result = loader.load_trace((), &trace[..]).unwrap();
+        let (circuit, instances) = loader.circuit_with_witness(result).unwrap();
+
+        loader.mock_test(&circuit, &instances).unwrap()
+    }
+
+    #[test]
+    fn test_trace_image_mismatch() {
+        let trace = trace_of(&loader_of(TEXTUAL_REPR));
+
+        let other = loader_of(&TEXTUAL_REPR.replace("(i32.const 8)))", "(i32.const 9)))"));
+
+        match other.load_trace((), &trace[..]) {
+            Err(e) => assert!(matches!(
+                e.downcast::<Error>().unwrap(),
+                Error::Trace(TraceErr::ImageMismatch)
+            )),
+            Ok(_) => panic!("trace of another image should be rejected"),
+        }
+    }
+
+    #[test]
+    fn test_trace_unsupported_version() {
+        let loader = loader_of(TEXTUAL_REPR);
+        let mut trace = trace_of(&loader);
+
+        // The version follows the 8 bytes magic.
+        trace[8] = trace[8].wrapping_add(1);
+
+        match loader.load_trace((), &trace[..]) {
+            Err(e) => assert!(matches!(
+                e.downcast::<Error>().unwrap(),
+                Error::Trace(TraceErr::UnsupportedVersion { .. })
+            )),
+            Ok(_) => panic!("trace with unknown version should be rejected"),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+mod tests {
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    use halo2_proofs::dev::MockProver;
+    use halo2_proofs::pairing::bn256::Bn256;
+    use halo2_proofs::pairing::bn256::Fr;
+    use specs::trap::TrapCode;
+    use specs::trap::TrapOutcome;
+
+    use crate::circuits::utils::bn_to_field;
+    use crate::loader::ZkWasmLoader;
+    use crate::runtime::host::default_env::DefaultHostEnvBuilder;
+    use crate::runtime::host::default_env::ExecutionArg;
+
+    fn trapping_loader(
+        textual_repr: &str,
+    ) -> ZkWasmLoader<Bn256, ExecutionArg, DefaultHostEnvBuilder> {
+        let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+
+        let mut loader =
+            ZkWasmLoader::<Bn256, ExecutionArg, DefaultHostEnvBuilder>::new(18, wasm, vec![])
+                .unwrap();
+        loader.set_provable_trap(true).unwrap();
+
+        loader
+    }
+
+    fn execution_arg(public_inputs: Vec<u64>) -> ExecutionArg {
+        ExecutionArg {
+            public_inputs,
+            private_inputs: vec![].into(),
+            context_inputs: vec![],
+            context_outputs: Arc::new(Mutex::new(vec![])),
+        }
+    }
+
+    fn run_with_provable_trap(textual_repr: &str, public_inputs: Vec<u64>) -> TrapOutcome {
+        let loader = trapping_loader(textual_repr);
+
+        let result = loader
+            .run(execution_arg(public_inputs), (), false, false)
+            .unwrap();
+        let outcome = result.trap.unwrap();
+
+        let (circuit, instances) = loader.circuit_with_witness(result).unwrap();
+        assert_eq!(
+            instances.last(),
+            Some(&bn_to_field(&outcome.encode_instance()))
+        );
+        loader.mock_test(&circuit, &instances).unwrap();
+
+        outcome
+    }
+
+    #[test]
+    fn test_trap_unreachable_mock() {
+        let textual_repr = r#"
+        (module
+            (func $zkmain
+              (i32.const 1)
+              (drop)
+              (unreachable)
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let outcome = run_with_provable_trap(textual_repr, vec![]);
+
+        assert_eq!(outcome.code, TrapCode::Unreachable);
+        assert_eq!(outcome.iid, 2);
+    }
+
+    #[test]
+    fn test_trap_division_by_zero_mock() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (func $div (param i64) (result i64)
+              (i64.div_u (i64.const 1) (local.get 0))
+            )
+
+            (func $zkmain
+              (drop (call $div (i64.const 1)))
+              (drop (call $div (call $wasm_input (i32.const 1))))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let outcome = run_with_provable_trap(textual_repr, vec![0]);
+
+        assert_eq!(outcome.code, TrapCode::DivisionByZero);
+    }
+
+    #[test]
+    fn test_trap_i32_integer_overflow_mock() {
+        let textual_repr = r#"
+        (module
+            (func $zkmain
+              (drop (i32.rem_s (i32.const 0x80000000) (i32.const -1)))
+              (drop (i32.div_s (i32.const 0x80000000) (i32.const -1)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let outcome = run_with_provable_trap(textual_repr, vec![]);
+
+        assert_eq!(outcome.code, TrapCode::IntegerOverflow);
+    }
+
+    #[test]
+    fn test_trap_i64_integer_overflow_mock() {
+        let textual_repr = r#"
+        (module
+            (import "env" "wasm_input" (func $wasm_input (param i32) (result i64)))
+
+            (func $zkmain
+              (drop (i64.div_s (i64.const 0x8000000000000000) (i64.const 1)))
+              (drop (i64.div_s (i64.const 0x8000000000000000) (call $wasm_input (i32.const 1))))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let outcome = run_with_provable_trap(textual_repr, vec![u64::MAX]);
+
+        assert_eq!(outcome.code, TrapCode::IntegerOverflow);
+    }
+
+    #[test]
+    fn test_trap_out_of_bounds_mock() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+
+            (func $zkmain
+              (i64.store (i32.const 65532) (i64.const 0))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let outcome = run_with_provable_trap(textual_repr, vec![]);
+
+        assert_eq!(outcome.code, TrapCode::MemoryAccessOutOfBounds);
+    }
+
+    #[test]
+    fn test_trap_address_overflow_not_provable() {
+        let textual_repr = r#"
+        (module
+            (memory $0 1)
+
+            (func $zkmain
+              (drop (i32.load offset=0xffffffff (i32.const 1)))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        // The effective address overflows u32, the trap is out of bounds but not provable.
+        let loader = trapping_loader(textual_repr);
+        assert!(loader.run(execution_arg(vec![]), (), false, false).is_err());
+    }
+
+    #[test]
+    fn test_trap_require_failed_mock() {
+        let textual_repr = r#"
+        (module
+            (import "env" "require" (func $require (param i32)))
+
+            (func $zkmain
+              (call $require (i32.const 1))
+              (call $require (i32.const 0))
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let outcome = run_with_provable_trap(textual_repr, vec![]);
+
+        assert_eq!(outcome.code, TrapCode::RequireFailed);
+    }
+
+    #[test]
+    fn test_trap_rejected_without_provable_trap_mock() {
+        let textual_repr = r#"
+        (module
+            (func $zkmain
+              (unreachable)
+            )
+
+            (export "zkmain" (func $zkmain))
+           )
+        "#;
+
+        let mut loader = trapping_loader(textual_repr);
+        let result = loader.run(execution_arg(vec![]), (), false, false).unwrap();
+        assert!(result.trap.is_some());
+
+        // The same witness is rejected by the circuit which doesn't accept traps.
+        loader.set_provable_trap(false).unwrap();
+        let (circuit, instances) = loader.circuit_with_witness(result).unwrap();
+        let prover = MockProver::<Fr>::run(18, &circuit, vec![instances]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
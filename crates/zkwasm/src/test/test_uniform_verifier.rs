@@ -1,3 +1,4 @@
+use crate::circuits::config::CircuitConfig;
 use crate::circuits::ZkWasmCircuit;
 use crate::circuits::ZkWasmCircuitBuilder;
 use crate::runtime::host::host_env::HostEnv;
@@ -32,6 +33,7 @@ fn setup_uniform_verifier() -> Result<(Params<G1Affine>, ProvingKey<G1Affine>)>
     let execution_result = test_circuit_with_env(env, WasmRuntimeIO::empty(), wasm, "zkmain")?;
 
     let builder = ZkWasmCircuitBuilder {
+        circuit_config: CircuitConfig::new(K)?,
         tables: execution_result.tables,
         public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
     };
@@ -134,6 +136,7 @@ mod tests {
         let instances = vec![];
 
         let builder = ZkWasmCircuitBuilder {
+            circuit_config: CircuitConfig::new(K).unwrap(),
             tables: execution_result.tables,
             public_inputs_and_outputs: execution_result.public_inputs_and_outputs,
         };
@@ -2,10 +2,15 @@ use specs::external_host_call_table::ExternalHostCallSignature;
 use std::rc::Rc;
 use wasmi::tracer::Observer;
 
+use crate::circuits::config::MIN_K;
+use crate::loader::err::Error;
+use crate::loader::err::RuntimeErr;
 use crate::runtime::host::host_env::HostEnv;
 use crate::runtime::host::ForeignContext;
+use crate::runtime::host::ForeignError;
 use crate::runtime::host::ForeignStatics;
 use crate::runtime::wasmi_interpreter::WasmRuntimeIO;
+use crate::test::compile_then_execute_wasm;
 use crate::test::test_circuit_with_env;
 
 #[derive(Default)]
@@ -57,7 +62,7 @@ fn test_call_host_external() {
                     let value: u64 = args.nth(0);
                     context.acc += value;
 
-                    None
+                    Ok(None)
                 },
             ),
         );
@@ -70,7 +75,7 @@ fn test_call_host_external() {
                 |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
                     let context = context.downcast_mut::<Context>().unwrap();
 
-                    Some(wasmi::RuntimeValue::I64(context.acc as i64))
+                    Ok(Some(wasmi::RuntimeValue::I64(context.acc as i64)))
                 },
             ),
         );
@@ -83,3 +88,99 @@ fn test_call_host_external() {
     let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
     test_circuit_with_env(env, WasmRuntimeIO::empty(), wasm, "test").unwrap();
 }
+
+/// Each push consumes a round, the circuit of K supports `K - MIN_K + 1` rounds.
+struct RoundContext {
+    k: u32,
+    used_round: usize,
+}
+impl ForeignContext for RoundContext {
+    fn get_statics(&self) -> Option<ForeignStatics> {
+        Some(ForeignStatics {
+            used_round: self.used_round,
+            max_round: self.max_rounds(self.k).unwrap(),
+        })
+    }
+
+    fn max_rounds(&self, k: u32) -> Option<usize> {
+        Some((k - MIN_K + 1) as usize)
+    }
+
+    fn rounds_of_call(&self, _op: usize, _args: &wasmi::RuntimeArgs) -> usize {
+        1
+    }
+}
+
+#[test]
+fn test_call_host_rounds_exceeded() {
+    let textual_repr = r#"
+        (module
+            (type (;0;) (func (param i64)))
+            (type (;1;) (func))
+            (import "env" "foreign_round" (func (;0;) (type 0)))
+            (func (;1;) (type 1)
+              i64.const 0
+              call 0
+              i64.const 0
+              call 0
+              i64.const 0
+              call 0)
+            (memory (;0;) 1)
+            (export "memory" (memory 0))
+            (export "test" (func 1)))
+        "#;
+
+    let env = {
+        let mut env = HostEnv::new();
+
+        let foreign_round_plugin = env.external_env.register_plugin(
+            "foreign_round",
+            Box::new(RoundContext {
+                k: MIN_K,
+                used_round: 0,
+            }),
+        );
+        env.external_env.register_function(
+            "foreign_round",
+            0,
+            ExternalHostCallSignature::Argument,
+            foreign_round_plugin,
+            Rc::new(
+                |_obs: &Observer, context: &mut dyn ForeignContext, _args: wasmi::RuntimeArgs| {
+                    let context = context.downcast_mut::<RoundContext>().unwrap();
+
+                    // The call exceeding the budget is rejected before the callback.
+                    assert!(context.used_round < context.max_rounds(context.k).unwrap());
+                    context.used_round += 1;
+
+                    Ok(None)
+                },
+            ),
+        );
+
+        env.finalize();
+
+        env
+    };
+
+    let wasm = wabt::wat2wasm(&textual_repr).expect("failed to parse wat");
+    let error = compile_then_execute_wasm(env, WasmRuntimeIO::empty(), wasm, "test").unwrap_err();
+
+    match error.downcast::<Error>().unwrap() {
+        Error::Runtime(RuntimeErr::ForeignTrap {
+            function, error, ..
+        }) => {
+            assert_eq!(function, "foreign_round");
+            assert_eq!(
+                error,
+                ForeignError::RoundsExceeded {
+                    plugin: "foreign_round".to_string(),
+                    used_round: 2,
+                    max_round: 1,
+                    suggested_k: Some(MIN_K + 1),
+                }
+            );
+        }
+        e => panic!("unexpected error {:?}", e),
+    }
+}